@@ -0,0 +1,219 @@
+use crate::{
+    clock::{Clock, SystemClock},
+    collections::CollectionInfo,
+    config::{IdScheme, PublicField, RevealMode},
+    events::{EventKind, EventLog, NftEvent},
+    extras::NftExtras,
+    idempotency::{IdempotencyCache, DEFAULT_TTL_SECS},
+    limits::FieldLimits,
+    owners::OwnerIndex,
+    persist::Backend,
+    reservation::{Reservation, DEFAULT_RESERVATION_TTL_SECS},
+    reveal::PublicPolicy,
+    reveallog::RevealLog,
+    staking::{StakeInfo, DEFAULT_REWARD_RATE},
+    traitschema::AttributeSchema,
+    viewcache::ViewCache,
+};
+use penumbra_nft::{state::NFTState, types::NFT};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tokio::sync::broadcast;
+
+// Live events buffered per subscriber before it is considered lagging.
+const EVENT_FEED_CAPACITY: usize = 256;
+
+// Everything the server keeps: the penumbra_nft ledger plus the bookkeeping
+// this crate layers on top of it. Persisted as a single JSON document.
+#[derive(Serialize, Deserialize)]
+pub struct AppState {
+    #[serde(rename = "nfts", with = "nft_map")]
+    pub ledger: NFTState,
+    // NFT id -> address allowed to move it on the owner's behalf.
+    #[serde(default)]
+    pub approvals: HashMap<String, String>,
+    #[serde(default)]
+    pub events: EventLog,
+    #[serde(default)]
+    pub extras: HashMap<String, NftExtras>,
+    #[serde(default)]
+    pub collections: HashMap<String, CollectionInfo>,
+    // Owner address -> highest nonce accepted on a signed request.
+    #[serde(default)]
+    pub nonces: HashMap<String, u64>,
+    // Reservation id -> held collection slot.
+    #[serde(default)]
+    pub reservations: HashMap<String, Reservation>,
+    #[serde(default)]
+    pub reveal_log: RevealLog,
+    // Collection name -> traits its mints must carry.
+    #[serde(default)]
+    pub attribute_schemas: HashMap<String, AttributeSchema>,
+    // Collection name -> what GET /view/:id/public shows of its NFTs.
+    #[serde(default)]
+    pub public_views: HashMap<String, PublicPolicy>,
+    // Address -> credit available for mint fees.
+    #[serde(default)]
+    pub balances: HashMap<String, u64>,
+    // NFT id -> its stake, present exactly while it is staked.
+    #[serde(default)]
+    pub staking: HashMap<String, StakeInfo>,
+    #[serde(skip)]
+    pub owner_index: OwnerIndex,
+    // Ids changed since the last save; see `persist::Backend`.
+    #[serde(skip)]
+    pub dirty: HashSet<String>,
+    // Idempotency-Key -> the (id, NFT) a /mint returned.
+    #[serde(skip, default = "default_mint_keys")]
+    pub mint_keys: IdempotencyCache<(String, NFT)>,
+    // Staking reward units per second; configured at startup, not persisted.
+    #[serde(skip, default = "default_reward_rate")]
+    pub reward_rate: u64,
+    // Minimum seconds between staking and unstaking; 0 for none.
+    #[serde(skip)]
+    pub stake_lockup_secs: u64,
+    // Charged per mint to the new owner's balance, or an airdrop's
+    // owner_source; 0 makes minting free.
+    #[serde(skip)]
+    pub mint_fee: u64,
+    #[serde(skip, default = "default_reservation_ttl")]
+    pub reservation_ttl_secs: u64,
+    // Configured at startup, not persisted.
+    #[serde(skip)]
+    pub reveal_mode: RevealMode,
+    #[serde(skip)]
+    pub id_scheme: IdScheme,
+    // Public view fields for collections without a `public_views` entry.
+    #[serde(skip, default = "default_public_fields")]
+    pub public_fields: Vec<PublicField>,
+    #[serde(skip)]
+    pub field_limits: FieldLimits,
+    #[serde(skip, default = "default_clock")]
+    pub clock: Arc<dyn Clock>,
+    // Where `save_state` writes this state; unset outside the server.
+    #[serde(skip)]
+    pub backend: Option<&'static Backend>,
+    // Every recorded event is also broadcast here for live subscribers.
+    #[serde(skip, default = "default_event_feed")]
+    pub event_feed: broadcast::Sender<NftEvent>,
+    // While set, recorded events are logged but not broadcast yet; see `tx`.
+    #[serde(skip)]
+    pub hold_feed: bool,
+    // Shared with /view, which reads it without taking the state lock.
+    #[serde(skip)]
+    pub view_cache: Arc<ViewCache>,
+    // The namespace this state is served under, "" for the default one;
+    // signed messages name it. Configured at startup, not persisted.
+    #[serde(skip)]
+    pub tenant: String,
+}
+
+fn default_reward_rate() -> u64 {
+    DEFAULT_REWARD_RATE
+}
+
+fn default_reservation_ttl() -> u64 {
+    DEFAULT_RESERVATION_TTL_SECS
+}
+
+fn default_public_fields() -> Vec<PublicField> {
+    PublicField::DEFAULTS.to_vec()
+}
+
+fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+fn default_mint_keys() -> IdempotencyCache<(String, NFT)> {
+    IdempotencyCache::new(DEFAULT_TTL_SECS)
+}
+
+fn default_event_feed() -> broadcast::Sender<NftEvent> {
+    broadcast::channel(EVENT_FEED_CAPACITY).0
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        AppState {
+            ledger: NFTState::new(),
+            approvals: HashMap::new(),
+            events: EventLog::default(),
+            extras: HashMap::new(),
+            collections: HashMap::new(),
+            nonces: HashMap::new(),
+            reservations: HashMap::new(),
+            reveal_log: RevealLog::default(),
+            attribute_schemas: HashMap::new(),
+            public_views: HashMap::new(),
+            balances: HashMap::new(),
+            staking: HashMap::new(),
+            owner_index: OwnerIndex::default(),
+            dirty: HashSet::new(),
+            mint_keys: default_mint_keys(),
+            reward_rate: DEFAULT_REWARD_RATE,
+            stake_lockup_secs: 0,
+            mint_fee: 0,
+            reservation_ttl_secs: DEFAULT_RESERVATION_TTL_SECS,
+            reveal_mode: RevealMode::default(),
+            id_scheme: IdScheme::default(),
+            public_fields: default_public_fields(),
+            field_limits: FieldLimits::default(),
+            clock: default_clock(),
+            backend: None,
+            event_feed: default_event_feed(),
+            hold_feed: false,
+            view_cache: Arc::default(),
+            tenant: String::new(),
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.clock.now()
+    }
+
+    // Appends an event stamped with the state's clock and broadcasts it.
+    // Every change goes through here, so it also bumps the NFT's version and
+    // marks it for the next save.
+    pub fn record(&mut self, kind: EventKind, nft_id: &str, from: Option<&str>, to: Option<&str>) {
+        self.dirty.insert(nft_id.to_string());
+        // Burned NFTs are already gone; don't recreate their extras.
+        if self.ledger.get_nft(nft_id).is_some() {
+            self.extras_mut(nft_id).version += 1;
+        }
+        self.view_cache.invalidate(nft_id);
+        let now = self.now();
+        let event = self.events.push(now, kind, nft_id, from, to);
+        // No subscribers is not an error.
+        if !self.hold_feed {
+            let _ = self.event_feed.send(event.clone());
+        }
+    }
+
+    pub fn extras_mut(&mut self, id: &str) -> &mut NftExtras {
+        self.extras.entry(id.to_string()).or_default()
+    }
+
+    pub fn version(&self, id: &str) -> u64 {
+        self.extras.get(id).map_or(0, |extras| extras.version)
+    }
+}
+
+// NFTState isn't serializable itself, so persist its `nfts` map.
+mod nft_map {
+    use penumbra_nft::{state::NFTState, types::NFT};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(ledger: &NFTState, serializer: S) -> Result<S::Ok, S::Error> {
+        ledger.nfts.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NFTState, D::Error> {
+        let mut ledger = NFTState::new();
+        ledger.nfts = HashMap::<String, NFT>::deserialize(deserializer)?;
+        Ok(ledger)
+    }
+}
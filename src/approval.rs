@@ -0,0 +1,140 @@
+use crate::{
+    app::AppState,
+    burn::ensure_not_burned,
+    error::NftError,
+    events::EventKind,
+    nonce::{accept_nonce, check_nonce},
+    signature::{approve_message, verify_signature},
+};
+
+// Lets `spender` move the NFT via `transfer_from` until its next transfer.
+// `caller` must be the owner and sign `approve_message` with its next nonce.
+pub fn approve_nft(
+    state: &mut AppState,
+    id: &str,
+    spender: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if nft.owner != caller {
+        return Err(NftError::Forbidden(format!(
+            "only the owner of NFT {} can approve a spender",
+            id
+        )));
+    }
+    verify_signature(
+        caller,
+        &approve_message(&state.tenant, id, spender, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    accept_nonce(state, caller, nonce);
+    state.approvals.insert(id.to_string(), spender.to_string());
+    state.record(EventKind::Approve, id, Some(caller), Some(spender));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        signature::transfer_from_message,
+        testutil::{self, Key},
+        transfer::{transfer_from, transfer_nft},
+    };
+
+    fn approve(state: &mut AppState, owner: &Key, id: &str, spender: &str, nonce: u64) {
+        let signature = owner.sign(&approve_message("", id, spender, nonce));
+        approve_nft(state, id, spender, &owner.address(), nonce, &signature).unwrap();
+    }
+
+    #[test]
+    fn approved_spender_can_transfer() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let spender = Key::new(2);
+        let id = testutil::mint(&mut state, &alice.address());
+        approve(&mut state, &alice, &id, &spender.address(), 1);
+        assert_eq!(state.nonces[&alice.address()], 1);
+        let message = transfer_from_message("", &id, &alice.address(), "carol", 1);
+        transfer_from(
+            &mut state,
+            &id,
+            &alice.address(),
+            "carol",
+            &spender.address(),
+            1,
+            &spender.sign(&message),
+        )
+        .unwrap();
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, "carol");
+    }
+
+    #[test]
+    fn transfer_clears_the_approval() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        approve(&mut state, &alice, &id, "spender", 1);
+        transfer_nft(&mut state, &id, "bob").unwrap();
+        assert!(!state.approvals.contains_key(&id));
+    }
+
+    #[test]
+    fn approval_needs_the_owner_signature_and_a_fresh_nonce() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let owner = alice.address();
+        let id = testutil::mint(&mut state, &owner);
+        let forged = Key::new(2).sign(&approve_message("", &id, "mallory", 1));
+        let err = approve_nft(&mut state, &id, "mallory", &owner, 1, &forged);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        // A key that isn't the owner can't approve even with its own signature.
+        let mallory = Key::new(3);
+        let signature = mallory.sign(&approve_message("", &id, "mallory", 1));
+        let err = approve_nft(
+            &mut state,
+            &id,
+            "mallory",
+            &mallory.address(),
+            1,
+            &signature,
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        approve(&mut state, &alice, &id, "spender", 1);
+        let replayed = alice.sign(&approve_message("", &id, "spender", 1));
+        let err = approve_nft(&mut state, &id, "spender", &owner, 1, &replayed);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        assert!(state
+            .approvals
+            .get(&id)
+            .is_some_and(|spender| spender == "spender"));
+    }
+
+    #[test]
+    fn unapproved_spender_is_rejected() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let spender = Key::new(2);
+        let id = testutil::mint(&mut state, &alice.address());
+        approve(&mut state, &alice, &id, &spender.address(), 1);
+        let other = Key::new(4);
+        let message = transfer_from_message("", &id, &alice.address(), "carol", 1);
+        let err = transfer_from(
+            &mut state,
+            &id,
+            &alice.address(),
+            "carol",
+            &other.address(),
+            1,
+            &other.sign(&message),
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+    }
+}
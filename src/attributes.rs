@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+// OpenSea-style trait. `NFTMetadata.attributes` is still a String upstream,
+// so these are stored JSON-encoded in that field.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Attribute {
+    pub trait_type: String,
+    pub value: AttributeValue,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(untagged)]
+pub enum AttributeValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl AttributeValue {
+    // Compares against a query-string value.
+    pub fn matches(&self, raw: &str) -> bool {
+        match self {
+            AttributeValue::Bool(b) => raw.parse::<bool>().is_ok_and(|raw| raw == *b),
+            AttributeValue::Number(n) => raw.parse::<f64>().is_ok_and(|raw| raw == *n),
+            AttributeValue::String(s) => s == raw,
+        }
+    }
+}
+
+// Accepted on input: structured attributes, or the old free-form string.
+#[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(untagged)]
+pub enum Attributes {
+    Structured(Vec<Attribute>),
+    Legacy(String),
+}
+
+impl Attributes {
+    pub fn into_vec(self) -> Vec<Attribute> {
+        match self {
+            Attributes::Structured(attrs) => attrs,
+            Attributes::Legacy(raw) => decode_attributes(&raw),
+        }
+    }
+}
+
+pub fn encode_attributes(attrs: &[Attribute]) -> String {
+    serde_json::to_string(attrs).expect("attributes always serialize")
+}
+
+// Reads the stored String back. Values saved before attributes were
+// structured are kept as a single "legacy" trait rather than dropped.
+pub fn decode_attributes(raw: &str) -> Vec<Attribute> {
+    if raw.trim().is_empty() {
+        return Vec::new();
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| {
+        vec![Attribute {
+            trait_type: "legacy".into(),
+            value: AttributeValue::String(raw.to_string()),
+        }]
+    })
+}
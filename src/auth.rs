@@ -0,0 +1,123 @@
+use crate::error::NftError;
+use axum::{
+    extract::{Request, State},
+    http::{header::AUTHORIZATION, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+// The configured admin bearer token, if any.
+#[derive(Clone)]
+pub struct AdminKey(pub Option<Arc<str>>);
+
+impl AdminKey {
+    // True only when a key is configured and `headers` present it. Handlers
+    // use this for admin overrides on routes that are otherwise open.
+    pub fn authorizes(&self, headers: &HeaderMap) -> bool {
+        self.0.is_some() && self.check(headers).is_ok()
+    }
+
+    fn check(&self, headers: &HeaderMap) -> Result<(), NftError> {
+        let Some(expected) = &self.0 else {
+            return Ok(());
+        };
+        let given = headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        match given {
+            None => Err(NftError::Unauthorized("missing admin bearer token".into())),
+            Some(given) if !constant_time_eq(given.as_bytes(), expected.as_bytes()) => {
+                Err(NftError::Unauthorized("invalid admin bearer token".into()))
+            }
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+// Requires `Authorization: Bearer <key>` when an admin key is configured.
+// Without one the routes stay open, as in local development.
+pub async fn require_admin(
+    State(admin_key): State<AdminKey>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Err(err) = admin_key.check(req.headers()) {
+        return err.into_response();
+    }
+    next.run(req).await
+}
+
+// For overrides that bypass owner checks: unlike `require_admin`, these stay
+// closed when no admin key is configured.
+pub async fn require_admin_key(
+    State(admin_key): State<AdminKey>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !admin_key.authorizes(req.headers()) {
+        let reason = if admin_key.0.is_some() {
+            "missing or invalid admin bearer token"
+        } else {
+            "admin overrides need --admin-key to be configured"
+        };
+        return NftError::Unauthorized(reason.into()).into_response();
+    }
+    next.run(req).await
+}
+
+// Doesn't stop at the first mismatch, so timing doesn't reveal the prefix.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    async fn mint_status(key: Option<&str>, bearer: Option<&str>) -> StatusCode {
+        let admin_key = AdminKey(key.map(Arc::from));
+        let app = Router::new()
+            .route("/mint", post(|| async { "minted" }))
+            .route_layer(middleware::from_fn_with_state(admin_key, require_admin));
+        let mut request = axum::http::Request::post("/mint");
+        if let Some(bearer) = bearer {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", bearer));
+        }
+        let request = request.body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn configured_key_is_required() {
+        assert_eq!(
+            mint_status(Some("secret"), None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            mint_status(Some("secret"), Some("secret")).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_key_is_rejected() {
+        assert_eq!(
+            mint_status(Some("secret"), Some("secreT")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            mint_status(Some("secret"), Some("secret2")).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn no_configured_key_leaves_routes_open() {
+        assert_eq!(mint_status(None, None).await, StatusCode::OK);
+        assert!(!AdminKey(None).authorizes(&HeaderMap::new()));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    app::AppState,
+    burn::{ensure_not_burned, is_burned, live_nfts},
+    collections::CollectionInfo,
+    error::NftError,
+    events::EventKind,
+    extras::NftExtras,
+    ids::normalize_id,
+    owners::OwnerIndex,
+    staking::StakeInfo,
+};
+use penumbra_nft::types::NFT;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+// Bumped whenever `Backup` changes shape in a way older servers can't read.
+pub const BACKUP_VERSION: u32 = 1;
+
+// Off-server backup of NFTs with everything this server keeps about them.
+// Unlike an IBC payload it keeps ids, history and versions exactly, and is
+// only meant to be read back by this crate.
+#[derive(Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    pub exported_at: u64,
+    pub nfts: Vec<BackupEntry>,
+    // The collections those NFTs belong to.
+    #[serde(default)]
+    pub collections: BTreeMap<String, CollectionInfo>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub nft: NFT,
+    #[serde(default)]
+    pub extras: NftExtras,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved: Option<String>,
+    // Backups taken before stakes left the extras carry none; an NFT the
+    // ledger has staked is restored with an unknown stake, as the state
+    // migration does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stake: Option<StakeInfo>,
+}
+
+// A backup of one NFT.
+pub fn export_one(state: &AppState, id: &str) -> Result<Backup, NftError> {
+    ensure_not_burned(state, id)?;
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    Ok(backup_of(state, [nft]))
+}
+
+// A backup of every live NFT, ordered by id.
+pub fn export_all(state: &AppState) -> Backup {
+    let mut nfts: Vec<&NFT> = live_nfts(state).collect();
+    nfts.sort_by(|a, b| a.id.cmp(&b.id));
+    backup_of(state, nfts)
+}
+
+fn backup_of<'a>(state: &'a AppState, nfts: impl IntoIterator<Item = &'a NFT>) -> Backup {
+    let mut collections = BTreeMap::new();
+    let entries = nfts
+        .into_iter()
+        .map(|nft| {
+            let extras = state.extras.get(&nft.id).cloned().unwrap_or_default();
+            if let Some(name) = &extras.collection {
+                if let Some(info) = state.collections.get(name) {
+                    collections.insert(name.clone(), info.clone());
+                }
+            }
+            BackupEntry {
+                nft: nft.clone(),
+                extras,
+                approved: state.approvals.get(&nft.id).cloned(),
+                stake: state.staking.get(&nft.id).cloned(),
+            }
+        })
+        .collect();
+    Backup {
+        version: BACKUP_VERSION,
+        exported_at: state.now(),
+        nfts: entries,
+        collections,
+    }
+}
+
+// Restores every NFT in `backup` under its original id and returns the ids.
+// An existing NFT with the same id is only replaced when `overwrite` is set,
+// and a burned id stays taken either way. All or nothing: a collision anywhere
+// fails the whole import. Collections already known here are left as they are.
+pub fn import_backup(
+    state: &mut AppState,
+    mut backup: Backup,
+    overwrite: bool,
+) -> Result<Vec<String>, NftError> {
+    if backup.version != BACKUP_VERSION {
+        return Err(NftError::Invalid(format!(
+            "unsupported backup version {} (expected {})",
+            backup.version, BACKUP_VERSION
+        )));
+    }
+    for entry in &mut backup.nfts {
+        entry.nft.id = normalize_id(&entry.nft.id);
+    }
+    let mut seen = HashSet::new();
+    for entry in &backup.nfts {
+        let id = &entry.nft.id;
+        if id.trim().is_empty() {
+            return Err(NftError::Invalid(
+                "backup contains an NFT with an empty id".into(),
+            ));
+        }
+        if entry.nft.owner.trim().is_empty() {
+            return Err(NftError::Invalid(format!(
+                "NFT {} in the backup has no owner",
+                id
+            )));
+        }
+        if !seen.insert(id.as_str()) {
+            return Err(NftError::Invalid(format!(
+                "NFT {} appears more than once in the backup",
+                id
+            )));
+        }
+        if is_burned(state, id) || (!overwrite && state.ledger.get_nft(id).is_some()) {
+            return Err(NftError::Conflict(format!("NFT {} already exists", id)));
+        }
+    }
+
+    for (name, info) in backup.collections {
+        state.collections.entry(name).or_insert(info);
+    }
+    let mut ids = Vec::with_capacity(backup.nfts.len());
+    for entry in backup.nfts {
+        let id = entry.nft.id.clone();
+        let owner = entry.nft.owner.clone();
+        let staked = entry.nft.staked;
+        let version = entry.extras.version;
+        state.ledger.nfts.insert(id.clone(), entry.nft);
+        state.extras.insert(id.clone(), entry.extras);
+        match entry.approved {
+            Some(approved) => state.approvals.insert(id.clone(), approved),
+            None => state.approvals.remove(&id),
+        };
+        let stake = entry.stake.or_else(|| {
+            staked.then_some(StakeInfo {
+                staked_at: None,
+                accrues_from: None,
+                unstake_after: None,
+                claimed: 0,
+            })
+        });
+        match stake {
+            Some(stake) => state.staking.insert(id.clone(), stake),
+            None => state.staking.remove(&id),
+        };
+        state.record(EventKind::BackupImport, &id, None, Some(&owner));
+        // Recording bumped the version; keep the one the backup was taken at.
+        state.extras_mut(&id).version = version;
+        ids.push(id);
+    }
+    // Replaced NFTs may have changed hands or shares, so reindex from scratch.
+    state.owner_index = OwnerIndex::build(state);
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mint::mint_nft,
+        signature::transfer_message,
+        staking::stake_nft,
+        testutil::{self, Key},
+        transfer::transfer_signed,
+    };
+    use serde_json::{json, Value};
+
+    // Everything this server keeps about `id`, for comparing across states.
+    fn snapshot(state: &AppState, id: &str) -> Value {
+        json!({
+            "nft": state.ledger.get_nft(id),
+            "extras": state.extras.get(id),
+            "approved": state.approvals.get(id),
+            "stake": state.staking.get(id),
+        })
+    }
+
+    #[test]
+    fn export_then_import_restores_everything() {
+        let (mut state, clock) = testutil::state();
+        let alice = Key::new(1);
+        let mut item = testutil::item("kept");
+        item.extras.collection = Some("apes".to_string());
+        item.max_supply = Some(10);
+        let id = mint_nft(&mut state, alice.address(), item).unwrap();
+        clock.advance(10);
+        let signature = alice.sign(&transfer_message("", &id, "bob", 1));
+        transfer_signed(&mut state, &id, &alice.address(), "bob", 1, &signature).unwrap();
+        state.approvals.insert(id.clone(), "spender".to_string());
+        stake_nft(&mut state, &id).unwrap();
+
+        let json = serde_json::to_string(&export_one(&state, &id).unwrap()).unwrap();
+        let (mut restored, _) = testutil::state();
+        let backup: Backup = serde_json::from_str(&json).unwrap();
+        let ids = import_backup(&mut restored, backup, false).unwrap();
+        assert_eq!(ids, [id.as_str()]);
+        assert_eq!(snapshot(&restored, &id), snapshot(&state, &id));
+        assert_eq!(restored.collections["apes"].minted, 1);
+        assert_eq!(restored.owner_index.ids("bob").count(), 1);
+
+        let backup: Backup = serde_json::from_str(&json).unwrap();
+        let err = import_backup(&mut restored, backup, false);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{app::AppState, error::NftError};
+use std::collections::HashMap;
+
+// Optional mint economics: with a non-zero `mint_fee`, every mint is paid for
+// from the new owner's balance (an airdrop's owner_source pays for its copies),
+// topped up by an admin through POST /credit.
+
+pub fn balance(state: &AppState, address: &str) -> u64 {
+    state.balances.get(address).copied().unwrap_or(0)
+}
+
+// Returns the new balance.
+pub fn credit(state: &mut AppState, address: &str, amount: u64) -> Result<u64, NftError> {
+    if address.trim().is_empty() {
+        return Err(NftError::Invalid("address must not be empty".into()));
+    }
+    if amount == 0 {
+        return Err(NftError::Invalid("amount must be positive".into()));
+    }
+    let balance = state.balances.entry(address.to_string()).or_default();
+    *balance = balance.checked_add(amount).ok_or_else(|| {
+        NftError::Invalid(format!("crediting {} would overflow the balance", amount))
+    })?;
+    Ok(*balance)
+}
+
+// Fails unless each address can pay for as many mints as it appears, so a
+// batch is rejected before any of it is minted.
+pub fn check_mint_fees<'a>(
+    state: &AppState,
+    owners: impl IntoIterator<Item = &'a str>,
+) -> Result<(), NftError> {
+    if state.mint_fee == 0 {
+        return Ok(());
+    }
+    let mut mints: HashMap<&str, u64> = HashMap::new();
+    for owner in owners {
+        *mints.entry(owner).or_default() += 1;
+    }
+    for (owner, count) in mints {
+        let due = state.mint_fee.saturating_mul(count);
+        let available = balance(state, owner);
+        if available < due {
+            return Err(NftError::PaymentRequired(format!(
+                "insufficient balance: {} has {}, minting {} costs {}",
+                owner, available, count, due
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Deducts one mint fee; `check_mint_fees` must have passed.
+pub fn charge_mint_fee(state: &mut AppState, owner: &str) {
+    if state.mint_fee == 0 {
+        return;
+    }
+    if let Some(balance) = state.balances.get_mut(owner) {
+        *balance = balance.saturating_sub(state.mint_fee);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mint::mint_nft, testutil};
+
+    #[test]
+    fn insufficient_balance_is_rejected() {
+        let (mut state, _) = testutil::state();
+        state.mint_fee = 10;
+        credit(&mut state, "alice", 9).unwrap();
+        let err = mint_nft(&mut state, "alice".to_string(), testutil::item("one"));
+        assert!(matches!(err, Err(NftError::PaymentRequired(_))));
+        assert!(state.ledger.nfts.is_empty());
+        assert_eq!(balance(&state, "alice"), 9);
+    }
+
+    #[test]
+    fn mint_deducts_the_fee() {
+        let (mut state, _) = testutil::state();
+        state.mint_fee = 10;
+        credit(&mut state, "alice", 25).unwrap();
+        testutil::mint(&mut state, "alice");
+        assert_eq!(balance(&state, "alice"), 15);
+    }
+
+    #[test]
+    fn zero_fee_needs_no_balance() {
+        let (mut state, _) = testutil::state();
+        testutil::mint(&mut state, "alice");
+        assert_eq!(balance(&state, "alice"), 0);
+        assert!(state.balances.is_empty());
+    }
+}
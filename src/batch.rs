@@ -0,0 +1,307 @@
+use crate::{
+    app::AppState,
+    balance::check_mint_fees,
+    collections::check_supply,
+    error::NftError,
+    events::EventKind,
+    ids::check_content_ids,
+    mint::{check_template, mint_nft, mint_nft_paid_by, MintItem},
+    reservation::reserved_count,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Mints every item to `owner` in order, returning the new ids in the same order.
+// Every item gets all of `mint_nft`'s checks first, including collection
+// supply and settings fixed by an earlier item of the same batch, so a bad
+// item leaves the state untouched. Callers hold the write lock for the whole
+// batch.
+pub fn mint_nft_batch(
+    state: &mut AppState,
+    owner: &str,
+    items: Vec<MintItem>,
+) -> Result<Vec<String>, NftError> {
+    // Collection -> (items minting into it, its max_supply and cooldown as
+    // fixed by the collection or else by the batch's first item in it).
+    let mut per_collection: HashMap<&str, (u32, Option<u32>, Option<u64>)> = HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        check_template(state, item).map_err(|e| e.prefixed(format_args!("item {}", index)))?;
+        if let Some(collection) = &item.extras.collection {
+            let (count, max_supply, cooldown) =
+                per_collection.entry(collection).or_insert_with(|| {
+                    match state.collections.get(collection) {
+                        Some(info) => (0, info.max_supply, info.transfer_cooldown_secs),
+                        None => (0, item.max_supply, item.transfer_cooldown_secs),
+                    }
+                });
+            if item.max_supply.is_some() && item.max_supply != *max_supply {
+                return Err(NftError::Invalid(format!(
+                    "item {}: max_supply for collection {} is set by its first mint",
+                    index, collection
+                )));
+            }
+            if item.transfer_cooldown_secs.is_some() && item.transfer_cooldown_secs != *cooldown {
+                return Err(NftError::Invalid(format!(
+                    "item {}: transfer_cooldown_secs for collection {} is set by its first mint",
+                    index, collection
+                )));
+            }
+            *count += 1;
+        }
+    }
+    for (collection, (count, max_supply, _)) in &per_collection {
+        check_supply(state, collection, *max_supply, *count)?;
+    }
+    check_content_ids(state, items.iter().map(|item| (owner, item)))?;
+    check_mint_fees(state, items.iter().map(|_| owner))?;
+    items
+        .into_iter()
+        .map(|item| mint_nft(state, owner.to_string(), item))
+        .collect()
+}
+
+// Mints a separate copy of `template` to each recipient, returning the new ids
+// in recipient order. Every copy keeps the template's metadata as given,
+// including its shielded flag. `owner_source` is recorded as the sender of each
+// airdrop event and pays every copy's mint fee; it never owns the copies.
+pub fn airdrop_mint(
+    state: &mut AppState,
+    template: MintItem,
+    owner_source: &str,
+    recipients: Vec<String>,
+) -> Result<Vec<String>, NftError> {
+    if recipients.is_empty() {
+        return Err(NftError::Invalid(
+            "airdrop needs at least one recipient".into(),
+        ));
+    }
+    if let Some(index) = recipients.iter().position(|r| r.trim().is_empty()) {
+        return Err(NftError::Invalid(format!("recipient {} is empty", index)));
+    }
+    check_template(state, &template)?;
+    if let Some(collection) = &template.extras.collection {
+        check_supply(
+            state,
+            collection,
+            template.max_supply,
+            recipients.len() as u32,
+        )?;
+    }
+    check_content_ids(state, recipients.iter().map(|r| (r.as_str(), &template)))?;
+    check_mint_fees(state, recipients.iter().map(|_| owner_source))?;
+    let mut ids = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let id = mint_nft_paid_by(state, recipient.clone(), owner_source, template.clone())?;
+        state.record(
+            EventKind::Airdrop,
+            &id,
+            Some(owner_source),
+            Some(&recipient),
+        );
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+#[derive(Debug, Serialize)]
+pub struct AirdropSimulation {
+    pub recipients: usize,
+    pub valid_recipients: usize,
+    // Positions in the request of recipients the airdrop would reject.
+    pub invalid_recipients: Vec<usize>,
+    // Zero unless the airdrop would go through; it mints all or nothing.
+    pub would_mint: usize,
+    // Slots left in the template's collection before this airdrop, if capped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply_remaining: Option<u32>,
+    pub violations: Vec<String>,
+}
+
+// Runs `airdrop_mint`'s checks without minting, collecting every failure
+// instead of stopping at the first.
+pub fn simulate_airdrop(
+    state: &AppState,
+    template: &MintItem,
+    owner_source: &str,
+    recipients: &[String],
+) -> AirdropSimulation {
+    let mut violations = Vec::new();
+    if recipients.is_empty() {
+        violations.push("airdrop needs at least one recipient".to_string());
+    }
+    let invalid_recipients: Vec<usize> = recipients
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.trim().is_empty())
+        .map(|(index, _)| index)
+        .collect();
+    for index in &invalid_recipients {
+        violations.push(format!("recipient {} is empty", index));
+    }
+    let valid: Vec<&str> = recipients
+        .iter()
+        .map(String::as_str)
+        .filter(|r| !r.trim().is_empty())
+        .collect();
+    if let Err(err) = check_template(state, template) {
+        violations.push(err.to_string());
+    }
+    let mut supply_remaining = None;
+    if let Some(collection) = &template.extras.collection {
+        let (cap, minted) = match state.collections.get(collection) {
+            Some(info) => (info.max_supply, info.minted),
+            None => (template.max_supply, 0),
+        };
+        supply_remaining = cap.map(|cap| {
+            cap.saturating_sub(minted)
+                .saturating_sub(reserved_count(state, collection))
+        });
+        if let Err(err) = check_supply(
+            state,
+            collection,
+            template.max_supply,
+            recipients.len() as u32,
+        ) {
+            violations.push(err.to_string());
+        }
+    }
+    if let Err(err) = check_content_ids(state, valid.iter().map(|r| (*r, template))) {
+        violations.push(err.to_string());
+    }
+    if let Err(err) = check_mint_fees(state, valid.iter().map(|_| owner_source)) {
+        violations.push(err.to_string());
+    }
+    AirdropSimulation {
+        recipients: recipients.len(),
+        valid_recipients: valid.len(),
+        invalid_recipients,
+        would_mint: if violations.is_empty() {
+            valid.len()
+        } else {
+            0
+        },
+        supply_remaining,
+        violations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+    use std::collections::HashSet;
+
+    fn in_collection(name: &str, cooldown: Option<u64>) -> MintItem {
+        let mut item = testutil::item(name);
+        item.extras.collection = Some("drops".to_string());
+        item.transfer_cooldown_secs = cooldown;
+        item
+    }
+
+    #[test]
+    fn mints_500_unique_ids_in_order() {
+        let (mut state, _) = testutil::state();
+        let items = (0..500)
+            .map(|i| testutil::item(&format!("nft {}", i)))
+            .collect();
+        let ids = mint_nft_batch(&mut state, "alice", items).unwrap();
+        assert_eq!(ids.len(), 500);
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 500);
+        assert_eq!(
+            state.ledger.get_nft(&ids[7]).unwrap().metadata.name,
+            "nft 7"
+        );
+    }
+
+    #[test]
+    fn late_bad_item_mints_nothing() {
+        let (mut state, _) = testutil::state();
+        let mut items: Vec<_> = (0..3)
+            .map(|i| testutil::item(&format!("nft {}", i)))
+            .collect();
+        items[2].extras.expires_at = Some(testutil::START);
+        let err = mint_nft_batch(&mut state, "alice", items);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        assert!(state.ledger.nfts.is_empty());
+    }
+
+    #[test]
+    fn settings_fixed_by_an_earlier_item_are_checked() {
+        let (mut state, _) = testutil::state();
+        let items = vec![in_collection("a", None), in_collection("b", Some(60))];
+        let err = mint_nft_batch(&mut state, "alice", items);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        assert!(state.ledger.nfts.is_empty());
+        assert!(state.collections.is_empty());
+
+        let items = vec![in_collection("a", Some(60)), in_collection("b", Some(60))];
+        assert_eq!(mint_nft_batch(&mut state, "alice", items).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn airdrop_mints_a_copy_per_recipient() {
+        let (mut state, _) = testutil::state();
+        let recipients: Vec<String> = (0..10).map(|i| format!("owner{}", i)).collect();
+        let ids = airdrop_mint(
+            &mut state,
+            testutil::item("drop"),
+            "sponsor",
+            recipients.clone(),
+        )
+        .unwrap();
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 10);
+        for (id, recipient) in ids.iter().zip(&recipients) {
+            assert_eq!(&state.ledger.get_nft(id).unwrap().owner, recipient);
+        }
+    }
+
+    #[test]
+    fn airdrop_fees_are_paid_by_the_owner_source() {
+        let (mut state, _) = testutil::state();
+        state.mint_fee = 5;
+        state.balances.insert("sponsor".to_string(), 12);
+        let recipients = vec!["bob".to_string(), "carol".to_string(), "dave".to_string()];
+        let template = testutil::item("drop");
+        let simulation = simulate_airdrop(&state, &template, "sponsor", &recipients);
+        assert_eq!(simulation.would_mint, 0);
+        let err = airdrop_mint(&mut state, template.clone(), "sponsor", recipients.clone());
+        assert!(matches!(err, Err(NftError::PaymentRequired(_))));
+        assert!(state.ledger.nfts.is_empty());
+
+        state.balances.insert("sponsor".to_string(), 15);
+        let simulation = simulate_airdrop(&state, &template, "sponsor", &recipients);
+        assert_eq!(simulation.would_mint, 3);
+        let ids = airdrop_mint(&mut state, template, "sponsor", recipients).unwrap();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(state.balances["sponsor"], 0);
+        assert!(!state.balances.contains_key("bob"));
+    }
+
+    #[test]
+    fn simulating_an_airdrop_over_supply_reports_it_without_minting() {
+        let (mut state, _) = testutil::state();
+        let mut template = in_collection("drop", None);
+        template.max_supply = Some(3);
+        mint_nft(&mut state, "alice".to_string(), template.clone()).unwrap();
+        let recipients = vec![
+            "bob".to_string(),
+            "".to_string(),
+            "carol".to_string(),
+            "dave".to_string(),
+        ];
+        let simulation = simulate_airdrop(&state, &template, "alice", &recipients);
+        assert_eq!(simulation.recipients, 4);
+        assert_eq!(simulation.valid_recipients, 3);
+        assert_eq!(simulation.invalid_recipients, [1]);
+        assert_eq!(simulation.supply_remaining, Some(2));
+        assert_eq!(simulation.would_mint, 0);
+        assert_eq!(
+            simulation.violations.len(),
+            2,
+            "{:?}",
+            simulation.violations
+        );
+        assert_eq!(state.ledger.nfts.len(), 1);
+        assert_eq!(state.collections["drops"].minted, 1);
+    }
+}
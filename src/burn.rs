@@ -0,0 +1,276 @@
+use crate::{
+    app::AppState,
+    error::NftError,
+    events::EventKind,
+    extras::NftStatus,
+    freeze::ensure_not_frozen,
+    ibc::ensure_not_exported,
+    nonce::{accept_nonce, check_nonce},
+    shares::ensure_not_shared,
+    signature::{burn_batch_message, verify_signature},
+    staking::is_staked,
+    telemetry,
+    transfer::BatchOutcome,
+};
+use penumbra_nft::types::NFT;
+use std::collections::BTreeMap;
+
+// Marks an NFT burned. The record stays, so /view and its history still
+// answer for the id and it can never be minted again, but it leaves every
+// listing and nothing can change it. Staked, frozen, IBC-exported or jointly
+// owned NFTs are refused.
+pub fn burn_nft(state: &mut AppState, id: &str) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    let owner = nft.owner.clone();
+    if is_staked(state, id) {
+        return Err(NftError::Locked(format!(
+            "NFT {} is staked; unstake it before burning",
+            id
+        )));
+    }
+    ensure_not_frozen(state, id)?;
+    ensure_not_exported(state, id)?;
+    ensure_not_shared(state, id)?;
+    state.approvals.remove(id);
+    let extras = state.extras_mut(id);
+    extras.status = NftStatus::Burned;
+    extras.offer = None;
+    state.owner_index.remove(&owner, id);
+    state.record(EventKind::Burn, id, Some(&owner), None);
+    metrics::counter!(telemetry::BURNS).increment(1);
+    Ok(())
+}
+
+// `burn_nft` for DELETE /burn/:id, authorized as a one-id /burn/batch is: the
+// owner signs `burn_batch_message` over just `id`.
+pub fn burn_signed(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    let owner = state
+        .ledger
+        .get_nft(id)
+        .map(|nft| nft.owner.as_str())
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if owner != caller {
+        return Err(NftError::Forbidden(format!(
+            "{} is not the owner of NFT {}",
+            caller, id
+        )));
+    }
+    let message = burn_batch_message(&state.tenant, &[id.to_string()], nonce);
+    verify_signature(caller, &message, signature)?;
+    check_nonce(state, caller, nonce)?;
+    burn_nft(state, id)?;
+    accept_nonce(state, caller, nonce);
+    Ok(())
+}
+
+// Burns what it can of `ids`, authorized by one signature by `caller` over
+// the whole list. Ids `caller` doesn't own, and any `burn_nft` refuses
+// (staked, frozen, ...), are skipped with the reason; the rest still burn.
+// As with batch transfers, the nonce is used up once the signature checks out.
+pub fn burn_nft_batch(
+    state: &mut AppState,
+    ids: &[String],
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<BTreeMap<String, BatchOutcome>, NftError> {
+    if ids.is_empty() {
+        return Err(NftError::Invalid("batch burn needs at least one id".into()));
+    }
+    verify_signature(
+        caller,
+        &burn_batch_message(&state.tenant, ids, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    accept_nonce(state, caller, nonce);
+    let mut results = BTreeMap::new();
+    for id in ids {
+        let result = match state.ledger.get_nft(id) {
+            None => Err(NftError::NotFound(format!("NFT {} not found", id))),
+            Some(nft) if nft.owner != caller => Err(NftError::Forbidden(format!(
+                "{} is not the owner of NFT {}",
+                caller, id
+            ))),
+            Some(_) => burn_nft(state, id),
+        };
+        let outcome = BatchOutcome {
+            ok: result.is_ok(),
+            error: result.err().map(|err| err.to_string()),
+        };
+        results.insert(id.clone(), outcome);
+    }
+    Ok(results)
+}
+
+pub fn is_burned(state: &AppState, id: &str) -> bool {
+    state
+        .extras
+        .get(id)
+        .is_some_and(|extras| extras.status == NftStatus::Burned)
+}
+
+// Checked by every path that changes an NFT.
+pub fn ensure_not_burned(state: &AppState, id: &str) -> Result<(), NftError> {
+    if is_burned(state, id) {
+        return Err(NftError::Conflict(format!("NFT {} has been burned", id)));
+    }
+    Ok(())
+}
+
+// Every NFT that hasn't been burned; what the listings and counts cover.
+pub fn live_nfts(state: &AppState) -> impl Iterator<Item = &NFT> {
+    state
+        .ledger
+        .nfts
+        .values()
+        .filter(|nft| !is_burned(state, &nft.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        list::list_nfts,
+        reveal::{reveal_view, NftView},
+        signature::transfer_message,
+        staking::stake_nft,
+        testutil::{self, Key},
+        transfer::transfer_signed,
+    };
+
+    fn burn(state: &mut AppState, owner: &Key, id: &str, nonce: u64) -> Result<(), NftError> {
+        let signature = owner.sign(&burn_batch_message("", &[id.to_string()], nonce));
+        burn_signed(state, id, &owner.address(), nonce, &signature)
+    }
+
+    #[test]
+    fn burned_nft_leaves_a_tombstone() {
+        let mut state = AppState::new();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        burn(&mut state, &alice, &id, 1).unwrap();
+        assert!(is_burned(&state, &id));
+        assert_eq!(live_nfts(&state).count(), 0);
+        assert_eq!(state.owner_index.ids(&alice.address()).count(), 0);
+        let err = burn(&mut state, &alice, &id, 2);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+    }
+
+    #[test]
+    fn burned_nft_stays_viewable_but_cannot_move() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        burn(&mut state, &alice, &id, 1).unwrap();
+        let view = reveal_view(&state, &id, None);
+        assert!(matches!(
+            view,
+            Some(NftView::Full {
+                status: NftStatus::Burned,
+                ..
+            })
+        ));
+        assert_eq!(list_nfts(&state, None, 0, 10).total, 0);
+        let signature = alice.sign(&transfer_message("", &id, "bob", 2));
+        let err = transfer_signed(&mut state, &id, &alice.address(), "bob", 2, &signature);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, alice.address());
+    }
+
+    #[test]
+    fn staked_nft_is_not_burned() {
+        let mut state = AppState::new();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        stake_nft(&mut state, &id).unwrap();
+        let err = burn(&mut state, &alice, &id, 1);
+        assert!(matches!(err, Err(NftError::Locked(_))));
+        assert!(!is_burned(&state, &id));
+        // A refused burn doesn't use up the nonce.
+        assert!(!state.nonces.contains_key(&alice.address()));
+    }
+
+    #[test]
+    fn missing_nft_is_not_found() {
+        let mut state = AppState::new();
+        let err = burn(&mut state, &Key::new(1), "missing", 1);
+        assert!(matches!(err, Err(NftError::NotFound(_))));
+    }
+
+    #[test]
+    fn burn_needs_the_owner_signature() {
+        let mut state = AppState::new();
+        let alice = Key::new(1);
+        let mallory = Key::new(2);
+        let id = testutil::mint(&mut state, &alice.address());
+        let forged = mallory.sign(&burn_batch_message("", std::slice::from_ref(&id), 1));
+        let err = burn_signed(&mut state, &id, &alice.address(), 1, &forged);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        let err = burn(&mut state, &mallory, &id, 1);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(!is_burned(&state, &id));
+    }
+
+    #[test]
+    fn a_burn_signature_is_not_replayed() {
+        let mut state = AppState::new();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        accept_nonce(&mut state, &alice.address(), 1);
+        let err = burn(&mut state, &alice, &id, 1);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        assert!(!is_burned(&state, &id));
+    }
+
+    #[test]
+    fn batch_burn_skips_ids_the_caller_does_not_own() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let mine = testutil::mint(&mut state, &alice.address());
+        let theirs = testutil::mint(&mut state, "bob");
+        let ids = vec![mine.clone(), theirs.clone()];
+        let signature = alice.sign(&burn_batch_message("", &ids, 1));
+        let results = burn_nft_batch(&mut state, &ids, &alice.address(), 1, &signature).unwrap();
+        assert!(results[&mine].ok);
+        assert!(!results[&theirs].ok);
+        assert!(!is_burned(&state, &theirs));
+    }
+
+    #[test]
+    fn batch_burn_reports_staked_and_frozen_ids_as_skipped() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let mine = testutil::mint(&mut state, &alice.address());
+        let theirs = testutil::mint(&mut state, "bob");
+        let staked = testutil::mint(&mut state, &alice.address());
+        let frozen = testutil::mint(&mut state, &alice.address());
+        stake_nft(&mut state, &staked).unwrap();
+        state.extras_mut(&frozen).frozen = true;
+
+        let ids = vec![mine.clone(), theirs.clone(), staked.clone(), frozen.clone()];
+        let signature = alice.sign(&burn_batch_message("", &ids, 1));
+        let results = burn_nft_batch(&mut state, &ids, &alice.address(), 1, &signature).unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(results[&mine].ok && is_burned(&state, &mine));
+        for (id, reason) in [(&theirs, "owner"), (&staked, "staked"), (&frozen, "frozen")] {
+            assert!(!results[id].ok);
+            assert!(
+                results[id].error.as_ref().unwrap().contains(reason),
+                "{}",
+                reason
+            );
+            assert!(!is_burned(&state, id));
+        }
+    }
+}
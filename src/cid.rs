@@ -0,0 +1,150 @@
+// Minimal IPFS CID checks: CIDv0 (base58btc `Qm...`) and CIDv1 in the
+// common multibase encodings. An `ipfs://` scheme prefix is accepted.
+
+use crate::error::NftError;
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE32_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz234567";
+
+// CIDv1 of `bytes` as a single raw block (codec 0x55, sha2-256 multihash),
+// base32 encoded. IPFS assigns the same CID via `ipfs block put --cid-codec raw`.
+pub fn raw_cid(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut cid = vec![0x01, 0x55, 0x12, 0x20];
+    cid.extend_from_slice(&Sha256::digest(bytes));
+    format!("b{}", encode_base32(&cid))
+}
+
+pub fn strip_scheme(cid: &str) -> &str {
+    cid.strip_prefix("ipfs://").unwrap_or(cid)
+}
+
+pub fn validate_cid(cid: &str) -> Result<(), NftError> {
+    let cid = strip_scheme(cid.trim());
+    if cid.is_empty() {
+        return Err(NftError::Invalid("image_cid must not be empty".into()));
+    }
+    let invalid = || NftError::Invalid(format!("image_cid {:?} is not a valid IPFS CID", cid));
+
+    if cid.starts_with("Qm") {
+        let ok = cid.len() == 46 && cid.chars().all(|c| BASE58_ALPHABET.contains(c));
+        return if ok { Ok(()) } else { Err(invalid()) };
+    }
+
+    let mut chars = cid.chars();
+    let prefix = chars.next().ok_or_else(invalid)?;
+    let body = chars.as_str();
+    let bytes = match prefix {
+        'b' => decode_base32(body),
+        'B' => decode_base32(&body.to_lowercase()),
+        'z' => decode_base58(body),
+        'f' | 'F' => decode_base16(body),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+
+    // version, codec, multihash code, multihash length.
+    if bytes.len() < 4 || bytes[0] != 0x01 {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+fn encode_base32(bytes: &[u8]) -> String {
+    let alphabet = BASE32_ALPHABET.as_bytes();
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(alphabet[(buffer >> bits) as usize & 31] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        out.push(alphabet[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    out
+}
+
+fn decode_base32(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in s.chars() {
+        buffer = (buffer << 5) | BASE32_ALPHABET.find(c)? as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+fn decode_base58(s: &str) -> Option<Vec<u8>> {
+    // Little-endian base-256 digits, grown as the value grows.
+    let mut out: Vec<u8> = Vec::new();
+    for c in s.chars() {
+        let mut carry = BASE58_ALPHABET.find(c)? as u32;
+        for byte in out.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            out.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = s.chars().take_while(|&c| c == '1').count();
+    out.extend(std::iter::repeat_n(0, zeros));
+    out.reverse();
+    Some(out)
+}
+
+fn decode_base16(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_v0_and_v1() {
+        validate_cid("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG").unwrap();
+        validate_cid("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+        validate_cid("ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+        validate_cid(&raw_cid(b"image")).unwrap();
+    }
+
+    #[test]
+    fn rejects_malformed_cids() {
+        for cid in [
+            "",
+            "ipfs://",
+            "not-a-cid",
+            // Too short, and with base58's excluded `0`, `O`, `I` and `l`.
+            "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbd",
+            "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPb0l",
+            "bafybeig!",
+            // Valid base32, but not CID version 1.
+            "baaaaaaaa",
+        ] {
+            assert!(
+                matches!(validate_cid(cid), Err(NftError::Invalid(_))),
+                "{:?} was accepted",
+                cid
+            );
+        }
+    }
+}
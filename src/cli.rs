@@ -0,0 +1,76 @@
+use crate::{
+    attributes::{encode_attributes, Attributes},
+    config::{Command, MintArgs, TransferArgs, ViewArgs},
+    extras::NftExtras,
+    ids::normalize_id,
+    mint::{mint_nft, MintItem, MintOptions},
+    persist::{load_or_new, Persist},
+    reveal::reveal_view,
+    royalty::Royalty,
+    transfer::transfer_nft,
+    STATE_PATH,
+};
+use penumbra_nft::types::NFTMetadata;
+use std::path::Path;
+
+// Runs a one-shot command against the state file, using the same functions
+// as the HTTP handlers.
+pub fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Serve(_) => unreachable!("serve is handled by main"),
+        Command::Mint(args) => mint(args),
+        Command::Transfer(args) => transfer(args),
+        Command::View(args) => view(args),
+    }
+}
+
+fn mint(args: MintArgs) -> Result<(), String> {
+    let path = Path::new(STATE_PATH);
+    let mut state = load_or_new(path).map_err(|e| e.to_string())?;
+    let attributes =
+        serde_json::from_str(&args.attributes).unwrap_or(Attributes::Legacy(args.attributes));
+    let item = MintItem {
+        metadata: NFTMetadata {
+            name: args.name,
+            description: args.description,
+            image_cid: args.image_cid,
+            attributes: encode_attributes(&attributes.into_vec()),
+            shielded: !args.public,
+        },
+        extras: NftExtras {
+            collection: args.collection,
+            royalty: Royalty::from_parts(args.royalty_bps, args.royalty_recipient),
+            expires_at: args.expires_at,
+            ..Default::default()
+        },
+        max_supply: args.max_supply,
+        transfer_cooldown_secs: args.transfer_cooldown_secs,
+        options: MintOptions {
+            upstream_param: args.upstream_param,
+        },
+    };
+    let id = mint_nft(&mut state, args.owner, item).map_err(|e| e.to_string())?;
+    state.save_to_file(path).map_err(|e| e.to_string())?;
+    println!("{}", id);
+    Ok(())
+}
+
+fn transfer(args: TransferArgs) -> Result<(), String> {
+    let path = Path::new(STATE_PATH);
+    let mut state = load_or_new(path).map_err(|e| e.to_string())?;
+    transfer_nft(&mut state, &normalize_id(&args.id), &args.to).map_err(|e| e.to_string())?;
+    state.save_to_file(path).map_err(|e| e.to_string())?;
+    println!("ok");
+    Ok(())
+}
+
+fn view(args: ViewArgs) -> Result<(), String> {
+    let state = load_or_new(Path::new(STATE_PATH)).map_err(|e| e.to_string())?;
+    let view = reveal_view(&state, &normalize_id(&args.id), args.viewing_key.as_deref())
+        .ok_or_else(|| format!("NFT {} not found", args.id))?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&view).map_err(|e| e.to_string())?
+    );
+    Ok(())
+}
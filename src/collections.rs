@@ -0,0 +1,137 @@
+use crate::{
+    app::AppState, burn::is_burned, error::NftError, list::NFTSummary, reservation::reserved_count,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Created on the first mint into a collection.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CollectionInfo {
+    pub max_supply: Option<u32>,
+    // Every mint counts, so burning does not free up supply.
+    pub minted: u32,
+    // Seconds after minting before an NFT in this collection can be transferred.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_cooldown_secs: Option<u64>,
+}
+
+// Fails unless `count` more NFTs fit in `name` alongside its minted and
+// reserved ones. `max_supply` is what the caller asked for; it may only be set
+// by the mint that creates the collection.
+pub fn check_supply(
+    state: &AppState,
+    name: &str,
+    max_supply: Option<u32>,
+    count: u32,
+) -> Result<(), NftError> {
+    let (cap, minted) = match state.collections.get(name) {
+        Some(info) => {
+            if max_supply.is_some() && max_supply != info.max_supply {
+                return Err(NftError::Invalid(format!(
+                    "max_supply can only be set by the first mint into collection {}",
+                    name
+                )));
+            }
+            (info.max_supply, info.minted)
+        }
+        None => (max_supply, 0),
+    };
+    let reserved = reserved_count(state, name);
+    match cap {
+        Some(cap) if minted.saturating_add(reserved).saturating_add(count) > cap => {
+            Err(NftError::Conflict(format!(
+                "collection {} sold out ({} of {} minted, {} reserved)",
+                name, minted, cap, reserved
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct CollectionCount {
+    pub name: String,
+    pub count: usize,
+}
+
+// Summaries of every NFT minted into `name`, ordered by id.
+pub fn nfts_in_collection(state: &AppState, name: &str) -> Vec<NFTSummary> {
+    let mut summaries: Vec<NFTSummary> = state
+        .extras
+        .iter()
+        .filter(|(_, extras)| extras.collection.as_deref() == Some(name))
+        .filter(|(id, _)| !is_burned(state, id))
+        .filter_map(|(id, _)| state.ledger.get_nft(id))
+        .map(|nft| NFTSummary::new(state, nft))
+        .collect();
+    summaries.sort_by(|a, b| a.id.cmp(&b.id));
+    summaries
+}
+
+// Distinct collection names with how many live NFTs each holds, ordered by name.
+pub fn collection_counts(state: &AppState) -> Vec<CollectionCount> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for (id, extras) in &state.extras {
+        if let Some(name) = &extras.collection {
+            if state.ledger.get_nft(id).is_some() && !is_burned(state, id) {
+                *counts.entry(name).or_default() += 1;
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(name, count)| CollectionCount {
+            name: name.to_string(),
+            count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mint::mint_nft, testutil};
+
+    fn mint_into(state: &mut AppState, collection: &str) -> String {
+        let mut item = testutil::item("member");
+        item.extras.collection = Some(collection.to_string());
+        mint_nft(state, "alice".to_string(), item).unwrap()
+    }
+
+    #[test]
+    fn nfts_are_grouped_by_collection() {
+        let (mut state, _) = testutil::state();
+        let mut apes: Vec<String> = (0..3).map(|_| mint_into(&mut state, "apes")).collect();
+        let cat = mint_into(&mut state, "cats");
+        testutil::mint(&mut state, "alice");
+        apes.sort();
+
+        let listed: Vec<String> = nfts_in_collection(&state, "apes")
+            .into_iter()
+            .map(|summary| summary.id)
+            .collect();
+        assert_eq!(listed, apes);
+        assert_eq!(nfts_in_collection(&state, "cats")[0].id, cat);
+        assert!(nfts_in_collection(&state, "dogs").is_empty());
+
+        let counts: Vec<(String, usize)> = collection_counts(&state)
+            .into_iter()
+            .map(|count| (count.name, count.count))
+            .collect();
+        assert_eq!(counts, [("apes".to_string(), 3), ("cats".to_string(), 1)]);
+    }
+
+    #[test]
+    fn mints_stop_at_max_supply() {
+        let (mut state, _) = testutil::state();
+        let mut item = testutil::item("capped");
+        item.extras.collection = Some("apes".to_string());
+        item.max_supply = Some(2);
+        mint_nft(&mut state, "alice".to_string(), item.clone()).unwrap();
+        mint_into(&mut state, "apes");
+        let err = mint_nft(&mut state, "alice".to_string(), item);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        assert_eq!(state.collections["apes"].minted, 2);
+        assert_eq!(nfts_in_collection(&state, "apes").len(), 2);
+    }
+}
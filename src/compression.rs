@@ -0,0 +1,21 @@
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+// Below this many bytes the encoding overhead outweighs the savings.
+pub const MIN_COMPRESS_BYTES: u16 = 1024;
+
+// gzip or brotli, whichever the client's Accept-Encoding prefers. Raster
+// images are already compressed and skipped; SVG (the QR codes) is text and
+// compresses well.
+pub fn compression_layer() -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(MIN_COMPRESS_BYTES)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+    CompressionLayer::new()
+        .gzip(true)
+        .br(true)
+        .compress_when(predicate)
+}
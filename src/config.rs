@@ -0,0 +1,375 @@
+use crate::{
+    idempotency::DEFAULT_TTL_SECS,
+    limits::{
+        FieldLimits, DEFAULT_MAX_ATTRIBUTES, DEFAULT_MAX_ATTRIBUTES_BYTES,
+        DEFAULT_MAX_DESCRIPTION_BYTES, DEFAULT_MAX_NAME_BYTES,
+    },
+    mint::DEFAULT_UPSTREAM_PARAM,
+    reservation::DEFAULT_RESERVATION_TTL_SECS,
+    staking::DEFAULT_REWARD_RATE,
+    tenant::{parse_tenant, TenantSpec},
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+// With no subcommand the server starts, taking the `serve` flags directly.
+#[derive(Parser, Debug)]
+#[command(
+    name = "pnft-cli-rpc",
+    version,
+    about = "Penumbra NFT RPC server and CLI",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub serve: Config,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the HTTP RPC server.
+    Serve(Box<Config>),
+    /// Mint an NFT into the state file.
+    Mint(MintArgs),
+    /// Transfer an NFT in the state file.
+    Transfer(TransferArgs),
+    /// Print an NFT from the state file.
+    View(ViewArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct Config {
+    /// Address to listen on.
+    #[arg(long, env = "PNFT_BIND", default_value = "127.0.0.1")]
+    pub bind: String,
+
+    /// Port to listen on.
+    #[arg(long, env = "PNFT_PORT", default_value_t = 3000)]
+    pub port: u16,
+
+    /// PEM certificate chain to serve HTTPS with; needs --tls-key.
+    #[arg(long, env = "PNFT_TLS_CERT", requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key for --tls-cert. Without both, plain HTTP is served.
+    #[arg(long, env = "PNFT_TLS_KEY", requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Extra namespace served under /t/<name>/ with its own NFTs, as `<name>` or
+    /// `<name>=<admin key>`; repeatable. Stored beside the default state, e.g.
+    /// `state.<name>.json`. Without its own key a tenant uses --admin-key.
+    #[arg(
+        long = "tenant",
+        env = "PNFT_TENANTS",
+        value_delimiter = ',',
+        value_parser = parse_tenant,
+        hide_env_values = true
+    )]
+    pub tenants: Vec<TenantSpec>,
+
+    /// Where state is persisted between runs.
+    #[arg(long, env = "PNFT_STORE", value_enum, default_value_t = StoreKind::Json)]
+    pub store: StoreKind,
+
+    /// Database directory for `--store sled`.
+    #[arg(long, env = "PNFT_SLED_PATH", default_value = "state.sled")]
+    pub sled_path: PathBuf,
+
+    /// Seconds in-flight requests get to finish after SIGINT/SIGTERM.
+    #[arg(long, env = "PNFT_SHUTDOWN_TIMEOUT_SECS", default_value_t = 30)]
+    pub shutdown_timeout_secs: u64,
+
+    /// Seconds a request may take before it is answered with 504; 0 disables the limit.
+    #[arg(long, env = "PNFT_REQUEST_TIMEOUT_SECS", default_value_t = 30)]
+    pub request_timeout_secs: u64,
+
+    /// Staking reward units accrued per second per staked NFT.
+    #[arg(long, env = "PNFT_REWARD_RATE", default_value_t = DEFAULT_REWARD_RATE)]
+    pub reward_rate: u64,
+
+    /// Seconds a staked NFT must stay staked before it can be unstaked.
+    #[arg(long, env = "PNFT_STAKE_LOCKUP_SECS", default_value_t = 0)]
+    pub stake_lockup_secs: u64,
+
+    /// Balance deducted from the new owner for each mint; 0 disables fees.
+    #[arg(long, env = "PNFT_MINT_FEE", default_value_t = 0)]
+    pub mint_fee: u64,
+
+    /// Origin allowed to make cross-origin requests; repeatable.
+    #[arg(long = "cors-origin", env = "PNFT_CORS_ORIGINS", value_delimiter = ',')]
+    pub cors_origins: Vec<String>,
+
+    /// Allow any origin. Intended for local development.
+    #[arg(long, env = "PNFT_CORS_PERMISSIVE")]
+    pub cors_permissive: bool,
+
+    /// Mutating requests allowed per client IP per minute; 0 disables the limit.
+    #[arg(long, env = "PNFT_RATE_LIMIT_PER_MIN", default_value_t = 60)]
+    pub rate_limit_per_min: u32,
+
+    /// Largest request body accepted, in bytes. Oversized bodies get 413.
+    #[arg(long, env = "PNFT_BODY_LIMIT_BYTES", default_value_t = 256 * 1024)]
+    pub body_limit_bytes: usize,
+
+    /// Body limit for the batch routes (/mint/batch, /airdrop/mint, /simulate/airdrop,
+    /// /transfer/batch, /stake/batch, /unstake/batch, /burn/batch, /tx) and /import.
+    #[arg(long, env = "PNFT_BATCH_BODY_LIMIT_BYTES", default_value_t = 4 * 1024 * 1024)]
+    pub batch_body_limit_bytes: usize,
+
+    /// Longest NFT name accepted at mint or metadata update, in bytes.
+    #[arg(long, env = "PNFT_MAX_NAME_BYTES", default_value_t = DEFAULT_MAX_NAME_BYTES)]
+    pub max_name_bytes: usize,
+
+    /// Longest NFT description accepted, in bytes.
+    #[arg(long, env = "PNFT_MAX_DESCRIPTION_BYTES", default_value_t = DEFAULT_MAX_DESCRIPTION_BYTES)]
+    pub max_description_bytes: usize,
+
+    /// Largest attributes accepted, in bytes of their stored JSON encoding.
+    #[arg(long, env = "PNFT_MAX_ATTRIBUTES_BYTES", default_value_t = DEFAULT_MAX_ATTRIBUTES_BYTES)]
+    pub max_attributes_bytes: usize,
+
+    /// Most attributes one NFT may carry. Repeated trait_types are always rejected.
+    #[arg(long, env = "PNFT_MAX_ATTRIBUTES", default_value_t = DEFAULT_MAX_ATTRIBUTES)]
+    pub max_attributes: usize,
+
+    /// Directory where POST /mint/upload stores images, named by CID.
+    #[arg(long, env = "PNFT_BLOB_DIR", default_value = "blobs")]
+    pub blob_dir: PathBuf,
+
+    /// Largest image POST /mint/upload accepts, in bytes.
+    #[arg(long, env = "PNFT_UPLOAD_LIMIT_BYTES", default_value_t = 5 * 1024 * 1024)]
+    pub upload_limit_bytes: usize,
+
+    /// IPFS gateway GET /nft/:id/image points at, as `<gateway>/<cid>`.
+    #[arg(
+        long,
+        env = "PNFT_IPFS_GATEWAY",
+        default_value = "https://ipfs.io/ipfs"
+    )]
+    pub ipfs_gateway: String,
+
+    /// Fetch images through the gateway and serve the bytes instead of redirecting.
+    #[arg(long, env = "PNFT_IMAGE_PROXY")]
+    pub image_proxy: bool,
+
+    /// Bearer token required on the /mint, /airdrop, /credit, backup and collection policy routes.
+    /// Unset leaves them open, and disables the /admin overrides entirely.
+    #[arg(long, env = "PNFT_ADMIN_KEY", hide_env_values = true)]
+    pub admin_key: Option<String>,
+
+    /// URL every recorded event is POSTed to as JSON, signed with --webhook-secret.
+    #[arg(long, env = "PNFT_WEBHOOK_URL", requires = "webhook_secret")]
+    pub webhook_url: Option<String>,
+
+    /// Shared secret for the webhook's `X-Pnft-Signature: sha256=<hex HMAC of the body>`.
+    #[arg(long, env = "PNFT_WEBHOOK_SECRET", hide_env_values = true)]
+    pub webhook_secret: Option<String>,
+
+    /// Retries per event, with exponential backoff, before a delivery is dropped.
+    #[arg(long, env = "PNFT_WEBHOOK_MAX_RETRIES", default_value_t = 5)]
+    pub webhook_max_retries: u32,
+
+    /// Hex 32-byte ed25519 seed for signing attribute proofs. Random per run if unset.
+    #[arg(long, env = "PNFT_PROOF_KEY", hide_env_values = true)]
+    pub proof_key: Option<String>,
+
+    /// How shielded NFTs are shown to callers without a valid viewing key.
+    #[arg(long, env = "PNFT_DEFAULT_REVEAL", value_enum, default_value_t = RevealMode::RedactShielded)]
+    pub default_reveal: RevealMode,
+
+    /// Fields GET /view/:id/public shows for collections without their own policy.
+    #[arg(
+        long = "public-field",
+        env = "PNFT_PUBLIC_FIELDS",
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = PublicField::DEFAULTS
+    )]
+    pub public_fields: Vec<PublicField>,
+
+    /// How new NFT ids are chosen.
+    #[arg(long, env = "PNFT_ID_SCHEME", value_enum, default_value_t = IdScheme::Uuid)]
+    pub id_scheme: IdScheme,
+
+    /// Number of encoded /view responses kept in memory; 0 disables the cache.
+    #[arg(long, env = "PNFT_VIEW_CACHE_SIZE", default_value_t = 0)]
+    pub view_cache_size: usize,
+
+    /// How long a /mint Idempotency-Key is remembered.
+    #[arg(long, env = "PNFT_IDEMPOTENCY_TTL_SECS", default_value_t = DEFAULT_TTL_SECS)]
+    pub idempotency_ttl_secs: u64,
+
+    /// Seconds a /mint/reserve slot is held before its supply is released.
+    #[arg(long, env = "PNFT_RESERVATION_TTL_SECS", default_value_t = DEFAULT_RESERVATION_TTL_SECS)]
+    pub reservation_ttl_secs: u64,
+
+    /// Seconds between sweeps for expired reservations and offer locks; 0 disables them.
+    #[arg(long, env = "PNFT_SWEEP_INTERVAL_SECS", default_value_t = 60)]
+    pub sweep_interval_secs: u64,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum StoreKind {
+    /// One JSON file (state.json), rewritten on every change.
+    Json,
+    /// In memory only; lost on restart.
+    Memory,
+    /// sled database, writing only changed NFTs.
+    Sled,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum IdScheme {
+    /// Random, as assigned by penumbra_nft.
+    #[default]
+    Uuid,
+    /// Hash of owner, metadata and collection; identical mints get 409.
+    ContentHash,
+    /// `<collection>:0001` and up; NFTs outside a collection stay random.
+    Sequence,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum RevealMode {
+    /// Reveal every NFT, shielded or not; for fully public galleries.
+    PublicOnly,
+    /// Show only the id of a shielded NFT unless a valid viewing key is given.
+    #[default]
+    RedactShielded,
+}
+
+// What GET /view/:id/public may show without a viewing key. Owner and
+// attributes are never among them.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublicField {
+    Name,
+    Description,
+    ImageCid,
+    Collection,
+    Edition,
+    Status,
+    Staked,
+}
+
+impl PublicField {
+    pub const DEFAULTS: [PublicField; 4] = [
+        PublicField::Name,
+        PublicField::Collection,
+        PublicField::Edition,
+        PublicField::Status,
+    ];
+}
+
+impl Config {
+    pub fn addr(&self) -> Result<SocketAddr, String> {
+        format!("{}:{}", self.bind, self.port)
+            .parse()
+            .map_err(|e| format!("invalid bind address {:?}: {}", self.bind, e))
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    pub fn sweep_interval(&self) -> Option<Duration> {
+        (self.sweep_interval_secs > 0).then(|| Duration::from_secs(self.sweep_interval_secs))
+    }
+
+    pub fn request_timeout(&self) -> Option<Duration> {
+        (self.request_timeout_secs > 0).then(|| Duration::from_secs(self.request_timeout_secs))
+    }
+
+    pub fn field_limits(&self) -> FieldLimits {
+        FieldLimits {
+            name: self.max_name_bytes,
+            description: self.max_description_bytes,
+            attributes: self.max_attributes_bytes,
+            attribute_count: self.max_attributes,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct MintArgs {
+    #[arg(long)]
+    pub owner: String,
+    #[arg(long)]
+    pub name: String,
+    #[arg(long, default_value = "")]
+    pub description: String,
+    #[arg(long)]
+    pub image_cid: String,
+    /// JSON array of `{ trait_type, value }`, or a free-form string.
+    #[arg(long, default_value = "[]")]
+    pub attributes: String,
+    #[arg(long)]
+    pub collection: Option<String>,
+    /// Supply cap, set by the first mint into a collection.
+    #[arg(long, requires = "collection")]
+    pub max_supply: Option<u32>,
+    /// Seconds after minting before NFTs in the collection can be transferred,
+    /// set by the first mint into a collection.
+    #[arg(long, requires = "collection")]
+    pub transfer_cooldown_secs: Option<u64>,
+    /// Secondary-sale royalty in basis points (at most 10000).
+    #[arg(long, requires = "royalty_recipient")]
+    pub royalty_bps: Option<u16>,
+    #[arg(long)]
+    pub royalty_recipient: Option<String>,
+    /// Mint unshielded, so it can be viewed without a viewing key.
+    #[arg(long)]
+    pub public: bool,
+    /// Unix seconds after which the NFT can't be transferred.
+    #[arg(long)]
+    pub expires_at: Option<u64>,
+    /// Trailing argument passed to penumbra_nft's mint_nft.
+    #[arg(long, default_value_t = DEFAULT_UPSTREAM_PARAM)]
+    pub upstream_param: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct TransferArgs {
+    #[arg(long)]
+    pub id: String,
+    #[arg(long)]
+    pub to: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ViewArgs {
+    #[arg(long)]
+    pub id: String,
+    #[arg(long)]
+    pub viewing_key: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Config {
+        let cli = Cli::try_parse_from(std::iter::once("pnft-cli-rpc").chain(args.iter().copied()));
+        cli.unwrap().serve
+    }
+
+    #[test]
+    fn listens_on_localhost_3000_by_default() {
+        assert_eq!(
+            parse(&[]).addr().unwrap(),
+            "127.0.0.1:3000".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn bind_and_port_can_be_overridden() {
+        let config = parse(&["--bind", "0.0.0.0", "--port", "8080"]);
+        assert_eq!(config.addr().unwrap(), "0.0.0.0:8080".parse().unwrap());
+        assert!(parse(&["--bind", "not an address"]).addr().is_err());
+        assert!(Cli::try_parse_from(["pnft-cli-rpc", "--port", "70000"]).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{app::AppState, error::NftError};
+
+// Unix seconds before which `id` can't change hands: its mint time plus its
+// collection's transfer cooldown. None when no cooldown applies.
+pub fn cooldown_ends_at(state: &AppState, id: &str) -> Option<u64> {
+    let extras = state.extras.get(id)?;
+    let cooldown = state
+        .collections
+        .get(extras.collection.as_deref()?)?
+        .transfer_cooldown_secs?;
+    Some(extras.minted_at?.saturating_add(cooldown))
+}
+
+// Checked by every path that moves an existing NFT to a new owner.
+pub fn ensure_cooldown_elapsed(state: &AppState, id: &str) -> Result<(), NftError> {
+    match cooldown_ends_at(state, id) {
+        Some(ends_at) if state.now() < ends_at => Err(NftError::Locked(format!(
+            "NFT {} is still in cooldown until {}",
+            id, ends_at
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mint::mint_nft,
+        signature::transfer_message,
+        testutil::{self, Key},
+        transfer::transfer_signed,
+    };
+
+    #[test]
+    fn transfer_waits_out_the_cooldown() {
+        let (mut state, clock) = testutil::state();
+        let alice = Key::new(1);
+        let mut item = testutil::item("fresh");
+        item.extras.collection = Some("drops".to_string());
+        item.transfer_cooldown_secs = Some(60);
+        let id = mint_nft(&mut state, alice.address(), item).unwrap();
+        assert_eq!(cooldown_ends_at(&state, &id), Some(testutil::START + 60));
+
+        clock.advance(59);
+        let signature = alice.sign(&transfer_message("", &id, "bob", 1));
+        let err = transfer_signed(&mut state, &id, &alice.address(), "bob", 1, &signature);
+        assert!(matches!(err, Err(NftError::Locked(_))));
+
+        clock.advance(1);
+        let signature = alice.sign(&transfer_message("", &id, "bob", 2));
+        transfer_signed(&mut state, &id, &alice.address(), "bob", 2, &signature).unwrap();
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, "bob");
+    }
+}
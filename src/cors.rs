@@ -0,0 +1,77 @@
+use crate::config::Config;
+use axum::http::{header, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+// Permissive in dev mode; otherwise only the configured origins may call the
+// API cross-origin (none, if the list is empty).
+pub fn cors_layer(config: &Config) -> Result<CorsLayer, String> {
+    let layer = CorsLayer::new()
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
+    if config.cors_permissive {
+        return Ok(layer.allow_origin(Any));
+    }
+    let origins = config
+        .cors_origins
+        .iter()
+        .map(|origin| {
+            HeaderValue::from_str(origin.trim())
+                .map_err(|_| format!("invalid CORS origin {:?}", origin))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(layer.allow_origin(AllowOrigin::list(origins)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Cli;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use clap::Parser;
+    use tower::ServiceExt;
+
+    fn preflight(origin: &str) -> Request<Body> {
+        Request::options("/nfts")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn preflight_gets_cors_headers_for_allowed_origins() {
+        let cli = Cli::parse_from(["pnft-cli-rpc", "--cors-origin", "https://app.example"]);
+        let app = Router::new()
+            .route("/nfts", get(|| async { "[]" }))
+            .layer(cors_layer(&cli.serve).unwrap());
+
+        let response = app
+            .clone()
+            .oneshot(preflight("https://app.example"))
+            .await
+            .unwrap();
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example"
+        );
+        assert!(headers[header::ACCESS_CONTROL_ALLOW_METHODS]
+            .to_str()
+            .unwrap()
+            .contains("POST"));
+
+        let response = app
+            .oneshot(preflight("https://evil.example"))
+            .await
+            .unwrap();
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+}
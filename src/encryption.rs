@@ -0,0 +1,131 @@
+use crate::config::StoreKind;
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use std::io;
+
+// Env var holding the hex 32-byte AES-256-GCM key for the state file. Read
+// by both the server and the CLI subcommands, which share that file.
+pub const STATE_KEY_VAR: &str = "PNFT_STATE_KEY";
+
+// Encrypted state files are `MAGIC || nonce (12 bytes) || ciphertext`.
+const MAGIC: &[u8] = b"PNFTENC1";
+const NONCE_LEN: usize = 12;
+
+pub struct StateKey(Aes256Gcm);
+
+impl StateKey {
+    // The configured key, or None to keep the file as plaintext JSON.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        match std::env::var(STATE_KEY_VAR) {
+            Ok(hex) if !hex.trim().is_empty() => Self::from_hex(hex.trim()).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn from_hex(hex: &str) -> io::Result<Self> {
+        let bytes: [u8; 32] = hex::decode(hex)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| invalid(format!("{} must be 32 hex-encoded bytes", STATE_KEY_VAR)))?;
+        Ok(StateKey(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(
+            &bytes,
+        ))))
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| invalid("failed to encrypt state".into()))?;
+        let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    fn open(&self, sealed: &[u8]) -> io::Result<Vec<u8>> {
+        let body = &sealed[MAGIC.len()..];
+        if body.len() < NONCE_LEN {
+            return Err(invalid("encrypted state file is truncated".into()));
+        }
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+        self.0
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                invalid(format!(
+                    "failed to decrypt state file: wrong {} or the file is corrupted",
+                    STATE_KEY_VAR
+                ))
+            })
+    }
+}
+
+// Only the JSON file is encrypted. sled keeps values as written, so a key
+// given with it would leave shielded metadata in plaintext while appearing
+// to protect it; the server refuses to start instead.
+pub fn check_store(store: StoreKind, key: Option<&StateKey>) -> Result<(), String> {
+    match (store, key) {
+        (StoreKind::Sled, Some(_)) => Err(format!(
+            "{} is set, but --store sled does not encrypt its data; use --store json or unset it",
+            STATE_KEY_VAR
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+// The JSON in a state file as read from disk. Plaintext files load with or
+// without a key, so turning encryption on doesn't strand an existing file;
+// the next save encrypts it.
+pub fn unseal(bytes: Vec<u8>, key: Option<&StateKey>) -> io::Result<Vec<u8>> {
+    match (is_encrypted(&bytes), key) {
+        (false, _) => Ok(bytes),
+        (true, Some(key)) => key.open(&bytes),
+        (true, None) => Err(invalid(format!(
+            "state file is encrypted; set {} to load it",
+            STATE_KEY_VAR
+        ))),
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> StateKey {
+        StateKey::from_hex(&hex::encode([byte; 32])).unwrap()
+    }
+
+    #[test]
+    fn sealed_state_round_trips() {
+        let sealed = key(1).seal(br#"{"nfts":{}}"#).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_eq!(unseal(sealed, Some(&key(1))).unwrap(), br#"{"nfts":{}}"#);
+    }
+
+    #[test]
+    fn wrong_key_fails_to_decrypt() {
+        let sealed = key(1).seal(b"{}").unwrap();
+        let err = unseal(sealed.clone(), Some(&key(2))).unwrap_err();
+        assert!(err.to_string().contains("failed to decrypt"));
+        assert!(unseal(sealed, None).is_err());
+    }
+
+    #[test]
+    fn key_is_refused_with_sled() {
+        assert!(check_store(StoreKind::Sled, Some(&key(1))).is_err());
+        assert!(check_store(StoreKind::Sled, None).is_ok());
+        assert!(check_store(StoreKind::Json, Some(&key(1))).is_ok());
+    }
+}
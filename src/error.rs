@@ -0,0 +1,166 @@
+use crate::{ibc::ImportError, upload::UploadError};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::{any::Any, fmt};
+
+// Errors returned by the RPC handlers, rendered as `{ code, message }` JSON.
+#[derive(Debug)]
+pub enum NftError {
+    NotFound(String),
+    Unauthorized(String),
+    Forbidden(String),
+    Invalid(String),
+    PaymentRequired(String),
+    Conflict(String),
+    Locked(String),
+    TooLarge(String),
+    RateLimited(String),
+    Storage(String),
+    BadGateway(String),
+    Timeout(String),
+}
+
+impl NftError {
+    fn status(&self) -> StatusCode {
+        match self {
+            NftError::NotFound(_) => StatusCode::NOT_FOUND,
+            NftError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            NftError::Forbidden(_) => StatusCode::FORBIDDEN,
+            NftError::Invalid(_) => StatusCode::BAD_REQUEST,
+            NftError::PaymentRequired(_) => StatusCode::PAYMENT_REQUIRED,
+            NftError::Conflict(_) => StatusCode::CONFLICT,
+            NftError::Locked(_) => StatusCode::LOCKED,
+            NftError::TooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            NftError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            NftError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            NftError::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            NftError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            NftError::NotFound(_) => "not_found",
+            NftError::Unauthorized(_) => "unauthorized",
+            NftError::Forbidden(_) => "forbidden",
+            NftError::Invalid(_) => "invalid_request",
+            NftError::PaymentRequired(_) => "payment_required",
+            NftError::Conflict(_) => "conflict",
+            NftError::Locked(_) => "locked",
+            NftError::TooLarge(_) => "payload_too_large",
+            NftError::RateLimited(_) => "rate_limited",
+            NftError::Storage(_) => "storage_error",
+            NftError::BadGateway(_) => "bad_gateway",
+            NftError::Timeout(_) => "timeout",
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            NftError::NotFound(m)
+            | NftError::Unauthorized(m)
+            | NftError::Forbidden(m)
+            | NftError::Invalid(m)
+            | NftError::PaymentRequired(m)
+            | NftError::Conflict(m)
+            | NftError::Locked(m)
+            | NftError::TooLarge(m)
+            | NftError::RateLimited(m)
+            | NftError::Storage(m)
+            | NftError::BadGateway(m)
+            | NftError::Timeout(m) => m,
+        }
+    }
+}
+
+impl fmt::Display for NftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl NftError {
+    // The same error with `prefix` in front of its message, e.g. naming the
+    // failing item of a batch.
+    pub fn prefixed(self, prefix: impl fmt::Display) -> Self {
+        let wrap = |message: String| format!("{}: {}", prefix, message);
+        match self {
+            NftError::NotFound(m) => NftError::NotFound(wrap(m)),
+            NftError::Unauthorized(m) => NftError::Unauthorized(wrap(m)),
+            NftError::Forbidden(m) => NftError::Forbidden(wrap(m)),
+            NftError::Invalid(m) => NftError::Invalid(wrap(m)),
+            NftError::PaymentRequired(m) => NftError::PaymentRequired(wrap(m)),
+            NftError::Conflict(m) => NftError::Conflict(wrap(m)),
+            NftError::Locked(m) => NftError::Locked(wrap(m)),
+            NftError::TooLarge(m) => NftError::TooLarge(wrap(m)),
+            NftError::RateLimited(m) => NftError::RateLimited(wrap(m)),
+            NftError::Storage(m) => NftError::Storage(wrap(m)),
+            NftError::BadGateway(m) => NftError::BadGateway(wrap(m)),
+            NftError::Timeout(m) => NftError::Timeout(wrap(m)),
+        }
+    }
+}
+
+impl From<ImportError> for NftError {
+    fn from(err: ImportError) -> Self {
+        match err {
+            ImportError::Malformed(_)
+            | ImportError::UnsupportedVersion(_)
+            | ImportError::Checksum { .. } => NftError::Invalid(err.to_string()),
+            ImportError::Duplicate(_) => NftError::Conflict(err.to_string()),
+        }
+    }
+}
+
+impl From<UploadError> for NftError {
+    fn from(err: UploadError) -> Self {
+        match err {
+            UploadError::Invalid(message) => NftError::Invalid(message),
+            UploadError::TooLarge(message) => NftError::TooLarge(message),
+            UploadError::Io(err) => NftError::Storage(format!("failed to store image: {}", err)),
+        }
+    }
+}
+
+impl From<std::io::Error> for NftError {
+    fn from(err: std::io::Error) -> Self {
+        NftError::Storage(format!("failed to persist state: {}", err))
+    }
+}
+
+impl IntoResponse for NftError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            code: self.code(),
+            message: self.message().to_string(),
+        };
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+// What a panicking handler's client gets instead of a dropped connection.
+// The state lock is a tokio RwLock, which is released on unwind without
+// poisoning, so requests after this one carry on as normal.
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let detail = panic
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown panic");
+    tracing::error!(panic = detail, "handler panicked");
+    let body = ErrorBody {
+        code: "internal_error",
+        message: "internal server error".into(),
+    };
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct ErrorBody {
+    #[schema(value_type = String)]
+    code: &'static str,
+    message: String,
+}
@@ -0,0 +1,24 @@
+use axum::http::{header::IF_NONE_MATCH, HeaderMap};
+
+// Weak, since the body is re-serialized on every request. The variant is part
+// of the tag so a redacted response never validates a full one.
+pub fn view_etag(id: &str, version: u64, full: bool) -> String {
+    let variant = if full { "full" } else { "redacted" };
+    format!("W/\"{}-v{}-{}\"", id, version, variant)
+}
+
+// Whether `If-None-Match` lists `etag` (or `*`), meaning a 304 will do.
+pub fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || weak_eq(tag, etag))
+}
+
+// If-None-Match uses weak comparison: the `W/` prefix is ignored.
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
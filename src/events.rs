@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Mint,
+    Transfer,
+    Approve,
+    Stake,
+    Unstake,
+    Claim,
+    Airdrop,
+    Burn,
+    IbcImport,
+    IbcExport,
+    BackupImport,
+    MetadataUpdate,
+    Freeze,
+    Unfreeze,
+    Repin,
+    OfferLock,
+    OfferCancel,
+    // An offer lock released by the sweeper once it ran out.
+    OfferExpire,
+    // A mint reservation dropped unfinalized. Its `nft_id` is the
+    // reservation id and `to` the collection the slot went back to.
+    ReservationExpire,
+    Share,
+    ShareConsent,
+    // Admin overrides of the owner and lockup checks.
+    ForceUnstake,
+    ForceUnfreeze,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NftEvent {
+    pub seq: u64,
+    pub timestamp: u64,
+    pub kind: EventKind,
+    pub nft_id: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+// Narrows /events and /ws/events to one NFT and/or one kind of event.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    pub nft_id: Option<String>,
+    pub kind: Option<EventKind>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &NftEvent) -> bool {
+        self.nft_id.as_ref().is_none_or(|id| *id == event.nft_id)
+            && self.kind.is_none_or(|kind| kind == event.kind)
+    }
+}
+
+// Append-only; `seq` starts at 1 and event `n` lives at index `n - 1`.
+#[derive(Default, Serialize, Deserialize)]
+pub struct EventLog {
+    events: Vec<NftEvent>,
+}
+
+impl EventLog {
+    pub fn push(
+        &mut self,
+        timestamp: u64,
+        kind: EventKind,
+        nft_id: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> &NftEvent {
+        self.events.push(NftEvent {
+            seq: self.events.len() as u64 + 1,
+            timestamp,
+            kind,
+            nft_id: nft_id.to_string(),
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+        });
+        self.events.last().expect("just pushed")
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    // Drops events recorded after the first `len`, for rolling back.
+    pub fn truncate(&mut self, len: usize) {
+        self.events.truncate(len);
+    }
+
+    // Events with a sequence number greater than `seq`.
+    pub fn since(&self, seq: u64) -> &[NftEvent] {
+        let start = (seq as usize).min(self.events.len());
+        &self.events[start..]
+    }
+
+    // Up to `limit` events after `seq` that pass `filter`, oldest first.
+    // Sequence numbers stay global, so the last one returned is the next
+    // `seq` to tail from.
+    pub fn query(&self, seq: u64, filter: &EventFilter, limit: Option<usize>) -> Vec<NftEvent> {
+        self.since(seq)
+            .iter()
+            .filter(|event| filter.matches(event))
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        signature::transfer_message,
+        testutil::{self, Key},
+        transfer::transfer_signed,
+    };
+
+    #[test]
+    fn mint_then_transfer_logs_two_ordered_events() {
+        let (mut state, clock) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        clock.advance(10);
+        let signature = alice.sign(&transfer_message("", &id, "bob", 1));
+        transfer_signed(&mut state, &id, &alice.address(), "bob", 1, &signature).unwrap();
+
+        let events = state.events.since(0);
+        assert_eq!(events.len(), 2);
+        let (mint, transfer) = (&events[0], &events[1]);
+        assert_eq!((mint.seq, mint.kind), (1, EventKind::Mint));
+        assert_eq!(mint.timestamp, testutil::START);
+        assert_eq!(mint.from, None);
+        assert_eq!(mint.to, Some(alice.address()));
+        assert_eq!((transfer.seq, transfer.kind), (2, EventKind::Transfer));
+        assert_eq!(transfer.timestamp, testutil::START + 10);
+        assert_eq!(transfer.from, Some(alice.address()));
+        assert_eq!(transfer.to.as_deref(), Some("bob"));
+        assert!(events.iter().all(|event| event.nft_id == id));
+    }
+
+    // seq: 1 mint a, 2 mint b, 3 stake a, 4 mint c, 5 freeze a, 6 stake b
+    fn log() -> EventLog {
+        let mut log = EventLog::default();
+        for (kind, id) in [
+            (EventKind::Mint, "a"),
+            (EventKind::Mint, "b"),
+            (EventKind::Stake, "a"),
+            (EventKind::Mint, "c"),
+            (EventKind::Freeze, "a"),
+            (EventKind::Stake, "b"),
+        ] {
+            log.push(0, kind, id, None, None);
+        }
+        log
+    }
+
+    fn seqs(events: Vec<NftEvent>) -> Vec<u64> {
+        events.into_iter().map(|event| event.seq).collect()
+    }
+
+    #[test]
+    fn query_filters_by_nft_and_kind() {
+        let log = log();
+        let by_nft = EventFilter {
+            nft_id: Some("a".into()),
+            kind: None,
+        };
+        assert_eq!(seqs(log.query(0, &by_nft, None)), [1, 3, 5]);
+        let by_kind = EventFilter {
+            nft_id: None,
+            kind: Some(EventKind::Mint),
+        };
+        assert_eq!(seqs(log.query(0, &by_kind, None)), [1, 2, 4]);
+        let both = EventFilter {
+            nft_id: Some("b".into()),
+            kind: Some(EventKind::Stake),
+        };
+        assert_eq!(seqs(log.query(0, &both, None)), [6]);
+    }
+
+    #[test]
+    fn query_combines_filters_with_since_and_limit() {
+        let log = log();
+        let by_nft = EventFilter {
+            nft_id: Some("a".into()),
+            kind: None,
+        };
+        assert_eq!(seqs(log.query(1, &by_nft, None)), [3, 5]);
+        assert_eq!(seqs(log.query(0, &by_nft, Some(2))), [1, 3]);
+        // Tailing from the last seq returned picks up where it left off.
+        assert_eq!(seqs(log.query(3, &by_nft, Some(2))), [5]);
+        assert!(log.query(6, &EventFilter::default(), None).is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{app::AppState, error::NftError};
+
+// Expired once the state's clock reaches `expires_at`.
+pub fn is_expired(state: &AppState, id: &str) -> bool {
+    state
+        .extras
+        .get(id)
+        .and_then(|extras| extras.expires_at)
+        .is_some_and(|expires_at| state.now() >= expires_at)
+}
+
+// Checked by every path that moves an NFT to a new owner.
+pub fn ensure_not_expired(state: &AppState, id: &str) -> Result<(), NftError> {
+    if is_expired(state, id) {
+        return Err(NftError::Conflict(format!("NFT {} has expired", id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mint::mint_nft,
+        reveal::{reveal_view, NftView},
+        signature::transfer_message,
+        testutil::{self, Key},
+        transfer::transfer_signed,
+    };
+
+    fn transfer(state: &mut AppState, key: &Key, id: &str) -> Result<(), NftError> {
+        let signature = key.sign(&transfer_message("", id, "bob", 1));
+        transfer_signed(state, id, &key.address(), "bob", 1, &signature)
+    }
+
+    #[test]
+    fn expired_nft_is_viewable_but_cannot_move() {
+        let (mut state, clock) = testutil::state();
+        let alice = Key::new(1);
+        let mut item = testutil::item("fleeting");
+        item.extras.expires_at = Some(testutil::START + 60);
+        let id = mint_nft(&mut state, alice.address(), item).unwrap();
+        clock.advance(60);
+
+        assert!(matches!(
+            reveal_view(&state, &id, None),
+            Some(NftView::Full { expired: true, .. })
+        ));
+        let err = transfer(&mut state, &alice, &id);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, alice.address());
+    }
+
+    #[test]
+    fn nfts_without_an_expiry_are_unaffected() {
+        let (mut state, clock) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        clock.advance(100 * 365 * 24 * 60 * 60);
+        assert!(!is_expired(&state, &id));
+        assert!(matches!(
+            reveal_view(&state, &id, None),
+            Some(NftView::Full { expired: false, .. })
+        ));
+        transfer(&mut state, &alice, &id).unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{offer::OfferLock, royalty::Royalty, shares::Share};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Per-NFT fields this crate tracks alongside penumbra_nft's `NFT`, keyed by id
+// in `AppState::extras`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NftExtras {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    // Frozen NFTs cannot be transferred, airdropped, or burned.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub frozen: bool,
+    // Every owner in order, starting with the minter.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owner_history: Vec<OwnershipRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub royalty: Option<Royalty>,
+    // Serial within a capped collection: `edition` of `edition_of`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition_of: Option<u32>,
+    // Unix seconds after which the NFT can no longer change hands.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "IbcStatus::is_local")]
+    pub ibc_status: IbcStatus,
+    // SHA-256 of the payload handed out by the last export, set while
+    // `ibc_status` is `Exported`; only that payload brings the NFT home.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_digest: Option<String>,
+    #[serde(default, skip_serializing_if = "NftStatus::is_active")]
+    pub status: NftStatus,
+    // Cleared whenever the NFT changes owner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer: Option<OfferLock>,
+    // Set while the NFT is jointly owned; see `shares`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shares: Option<Vec<Share>>,
+    // Shareholder -> address it has agreed to transfer the NFT to.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub share_consents: BTreeMap<String, String>,
+    // Image CIDs replaced by a repin, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub image_cid_history: Vec<CidChange>,
+    // Unknown for NFTs minted before it was tracked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minted_at: Option<u64>,
+    // Bumped on every recorded change; backs the ETag on /view.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub version: u64,
+}
+
+impl NftExtras {
+    // "#3 of 100", for NFTs minted into a capped collection.
+    pub fn edition_label(&self) -> Option<String> {
+        Some(format!("#{} of {}", self.edition?, self.edition_of?))
+    }
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftStatus {
+    #[default]
+    Active,
+    // Kept as a tombstone; see `burn::burn_nft`.
+    Burned,
+}
+
+impl NftStatus {
+    fn is_active(&self) -> bool {
+        *self == NftStatus::Active
+    }
+}
+
+// Where an NFT stands with respect to the IBC bridge.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IbcStatus {
+    // Minted here and never bridged.
+    #[default]
+    Local,
+    // Bridged out; frozen here until it is imported back.
+    Exported,
+    // Arrived (or returned) through an IBC import.
+    Imported,
+}
+
+impl IbcStatus {
+    fn is_local(&self) -> bool {
+        *self == IbcStatus::Local
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CidChange {
+    pub cid: String,
+    pub replaced_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipRecord {
+    pub owner: String,
+    // Unknown for owners recorded before history was tracked.
+    pub acquired_at: Option<u64>,
+}
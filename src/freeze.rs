@@ -0,0 +1,145 @@
+use crate::{
+    app::AppState,
+    burn::ensure_not_burned,
+    error::NftError,
+    events::EventKind,
+    nonce::{accept_nonce, check_nonce},
+    signature::{action_message, verify_signature},
+};
+
+// The owner signs `action_message("freeze")`.
+pub fn freeze_nft(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    set_frozen(state, id, caller, true, nonce, signature)?;
+    state.record(EventKind::Freeze, id, Some(caller), None);
+    Ok(())
+}
+
+// The owner signs `action_message("unfreeze")`.
+pub fn unfreeze_nft(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    set_frozen(state, id, caller, false, nonce, signature)?;
+    state.record(EventKind::Unfreeze, id, Some(caller), None);
+    Ok(())
+}
+
+// Admin override: lifts a freeze without the owner's say-so.
+pub fn force_unfreeze_nft(state: &mut AppState, id: &str) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    if state.ledger.get_nft(id).is_none() {
+        return Err(NftError::NotFound(format!("NFT {} not found", id)));
+    }
+    let extras = state.extras_mut(id);
+    if !extras.frozen {
+        return Err(NftError::Invalid(format!(
+            "NFT {} has no freeze to lift",
+            id
+        )));
+    }
+    extras.frozen = false;
+    state.record(EventKind::ForceUnfreeze, id, None, None);
+    Ok(())
+}
+
+// Fails if the NFT is frozen; checked by every path that moves or destroys it.
+pub fn ensure_not_frozen(state: &AppState, id: &str) -> Result<(), NftError> {
+    if state.extras.get(id).is_some_and(|extras| extras.frozen) {
+        return Err(NftError::Locked(format!("NFT {} is frozen", id)));
+    }
+    Ok(())
+}
+
+fn set_frozen(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    frozen: bool,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if nft.owner != caller {
+        return Err(NftError::Forbidden(format!(
+            "only the owner of NFT {} can freeze or unfreeze it",
+            id
+        )));
+    }
+    let action = if frozen { "freeze" } else { "unfreeze" };
+    verify_signature(
+        caller,
+        &action_message(&state.tenant, action, id, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    accept_nonce(state, caller, nonce);
+    state.extras_mut(id).frozen = frozen;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testutil::{self, Key},
+        transfer::transfer_nft,
+    };
+
+    fn freeze(state: &mut AppState, owner: &Key, id: &str, nonce: u64) {
+        let signature = owner.sign(&action_message("", "freeze", id, nonce));
+        freeze_nft(state, id, &owner.address(), nonce, &signature).unwrap();
+    }
+
+    #[test]
+    fn transfer_is_rejected_while_frozen_and_allowed_after_unfreeze() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        freeze(&mut state, &alice, &id, 1);
+        let err = transfer_nft(&mut state, &id, "bob");
+        assert!(matches!(err, Err(NftError::Locked(_))));
+        let signature = alice.sign(&action_message("", "unfreeze", &id, 2));
+        unfreeze_nft(&mut state, &id, &alice.address(), 2, &signature).unwrap();
+        transfer_nft(&mut state, &id, "bob").unwrap();
+    }
+
+    #[test]
+    fn freeze_needs_the_owner_signature() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let forged = Key::new(2).sign(&action_message("", "freeze", &id, 1));
+        let err = freeze_nft(&mut state, &id, &alice.address(), 1, &forged);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        // A freeze signature can't be replayed as an unfreeze.
+        freeze(&mut state, &alice, &id, 1);
+        let signature = alice.sign(&action_message("", "freeze", &id, 2));
+        let err = unfreeze_nft(&mut state, &id, &alice.address(), 2, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(ensure_not_frozen(&state, &id).is_err());
+    }
+
+    #[test]
+    fn admin_can_lift_a_freeze() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        assert!(force_unfreeze_nft(&mut state, &id).is_err());
+        freeze(&mut state, &alice, &id, 1);
+        force_unfreeze_nft(&mut state, &id).unwrap();
+        ensure_not_frozen(&state, &id).unwrap();
+    }
+}
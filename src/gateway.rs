@@ -0,0 +1,86 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+
+// Where GET /nft/:id/image sends clients for an image CID: a redirect to
+// `<base>/<cid>`, or with `proxy` the bytes fetched from there.
+pub struct Gateway {
+    base: String,
+    proxy: Option<reqwest::Client>,
+}
+
+impl Gateway {
+    // `base` must be an absolute http(s) URL; checked once at startup.
+    pub fn new(base: &str, proxy: bool) -> Result<Self, String> {
+        let base = base.trim().trim_end_matches('/');
+        let uri: Uri = base
+            .parse()
+            .map_err(|e| format!("invalid IPFS gateway {:?}: {}", base, e))?;
+        let scheme_ok = matches!(uri.scheme_str(), Some("http" | "https"));
+        if !scheme_ok || uri.authority().is_none() || uri.query().is_some() {
+            return Err(format!(
+                "invalid IPFS gateway {:?}: expected an http(s) URL like https://ipfs.io/ipfs",
+                base
+            ));
+        }
+        Ok(Gateway {
+            base: base.to_string(),
+            proxy: proxy.then(reqwest::Client::new),
+        })
+    }
+
+    pub fn url(&self, cid: &str) -> String {
+        format!("{}/{}", self.base, cid)
+    }
+
+    // 302 to the gateway, or the gateway's response relayed as-is when
+    // proxying. Errors are the gateway being unreachable.
+    pub async fn respond(&self, cid: &str) -> Result<Response, String> {
+        let url = self.url(cid);
+        let Some(client) = &self.proxy else {
+            let location = HeaderValue::from_str(&url)
+                .map_err(|_| format!("image CID {:?} doesn't fit in a URL", cid))?;
+            return Ok((StatusCode::FOUND, [(header::LOCATION, location)]).into_response());
+        };
+        let upstream = client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("IPFS gateway request for {} failed: {}", cid, e))?;
+        let status =
+            StatusCode::from_u16(upstream.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+        let content_type = upstream
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| HeaderValue::from_bytes(value.as_bytes()).ok());
+        let mut response = Response::new(Body::from_stream(upstream.bytes_stream()));
+        *response.status_mut() = status;
+        if let Some(content_type) = content_type {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, content_type);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_absolute_http_urls_are_accepted() {
+        let gateway = Gateway::new("https://ipfs.io/ipfs/", false).unwrap();
+        assert_eq!(gateway.url("bafy"), "https://ipfs.io/ipfs/bafy");
+        for base in [
+            "ipfs.io/ipfs",
+            "ftp://ipfs.io",
+            "https://ipfs.io/ipfs?x=1",
+            "",
+        ] {
+            assert!(Gateway::new(base, false).is_err(), "{}", base);
+        }
+    }
+}
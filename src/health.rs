@@ -0,0 +1,33 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+// Liveness/readiness bookkeeping that never touches the state lock, so
+// probes answer even while a long write holds it.
+pub struct Health {
+    started: Instant,
+    ready: AtomicBool,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Health {
+            started: Instant::now(),
+            ready: AtomicBool::new(false),
+        }
+    }
+
+    pub fn uptime_secs(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+
+    // Set once the initial state load from disk has finished.
+    pub fn set_ready(&self) {
+        self.ready.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,592 @@
+use crate::{
+    app::AppState,
+    balance::{charge_mint_fee, check_mint_fees},
+    burn::{ensure_not_burned, is_burned},
+    error::NftError,
+    events::EventKind,
+    expiry::ensure_not_expired,
+    extras::{IbcStatus, NftExtras, OwnershipRecord},
+    freeze::ensure_not_frozen,
+    ids::normalize_id,
+    mint::{check_template, MintItem, MintOptions},
+    nonce::{accept_nonce, check_nonce},
+    offer::ensure_offer_allows,
+    shares::ensure_not_shared,
+    signature::{action_message, batch_action_message, verify_signature},
+    staking::is_staked,
+};
+use penumbra_nft::{
+    ibc::{export_nft_for_ibc, import_nft_from_ibc},
+    types::NFT,
+};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fmt, panic};
+
+// Payloads are `<version: 2 hex><crc32 of body: 8 hex><body>`, where body is
+// penumbra_nft's own serialization. Version 1 was the bare body.
+pub const PAYLOAD_VERSION: u8 = 2;
+const HEADER_LEN: usize = 10;
+
+// Most ids one POST /ibc/export/batch may ask for.
+pub const MAX_EXPORT_BATCH: usize = 100;
+
+#[derive(Debug)]
+pub enum ImportError {
+    Malformed(String),
+    UnsupportedVersion(String),
+    Checksum { expected: u32, actual: u32 },
+    Duplicate(String),
+}
+
+// Bridges `id` out: returns its payload and marks it exported, after which it
+// can't be transferred or burned here until the payload comes back through
+// `import_nft`. Only the owner may export, signing
+// `action_message("ibc-export")`.
+pub fn export_nft(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<String, NftError> {
+    ensure_exportable(state, id, caller)?;
+    verify_signature(
+        caller,
+        &action_message(&state.tenant, "ibc-export", id, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    accept_nonce(state, caller, nonce);
+    Ok(mark_exported(state, id, caller))
+}
+
+// Everything that must hold for `caller` to bridge `id` out: the rules a
+// transfer passes, since the NFT leaves its owner's hands here just the same.
+fn ensure_exportable(state: &AppState, id: &str, caller: &str) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if nft.owner != caller {
+        return Err(NftError::Forbidden(format!(
+            "only the owner of NFT {} can export it",
+            id
+        )));
+    }
+    if is_staked(state, id) {
+        return Err(NftError::Locked(format!(
+            "NFT {} is staked; unstake it before exporting",
+            id
+        )));
+    }
+    ensure_not_exported(state, id)?;
+    ensure_not_shared(state, id)?;
+    ensure_not_frozen(state, id)?;
+    ensure_not_expired(state, id)?;
+    // The owner is never the buyer, so any live offer lock refuses.
+    ensure_offer_allows(state, id, caller)
+}
+
+fn mark_exported(state: &mut AppState, id: &str, caller: &str) -> String {
+    let payload = export_payload(state.ledger.get_nft(id).expect("checked exportable"));
+    let extras = state.extras_mut(id);
+    extras.ibc_status = IbcStatus::Exported;
+    extras.export_digest = Some(payload_digest(&payload));
+    state.record(EventKind::IbcExport, id, Some(caller), None);
+    payload
+}
+
+// The payload `export_nft` handed out for `id`, while it is still exported.
+pub fn exported_payload(state: &AppState, id: &str) -> Option<String> {
+    let extras = state.extras.get(id)?;
+    if extras.ibc_status != IbcStatus::Exported {
+        return None;
+    }
+    let payload = export_payload(state.ledger.get_nft(id)?);
+    (extras.export_digest.as_deref() == Some(payload_digest(&payload).as_str())).then_some(payload)
+}
+
+// Checked by every path that moves or destroys an NFT, so a bridged NFT
+// can't also be spent on this side.
+pub fn ensure_not_exported(state: &AppState, id: &str) -> Result<(), NftError> {
+    let exported = state
+        .extras
+        .get(id)
+        .is_some_and(|extras| extras.ibc_status == IbcStatus::Exported);
+    if exported {
+        return Err(NftError::Locked(format!(
+            "NFT {} has been exported over IBC; import it back first",
+            id
+        )));
+    }
+    Ok(())
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Malformed(reason) => write!(f, "malformed IBC payload: {}", reason),
+            ImportError::UnsupportedVersion(found) => write!(
+                f,
+                "unsupported IBC payload version {} (expected {:02x}); re-export it from an up-to-date server",
+                found, PAYLOAD_VERSION
+            ),
+            ImportError::Checksum { expected, actual } => write!(
+                f,
+                "IBC payload checksum mismatch (expected {:08x}, got {:08x}); it was truncated or corrupted",
+                expected, actual
+            ),
+            ImportError::Duplicate(id) => write!(f, "NFT {} already exists", id),
+        }
+    }
+}
+
+fn payload_digest(payload: &str) -> String {
+    hex::encode(Sha256::digest(payload.as_bytes()))
+}
+
+pub fn export_payload(nft: &NFT) -> String {
+    let body = export_nft_for_ibc(nft);
+    format!(
+        "{:02x}{:08x}{}",
+        PAYLOAD_VERSION,
+        crc32fast::hash(body.as_bytes()),
+        body
+    )
+}
+
+// `export_nft` for each id, authorized by one signature by `caller` over
+// `batch_action_message("ibc-export")`. Missing and burned ids map to None;
+// any other id that can't be exported fails the batch before any is marked.
+pub fn export_batch(
+    state: &mut AppState,
+    ids: &[String],
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<BTreeMap<String, Option<String>>, NftError> {
+    if ids.len() > MAX_EXPORT_BATCH {
+        return Err(NftError::Invalid(format!(
+            "batch export takes at most {} ids, got {}",
+            MAX_EXPORT_BATCH,
+            ids.len()
+        )));
+    }
+    let message = batch_action_message(&state.tenant, "ibc-export", ids, nonce);
+    verify_signature(caller, &message, signature)?;
+    check_nonce(state, caller, nonce)?;
+    for id in ids.iter().filter(|id| is_live(state, id)) {
+        ensure_exportable(state, id, caller)?;
+    }
+    accept_nonce(state, caller, nonce);
+    let mut payloads = BTreeMap::new();
+    for id in ids {
+        if payloads.contains_key(id) {
+            continue;
+        }
+        let payload = is_live(state, id).then(|| mark_exported(state, id, caller));
+        payloads.insert(id.clone(), payload);
+    }
+    Ok(payloads)
+}
+
+fn is_live(state: &AppState, id: &str) -> bool {
+    state.ledger.get_nft(id).is_some() && !is_burned(state, id)
+}
+
+// Every payload in `ids` order as one blob, each framed as
+// `<payload length: 8 hex><payload>`. All or nothing: a missing id fails the
+// whole export, so a bridge never relays part of a set.
+pub fn export_framed(
+    state: &mut AppState,
+    ids: &[String],
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<String, NftError> {
+    if let Some(id) = ids.iter().find(|id| !is_live(state, id)) {
+        return Err(NftError::NotFound(format!("NFT {} not found", id)));
+    }
+    let payloads = export_batch(state, ids, caller, nonce, signature)?;
+    let mut blob = String::new();
+    for id in ids {
+        let payload = payloads[id]
+            .as_ref()
+            .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+        blob.push_str(&format!("{:08x}{}", payload.len(), payload));
+    }
+    Ok(blob)
+}
+
+// Verifies the envelope and decodes the body. penumbra_nft panics on bad
+// input, so the panic is contained here and reported as `Malformed`.
+pub fn decode_payload(serialized: &str) -> Result<NFT, ImportError> {
+    let header = serialized
+        .get(..HEADER_LEN)
+        .ok_or_else(|| ImportError::Malformed("payload is too short".into()))?;
+    let (version, checksum) = header.split_at(2);
+    if u8::from_str_radix(version, 16).ok() != Some(PAYLOAD_VERSION) {
+        return Err(ImportError::UnsupportedVersion(
+            if version.starts_with('{') {
+                "1 (unversioned)".into()
+            } else {
+                format!("{:?}", version)
+            },
+        ));
+    }
+    let expected = u32::from_str_radix(checksum, 16)
+        .map_err(|_| ImportError::Malformed("checksum is not hex".into()))?;
+    let body = &serialized[HEADER_LEN..];
+    let actual = crc32fast::hash(body.as_bytes());
+    if actual != expected {
+        return Err(ImportError::Checksum { expected, actual });
+    }
+
+    let mut nft = panic::catch_unwind(|| import_nft_from_ibc(body))
+        .map_err(|_| ImportError::Malformed("payload could not be decoded".into()))?;
+    nft.id = normalize_id(&nft.id);
+    if nft.id.trim().is_empty() {
+        return Err(ImportError::Malformed("NFT id is empty".into()));
+    }
+    if nft.owner.trim().is_empty() {
+        return Err(ImportError::Malformed("NFT owner is empty".into()));
+    }
+    Ok(nft)
+}
+
+// Inserts the NFT carried by an IBC payload and returns its id. An existing
+// NFT with the same id is only replaced when `overwrite` is set (which
+// callers must reserve for the admin), or when the payload is exactly the one
+// its export handed out.
+pub fn import_nft(
+    state: &mut AppState,
+    serialized: &str,
+    overwrite: bool,
+) -> Result<String, NftError> {
+    let nft = decode_payload(serialized)?;
+    let id = nft.id.clone();
+    let returning = state.extras.get(&id).is_some_and(|extras| {
+        extras.ibc_status == IbcStatus::Exported
+            && extras.export_digest.as_deref() == Some(payload_digest(serialized).as_str())
+    });
+    let existing = state.ledger.get_nft(&id).is_some();
+    // A burned id stays taken, overwrite or not.
+    let taken = is_burned(state, &id) || (!overwrite && existing);
+    if taken && !returning {
+        return Err(ImportError::Duplicate(id).into());
+    }
+    let owner = nft.owner.clone();
+    // Anything but a returning NFT is checked as a mint of its metadata, and
+    // one that is new here pays the mint fee like any other.
+    if !returning {
+        check_template(state, &imported_item(&nft))?;
+        if !existing {
+            check_mint_fees(state, [owner.as_str()])?;
+        }
+    }
+    if let Some(replaced) = state.ledger.nfts.insert(id.clone(), nft) {
+        state.owner_index.remove(&replaced.owner, &id);
+    }
+    state.owner_index.insert(&owner, &id);
+    if !returning {
+        if existing {
+            // The replaced NFT's approval, offer, freeze, stake and shares
+            // don't carry over; only its history and version do.
+            state.approvals.remove(&id);
+            state.staking.remove(&id);
+            let extras = state.extras_mut(&id);
+            *extras = NftExtras {
+                owner_history: std::mem::take(&mut extras.owner_history),
+                version: extras.version,
+                ..NftExtras::default()
+            };
+        } else {
+            charge_mint_fee(state, &owner);
+        }
+    }
+    let now = state.now();
+    let extras = state.extras_mut(&id);
+    extras.ibc_status = IbcStatus::Imported;
+    extras.export_digest = None;
+    extras.owner_history.push(OwnershipRecord {
+        owner: owner.clone(),
+        acquired_at: Some(now),
+    });
+    state.record(EventKind::IbcImport, &id, None, Some(&owner));
+    Ok(id)
+}
+
+// The mint an imported NFT stands in for, to run `check_template` on. It has
+// no collection, so there is no supply to check.
+fn imported_item(nft: &NFT) -> MintItem {
+    MintItem {
+        metadata: nft.metadata.clone(),
+        extras: NftExtras::default(),
+        max_supply: None,
+        transfer_cooldown_secs: None,
+        options: MintOptions::default(),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        offer::OfferLock,
+        signature::transfer_message,
+        staking::{is_staked, stake_nft},
+        testutil::{self, Key},
+        transfer::transfer_signed,
+    };
+
+    fn exported(state: &mut AppState, owner: &Key) -> (String, String) {
+        let id = testutil::mint(state, &owner.address());
+        let signature = owner.sign(&action_message("", "ibc-export", &id, 1));
+        let payload = export_nft(state, &id, &owner.address(), 1, &signature).unwrap();
+        (id, payload)
+    }
+
+    fn transfer(state: &mut AppState, owner: &Key, id: &str, nonce: u64) -> Result<(), NftError> {
+        let signature = owner.sign(&transfer_message("", id, "bob", nonce));
+        transfer_signed(state, id, &owner.address(), "bob", nonce, &signature)
+    }
+
+    #[test]
+    fn transfer_after_export_is_rejected() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let (id, _) = exported(&mut state, &alice);
+        let err = transfer(&mut state, &alice, &id, 2);
+        assert!(matches!(err, Err(NftError::Locked(_))));
+    }
+
+    #[test]
+    fn matching_import_makes_it_transferable_again() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let (id, payload) = exported(&mut state, &alice);
+        assert_eq!(import_nft(&mut state, &payload, false).unwrap(), id);
+        assert_eq!(state.extras[&id].ibc_status, IbcStatus::Imported);
+        transfer(&mut state, &alice, &id, 2).unwrap();
+    }
+
+    #[test]
+    fn other_payload_for_an_exported_id_is_a_duplicate() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let (id, payload) = exported(&mut state, &alice);
+        let mut forged = decode_payload(&payload).unwrap();
+        forged.owner = "mallory".to_string();
+        let err = import_nft(&mut state, &export_payload(&forged), false);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        assert_eq!(state.extras[&id].ibc_status, IbcStatus::Exported);
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, alice.address());
+    }
+
+    fn foreign_payload(state: &AppState, id: &str, owner: &str) -> String {
+        let mut nft = state.ledger.get_nft(id).unwrap().clone();
+        nft.owner = owner.to_string();
+        export_payload(&nft)
+    }
+
+    #[test]
+    fn payload_round_trips() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let nft = state.ledger.get_nft(&id).unwrap();
+        assert_eq!(&decode_payload(&export_payload(nft)).unwrap(), nft);
+    }
+
+    #[test]
+    fn flipped_byte_fails_the_checksum() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let mut payload = export_payload(state.ledger.get_nft(&id).unwrap()).into_bytes();
+        let last = payload.len() - 1;
+        payload[last] ^= 0x01;
+        let payload = String::from_utf8(payload).unwrap();
+        assert!(matches!(
+            decode_payload(&payload),
+            Err(ImportError::Checksum { .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_payload_is_rejected() {
+        let (mut state, _) = testutil::state();
+        for payload in ["", "02", "02zzzzzzzz{}", "0200000000not json"] {
+            let err = import_nft(&mut state, payload, false);
+            assert!(matches!(err, Err(NftError::Invalid(_))), "{:?}", payload);
+        }
+        assert!(state.ledger.nfts.is_empty());
+    }
+
+    #[test]
+    fn clean_import_inserts_the_nft() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let payload = foreign_payload(&state, &id, "carol");
+        let (mut other, _) = testutil::state();
+        assert_eq!(import_nft(&mut other, &payload, false).unwrap(), id);
+        assert_eq!(other.ledger.get_nft(&id).unwrap().owner, "carol");
+        assert_eq!(other.extras[&id].ibc_status, IbcStatus::Imported);
+    }
+
+    #[test]
+    fn import_pays_the_mint_fee() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let payload = foreign_payload(&state, &id, "carol");
+        let (mut other, _) = testutil::state();
+        other.mint_fee = 5;
+        let err = import_nft(&mut other, &payload, false);
+        assert!(matches!(err, Err(NftError::PaymentRequired(_))));
+        assert!(other.ledger.get_nft(&id).is_none());
+        other.balances.insert("carol".to_string(), 7);
+        import_nft(&mut other, &payload, false).unwrap();
+        assert_eq!(other.balances["carol"], 2);
+    }
+
+    #[test]
+    fn duplicate_id_is_rejected_without_overwrite() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let payload = foreign_payload(&state, &id, "carol");
+        let err = import_nft(&mut state, &payload, false);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, "alice");
+    }
+
+    #[test]
+    fn overwrite_drops_the_replaced_nfts_locks() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        stake_nft(&mut state, &id).unwrap();
+        state.approvals.insert(id.clone(), "spender".to_string());
+        state.extras_mut(&id).frozen = true;
+        state.extras_mut(&id).offer = Some(OfferLock {
+            buyer: "buyer".to_string(),
+            expires_at: testutil::START + 60,
+        });
+        let payload = foreign_payload(&state, &id, "carol");
+        import_nft(&mut state, &payload, true).unwrap();
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, "carol");
+        assert!(!is_staked(&state, &id));
+        assert!(!state.approvals.contains_key(&id));
+        assert!(!state.extras[&id].frozen);
+        assert!(state.extras[&id].offer.is_none());
+        assert_eq!(state.extras[&id].owner_history.len(), 2);
+    }
+
+    #[test]
+    fn export_needs_the_owners_signature() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = Key::new(2).sign(&action_message("", "ibc-export", &id, 1));
+        let err = export_nft(&mut state, &id, &alice.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(state.extras[&id].ibc_status, IbcStatus::Local);
+    }
+
+    #[test]
+    fn locked_nfts_cant_be_exported() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let staked = testutil::mint(&mut state, &alice.address());
+        stake_nft(&mut state, &staked).unwrap();
+        let frozen = testutil::mint(&mut state, &alice.address());
+        state.extras_mut(&frozen).frozen = true;
+        let expired = testutil::mint(&mut state, &alice.address());
+        state.extras_mut(&expired).expires_at = Some(testutil::START);
+        let offered = testutil::mint(&mut state, &alice.address());
+        state.extras_mut(&offered).offer = Some(OfferLock {
+            buyer: "bob".to_string(),
+            expires_at: testutil::START + 60,
+        });
+
+        for (nonce, id) in (1..).zip([&staked, &frozen, &expired, &offered]) {
+            let signature = alice.sign(&action_message("", "ibc-export", id, nonce));
+            let err = export_nft(&mut state, id, &alice.address(), nonce, &signature);
+            assert!(
+                matches!(err, Err(NftError::Locked(_) | NftError::Conflict(_))),
+                "{}",
+                id
+            );
+            assert_eq!(state.extras[id].ibc_status, IbcStatus::Local);
+        }
+        let ids = vec![testutil::mint(&mut state, &alice.address()), staked];
+        let signature = sign_batch(&alice, &ids, 1);
+        let err = export_batch(&mut state, &ids, &alice.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::Locked(_))));
+        assert!(exported_payload(&state, &ids[0]).is_none());
+    }
+
+    fn sign_batch(owner: &Key, ids: &[String], nonce: u64) -> String {
+        owner.sign(&batch_action_message("", "ibc-export", ids, nonce))
+    }
+
+    #[test]
+    fn batch_export_maps_missing_ids_to_none_and_locks_the_rest() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let ids = vec![
+            testutil::mint(&mut state, &alice.address()),
+            "missing".to_string(),
+            testutil::mint(&mut state, &alice.address()),
+        ];
+        let signature = sign_batch(&alice, &ids, 1);
+        let err = export_framed(&mut state, &ids, &alice.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::NotFound(_))));
+        let payloads = export_batch(&mut state, &ids, &alice.address(), 1, &signature).unwrap();
+        assert_eq!(payloads.len(), 3);
+        assert!(payloads["missing"].is_none());
+        for id in [&ids[0], &ids[2]] {
+            let payload = payloads[id].as_ref().unwrap();
+            assert_eq!(decode_payload(payload).unwrap().id, *id);
+            assert_eq!(state.extras[id].ibc_status, IbcStatus::Exported);
+            assert_eq!(exported_payload(&state, id).as_ref(), Some(payload));
+        }
+        let err = transfer(&mut state, &alice, &ids[0], 2);
+        assert!(matches!(err, Err(NftError::Locked(_))));
+    }
+
+    #[test]
+    fn batch_export_is_refused_whole_for_one_unowned_id() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let ids = vec![
+            testutil::mint(&mut state, &alice.address()),
+            testutil::mint(&mut state, "bob"),
+        ];
+        let signature = sign_batch(&alice, &ids, 1);
+        let err = export_batch(&mut state, &ids, &alice.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(state.extras[&ids[0]].ibc_status, IbcStatus::Local);
+        let signature = sign_batch(&Key::new(2), &ids[..1], 1);
+        let err = export_batch(&mut state, &ids[..1], &alice.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(exported_payload(&state, &ids[0]).is_none());
+    }
+
+    #[test]
+    fn framed_blob_holds_each_payload_in_order() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let ids = vec![
+            testutil::mint(&mut state, &alice.address()),
+            testutil::mint(&mut state, &alice.address()),
+        ];
+        let signature = sign_batch(&alice, &ids, 1);
+        let framed = export_framed(&mut state, &ids, &alice.address(), 1, &signature).unwrap();
+        let mut blob = framed.as_str();
+        for id in &ids {
+            let len = usize::from_str_radix(&blob[..8], 16).unwrap();
+            let payload = &blob[8..8 + len];
+            assert_eq!(payload, export_payload(state.ledger.get_nft(id).unwrap()));
+            assert_eq!(state.extras[id].ibc_status, IbcStatus::Exported);
+            blob = &blob[8 + len..];
+        }
+        assert!(blob.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use crate::error::NftError;
+use std::collections::HashMap;
+
+pub const DEFAULT_TTL_SECS: u64 = 24 * 60 * 60;
+
+// Responses remembered by `Idempotency-Key`, so a retried request returns the
+// first result instead of repeating its effect. Kept in memory only: a retry
+// that arrives after a restart is treated as new. Each entry keeps a
+// fingerprint of the request it answered, so a key reused for a different
+// request is refused rather than answered with someone else's result.
+pub struct IdempotencyCache<T> {
+    pub ttl_secs: u64,
+    entries: HashMap<String, Entry<T>>,
+}
+
+struct Entry<T> {
+    expires_at: u64,
+    fingerprint: String,
+    value: T,
+}
+
+impl<T> IdempotencyCache<T> {
+    pub fn new(ttl_secs: u64) -> Self {
+        IdempotencyCache {
+            ttl_secs,
+            entries: HashMap::new(),
+        }
+    }
+
+    // The remembered result for `key`, or Conflict if it was first used
+    // for a request with a different fingerprint.
+    pub fn get(&self, key: &str, fingerprint: &str, now: u64) -> Result<Option<&T>, NftError> {
+        match self.entries.get(key).filter(|entry| now < entry.expires_at) {
+            Some(entry) if entry.fingerprint != fingerprint => Err(NftError::Conflict(format!(
+                "Idempotency-Key {} was already used for a different request",
+                key
+            ))),
+            entry => Ok(entry.map(|entry| &entry.value)),
+        }
+    }
+
+    // Expired entries are dropped here, which bounds the map to roughly one
+    // TTL's worth of keys.
+    pub fn insert(&mut self, key: String, fingerprint: String, value: T, now: u64) {
+        self.entries.retain(|_, entry| now < entry.expires_at);
+        let entry = Entry {
+            expires_at: now.saturating_add(self.ttl_secs),
+            fingerprint,
+            value,
+        };
+        self.entries.insert(key, entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_after_the_ttl() {
+        let mut cache = IdempotencyCache::new(10);
+        cache.insert("key".to_string(), "body".to_string(), 1, 100);
+        assert_eq!(cache.get("key", "body", 109).unwrap(), Some(&1));
+        assert_eq!(cache.get("key", "body", 110).unwrap(), None);
+        assert_eq!(cache.get("other", "body", 100).unwrap(), None);
+    }
+
+    #[test]
+    fn a_key_reused_for_another_request_is_a_conflict() {
+        let mut cache = IdempotencyCache::new(10);
+        cache.insert("key".to_string(), "body".to_string(), 1, 100);
+        assert!(matches!(
+            cache.get("key", "other body", 101),
+            Err(NftError::Conflict(_))
+        ));
+        // Once expired, the key is free for any request.
+        assert_eq!(cache.get("key", "other body", 110).unwrap(), None);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{app::AppState, cid::strip_scheme, config::IdScheme, error::NftError, mint::MintItem};
+use axum::{
+    async_trait,
+    extract::{rejection::PathRejection, FromRequestParts, Path},
+    http::request::Parts,
+};
+use penumbra_nft::types::NFTMetadata;
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+// The form ids are stored and looked up in: surrounding whitespace from
+// copy-paste is dropped, and uuid or hex ids are lowercased. Sequence ids
+// keep their collection name's case, since two collections may differ only
+// by it.
+pub fn normalize_id(id: &str) -> String {
+    let id = id.trim();
+    if id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        id.to_ascii_lowercase()
+    } else {
+        id.to_string()
+    }
+}
+
+// `deserialize_with` helpers, so request bodies carry normalized ids.
+pub fn normalized<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(normalize_id(&String::deserialize(deserializer)?))
+}
+
+pub fn normalized_opt<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(|id| normalize_id(&id)))
+}
+
+pub fn normalized_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    let ids = Vec::<String>::deserialize(deserializer)?;
+    Ok(ids.iter().map(|id| normalize_id(id)).collect())
+}
+
+// An NFT id taken from the path, normalized.
+pub struct NftId(pub String);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for NftId {
+    type Rejection = PathRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(id) = Path::<String>::from_request_parts(parts, state).await?;
+        Ok(NftId(normalize_id(&id)))
+    }
+}
+
+// Hex SHA-256 of everything that makes two mints identical, truncated to
+// 128 bits. Fields are newline-framed JSON so none can run into the next.
+pub fn content_id(owner: &str, metadata: &NFTMetadata, collection: Option<&str>) -> String {
+    let fields = serde_json::json!([
+        owner,
+        metadata.name,
+        metadata.description,
+        metadata.image_cid,
+        metadata.attributes,
+        metadata.shielded,
+        collection,
+    ]);
+    let digest = Sha256::digest(fields.to_string().as_bytes());
+    hex::encode(&digest[..16])
+}
+
+// The id `mint_nft` will give this mint under the configured scheme, or None
+// to keep the one penumbra_nft assigns. `metadata.image_cid` must already be
+// normalized.
+pub fn planned_id(
+    state: &AppState,
+    owner: &str,
+    metadata: &NFTMetadata,
+    collection: Option<&str>,
+) -> Option<String> {
+    match state.id_scheme {
+        IdScheme::Uuid => None,
+        IdScheme::ContentHash => Some(content_id(owner, metadata, collection)),
+        // NFTs outside a collection have no sequence and keep a random id.
+        IdScheme::Sequence => {
+            let collection = collection?;
+            let minted = state
+                .collections
+                .get(collection)
+                .map_or(0, |info| info.minted);
+            Some(normalize_id(&format!("{}:{:04}", collection, minted + 1)))
+        }
+    }
+}
+
+pub fn ensure_unused(state: &AppState, id: &str) -> Result<(), NftError> {
+    if state.ledger.get_nft(id).is_some() {
+        return Err(NftError::Conflict(format!("NFT {} already exists", id)));
+    }
+    Ok(())
+}
+
+// Under content hashing, fails if any of `mints` would duplicate an existing
+// NFT or another mint in the same request, so a batch fails before minting
+// anything rather than partway through.
+pub fn check_content_ids<'a>(
+    state: &AppState,
+    mints: impl IntoIterator<Item = (&'a str, &'a MintItem)>,
+) -> Result<(), NftError> {
+    if state.id_scheme != IdScheme::ContentHash {
+        return Ok(());
+    }
+    let mut seen = HashSet::new();
+    for (owner, item) in mints {
+        let mut metadata = item.metadata.clone();
+        metadata.image_cid = strip_scheme(metadata.image_cid.trim()).to_string();
+        let id = content_id(owner, &metadata, item.extras.collection.as_deref());
+        ensure_unused(state, &id)?;
+        if !seen.insert(id.clone()) {
+            return Err(NftError::Conflict(format!(
+                "NFT {} already exists in this request",
+                id
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Moves a freshly minted NFT from the id penumbra_nft gave it to `new_id`.
+pub fn rekey(state: &mut AppState, id: &str, new_id: &str) {
+    if let Some(mut nft) = state.ledger.nfts.remove(id) {
+        nft.id = new_id.to_string();
+        state.ledger.nfts.insert(new_id.to_string(), nft);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{batch::mint_nft_batch, mint::mint_nft, testutil};
+
+    #[test]
+    fn uuid_scheme_gives_identical_mints_distinct_ids() {
+        let (mut state, _) = testutil::state();
+        let first = testutil::mint(&mut state, "alice");
+        let second = testutil::mint(&mut state, "alice");
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn content_hash_refuses_a_twin() {
+        let (mut state, _) = testutil::state();
+        state.id_scheme = IdScheme::ContentHash;
+        let id = testutil::mint(&mut state, "alice");
+        assert_eq!(
+            id,
+            content_id("alice", &testutil::item("test").metadata, None)
+        );
+        let err = mint_nft(&mut state, "alice".to_string(), testutil::item("test"));
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        // Any difference makes it a different NFT.
+        assert_ne!(testutil::mint(&mut state, "bob"), id);
+        assert_eq!(state.ledger.nfts.len(), 2);
+
+        let items = vec![testutil::item("twin"), testutil::item("twin")];
+        let err = mint_nft_batch(&mut state, "alice", items);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        assert_eq!(state.ledger.nfts.len(), 2);
+    }
+
+    #[test]
+    fn sequence_numbers_each_collection() {
+        let (mut state, _) = testutil::state();
+        state.id_scheme = IdScheme::Sequence;
+        let mut mint_into = |collection: &str| {
+            let mut item = testutil::item("member");
+            item.extras.collection = Some(collection.to_string());
+            mint_nft(&mut state, "alice".to_string(), item).unwrap()
+        };
+        assert_eq!(mint_into("Apes"), "Apes:0001");
+        assert_eq!(mint_into("Apes"), "Apes:0002");
+        assert_eq!(mint_into("cats"), "cats:0001");
+        assert!(state.ledger.get_nft("Apes:0002").is_some());
+    }
+
+    #[test]
+    fn hex_ids_lowercase_and_sequence_ids_keep_their_case() {
+        assert_eq!(normalize_id("  ABCDEF-0123\n"), "abcdef-0123");
+        assert_eq!(normalize_id(" Apes:0001 "), "Apes:0001");
+    }
+}
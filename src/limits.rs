@@ -0,0 +1,147 @@
+use crate::{attributes::decode_attributes, error::NftError};
+use penumbra_nft::types::NFTMetadata;
+use std::collections::HashSet;
+
+pub const DEFAULT_MAX_NAME_BYTES: usize = 200;
+pub const DEFAULT_MAX_DESCRIPTION_BYTES: usize = 4000;
+pub const DEFAULT_MAX_ATTRIBUTES_BYTES: usize = 16 * 1024;
+pub const DEFAULT_MAX_ATTRIBUTES: usize = 64;
+
+// Caps on the free-form metadata strings, so one NFT can't take megabytes of
+// memory and state file. Measured in bytes; attributes in their encoded form.
+#[derive(Clone, Copy, Debug)]
+pub struct FieldLimits {
+    pub name: usize,
+    pub description: usize,
+    pub attributes: usize,
+    // Number of traits, each of which must have its own trait_type.
+    pub attribute_count: usize,
+}
+
+impl Default for FieldLimits {
+    fn default() -> Self {
+        FieldLimits {
+            name: DEFAULT_MAX_NAME_BYTES,
+            description: DEFAULT_MAX_DESCRIPTION_BYTES,
+            attributes: DEFAULT_MAX_ATTRIBUTES_BYTES,
+            attribute_count: DEFAULT_MAX_ATTRIBUTES,
+        }
+    }
+}
+
+impl FieldLimits {
+    pub fn check(&self, metadata: &NFTMetadata) -> Result<(), NftError> {
+        check_len("name", &metadata.name, self.name)?;
+        check_len("description", &metadata.description, self.description)?;
+        check_len("attributes", &metadata.attributes, self.attributes)?;
+        let attributes = decode_attributes(&metadata.attributes);
+        if attributes.len() > self.attribute_count {
+            return Err(NftError::Invalid(format!(
+                "{} attributes given, over the limit of {}",
+                attributes.len(),
+                self.attribute_count
+            )));
+        }
+        // Filters match on trait_type, so a repeat would make one ambiguous.
+        let mut seen = HashSet::new();
+        for attribute in &attributes {
+            if !seen.insert(attribute.trait_type.as_str()) {
+                return Err(NftError::Invalid(format!(
+                    "duplicate trait_type {:?}",
+                    attribute.trait_type
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_len(field: &str, value: &str, limit: usize) -> Result<(), NftError> {
+    if value.len() > limit {
+        return Err(NftError::Invalid(format!(
+            "{} is {} bytes, over the limit of {}",
+            field,
+            value.len(),
+            limit
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::AppState, mint::mint_nft, testutil};
+
+    fn mint_with(
+        state: &mut AppState,
+        edit: impl FnOnce(&mut NFTMetadata),
+    ) -> Result<String, NftError> {
+        let mut item = testutil::item("test");
+        edit(&mut item.metadata);
+        mint_nft(state, "alice".to_string(), item)
+    }
+
+    fn rejected_naming(result: Result<String, NftError>, field: &str) {
+        match result {
+            Err(NftError::Invalid(message)) => assert!(message.starts_with(field), "{}", message),
+            other => panic!(
+                "expected {} to be rejected, got {:?}",
+                field,
+                other.map(|_| ())
+            ),
+        }
+    }
+
+    #[test]
+    fn strings_at_the_limit_are_accepted() {
+        let (mut state, _) = testutil::state();
+        let attributes = r#"[{"trait_type":"eyes","value":"red"}]"#;
+        state.field_limits.attributes = attributes.len();
+        mint_with(&mut state, |metadata| {
+            metadata.name = "n".repeat(DEFAULT_MAX_NAME_BYTES);
+            metadata.description = "d".repeat(DEFAULT_MAX_DESCRIPTION_BYTES);
+            metadata.attributes = attributes.to_string();
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn each_string_over_the_limit_is_rejected_by_name() {
+        let (mut state, _) = testutil::state();
+        let attributes = r#"[{"trait_type":"eyes","value":"red"}]"#;
+        state.field_limits.attributes = attributes.len() - 1;
+        let result = mint_with(&mut state, |m| {
+            m.name = "n".repeat(DEFAULT_MAX_NAME_BYTES + 1)
+        });
+        rejected_naming(result, "name");
+        let result = mint_with(&mut state, |m| {
+            m.description = "d".repeat(DEFAULT_MAX_DESCRIPTION_BYTES + 1)
+        });
+        rejected_naming(result, "description");
+        let result = mint_with(&mut state, |m| m.attributes = attributes.to_string());
+        rejected_naming(result, "attributes");
+        assert!(state.ledger.nfts.is_empty());
+    }
+
+    #[test]
+    fn too_many_attributes_are_rejected() {
+        let (mut state, _) = testutil::state();
+        state.field_limits.attribute_count = 2;
+        let two = r#"[{"trait_type":"a","value":1},{"trait_type":"b","value":2}]"#;
+        mint_with(&mut state, |m| m.attributes = two.to_string()).unwrap();
+        let three = r#"[{"trait_type":"a","value":1},{"trait_type":"b","value":2},{"trait_type":"c","value":3}]"#;
+        let result = mint_with(&mut state, |m| m.attributes = three.to_string());
+        rejected_naming(result, "3 attributes");
+        assert_eq!(state.ledger.nfts.len(), 1);
+    }
+
+    #[test]
+    fn duplicate_trait_types_are_rejected() {
+        let (mut state, _) = testutil::state();
+        let twice = r#"[{"trait_type":"eyes","value":"red"},{"trait_type":"eyes","value":"blue"}]"#;
+        let result = mint_with(&mut state, |m| m.attributes = twice.to_string());
+        rejected_naming(result, "duplicate trait_type \"eyes\"");
+        assert!(state.ledger.nfts.is_empty());
+    }
+}
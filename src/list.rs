@@ -0,0 +1,236 @@
+use crate::{
+    app::AppState,
+    burn::{is_burned, live_nfts},
+    reveal::is_revealed,
+    staking::is_staked,
+    SharedState,
+};
+use axum::body::{Body, Bytes};
+use futures_util::stream;
+use penumbra_nft::types::NFT;
+use std::convert::Infallible;
+
+pub const DEFAULT_LIMIT: usize = 50;
+pub const MAX_LIMIT: usize = 200;
+// Rows read per turn of the state lock while streaming.
+const STREAM_CHUNK: usize = 256;
+
+// Lightweight listing entry; fetch /view/:id for the full metadata. Like
+// `NftView::Redacted`, a shielded NFT that isn't revealed keeps only its id.
+#[derive(serde::Serialize)]
+pub struct NFTSummary {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub shielded: bool,
+    pub staked: bool,
+}
+
+impl NFTSummary {
+    // Listings take no viewing key, so only `RevealMode::PublicOnly` shows a
+    // shielded NFT's name and owner.
+    pub fn new(state: &AppState, nft: &NFT) -> Self {
+        Self::build(state, nft, is_revealed(state, nft, None))
+    }
+
+    // For an NFT the caller has already revealed, as /search does.
+    pub fn revealed(state: &AppState, nft: &NFT) -> Self {
+        Self::build(state, nft, true)
+    }
+
+    fn build(state: &AppState, nft: &NFT, revealed: bool) -> Self {
+        NFTSummary {
+            id: nft.id.clone(),
+            name: revealed.then(|| nft.metadata.name.clone()),
+            owner: revealed.then(|| nft.owner.clone()),
+            shielded: nft.metadata.shielded,
+            // The stake entry, not the ledger flag, says whether it's staked.
+            staked: is_staked(state, &nft.id),
+        }
+    }
+}
+
+pub struct Page {
+    // Live NFTs overall, not just on this page.
+    pub total: usize,
+    pub items: Vec<NFTSummary>,
+    // Pass as `after` to get the next page; `None` once exhausted.
+    pub next_cursor: Option<String>,
+}
+
+// Every live NFT as NDJSON, one summary per line, ordered by id. The ids are
+// sorted once up front; after that the read lock is taken per chunk and
+// released before the chunk is sent, each chunk resuming at its position in
+// that snapshot. NFTs minted mid-stream don't appear; ones burned mid-stream
+// are skipped if not yet sent.
+pub fn stream_summaries(state: SharedState) -> Body {
+    let chunks = stream::unfold(None, move |cursor: Option<(Vec<String>, usize)>| {
+        let state = state.clone();
+        async move {
+            let (ids, rows) = {
+                let state = state.read().await;
+                let (ids, start) = cursor.unwrap_or_else(|| (sorted_live_ids(&state), 0));
+                if start >= ids.len() {
+                    return None;
+                }
+                let end = ids.len().min(start + STREAM_CHUNK);
+                let rows: Vec<NFTSummary> = ids[start..end]
+                    .iter()
+                    .filter(|id| !is_burned(&state, id))
+                    .filter_map(|id| state.ledger.get_nft(id))
+                    .map(|nft| NFTSummary::new(&state, nft))
+                    .collect();
+                ((ids, end), rows)
+            };
+            let mut bytes = Vec::new();
+            for row in &rows {
+                serde_json::to_writer(&mut bytes, row).expect("summaries serialize");
+                bytes.push(b'\n');
+            }
+            Some((Ok::<_, Infallible>(Bytes::from(bytes)), Some(ids)))
+        }
+    });
+    Body::from_stream(chunks)
+}
+
+fn sorted_live_ids(state: &AppState) -> Vec<String> {
+    let mut ids: Vec<String> = live_nfts(state).map(|nft| nft.id.clone()).collect();
+    ids.sort();
+    ids
+}
+
+// One page of summaries ordered by id, leaving out burned NFTs. `after` is
+// the last id already seen; unlike an offset it isn't thrown off by mints or
+// burns between pages.
+pub fn list_nfts(state: &AppState, after: Option<&str>, offset: usize, limit: usize) -> Page {
+    let limit = limit.min(MAX_LIMIT);
+    let live: Vec<&NFT> = live_nfts(state).collect();
+    let total = live.len();
+    let mut nfts: Vec<&NFT> = live
+        .into_iter()
+        .filter(|nft| after.is_none_or(|after| nft.id.as_str() > after))
+        .collect();
+    nfts.sort_by(|a, b| a.id.cmp(&b.id));
+    let remaining = nfts.len().saturating_sub(offset);
+    let items: Vec<NFTSummary> = nfts
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|nft| NFTSummary::new(state, nft))
+        .collect();
+    let next_cursor = (remaining > items.len())
+        .then(|| items.last().map(|item| item.id.clone()))
+        .flatten();
+    Page {
+        total,
+        items,
+        next_cursor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::RevealMode, mint::mint_nft, staking::stake_nft, testutil};
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    #[test]
+    fn offset_and_limit_select_a_window() {
+        let mut state = AppState::new();
+        let page = list_nfts(&state, None, 0, 10);
+        assert_eq!(page.total, 0);
+        assert!(page.items.is_empty());
+
+        let mut ids: Vec<String> = (0..5)
+            .map(|_| testutil::mint(&mut state, "alice"))
+            .collect();
+        ids.sort();
+        let page = list_nfts(&state, None, 1, 2);
+        assert_eq!(page.total, 5);
+        let got: Vec<&str> = page.items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(got, [ids[1].as_str(), ids[2].as_str()]);
+        assert_eq!(list_nfts(&state, None, 4, 10).items.len(), 1);
+
+        let page = list_nfts(&state, None, 9, 10);
+        assert_eq!(page.total, 5);
+        assert!(page.items.is_empty());
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn cursor_pages_survive_mints_in_between() {
+        let (mut state, _) = testutil::state();
+        let before: Vec<String> = (0..5)
+            .map(|_| testutil::mint(&mut state, "alice"))
+            .collect();
+        let first = list_nfts(&state, None, 0, 2);
+        let mut cursor = first.next_cursor.clone();
+        let mut listed: Vec<String> = first.items.into_iter().map(|item| item.id).collect();
+        while let Some(after) = cursor {
+            // A mint between fetches lands before or after the cursor: it is
+            // listed at most once and nothing already listed shifts.
+            testutil::mint(&mut state, "alice");
+            let page = list_nfts(&state, Some(&after), 0, 2);
+            cursor = page.next_cursor.clone();
+            listed.extend(page.items.into_iter().map(|item| item.id));
+        }
+        // Strictly increasing, so nothing was listed twice.
+        assert!(listed.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(before.iter().all(|id| listed.contains(id)));
+    }
+
+    #[test]
+    fn shielded_summaries_keep_only_the_id() {
+        let (mut state, _) = testutil::state();
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        let id = mint_nft(&mut state, "alice".to_string(), item).unwrap();
+        let page = list_nfts(&state, None, 0, 10);
+        assert_eq!(page.items[0].id, id);
+        assert_eq!(page.items[0].name, None);
+        assert_eq!(page.items[0].owner, None);
+        assert!(page.items[0].shielded);
+
+        state.reveal_mode = RevealMode::PublicOnly;
+        let page = list_nfts(&state, None, 0, 10);
+        assert_eq!(page.items[0].owner.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn stream_sends_every_nft_once_in_order() {
+        let (mut state, _) = testutil::state();
+        for i in 0..600 {
+            mint_nft(
+                &mut state,
+                "alice".to_string(),
+                testutil::item(&i.to_string()),
+            )
+            .unwrap();
+        }
+        let body = stream_summaries(Arc::new(RwLock::new(state)));
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let ids: Vec<String> = std::str::from_utf8(&bytes)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let row: serde_json::Value = serde_json::from_str(line).unwrap();
+                assert_eq!(row["owner"], "alice");
+                row["id"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(ids.len(), 600);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn staked_comes_from_the_stake_entry() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        stake_nft(&mut state, &id).unwrap();
+        state.ledger.nfts.get_mut(&id).unwrap().staked = false;
+        assert!(list_nfts(&state, None, 0, 10).items[0].staked);
+    }
+}
@@ -1,174 +1,3332 @@
 use axum::{
-    routing::{get, post},
-    extract::Json,
-    Router,
+    middleware,
+    routing::{delete, get, patch, post, put},
+    extract::{DefaultBodyLimit, Json},
+    http::{
+        header::{CONTENT_TYPE, ETAG},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Extension, Router,
 };
-use penumbra_nft::{
-    mint::mint_nft,
-    transfer::transfer_nft,
-    view::reveal_nft,
-    staking::{stake_nft, unstake_nft},
-    airdrop::airdrop_nft,
-    ibc::{export_nft_for_ibc, import_nft_from_ibc},
-    types::{NFTMetadata, NFT},
-    state::NFTState,
+use clap::Parser;
+use futures_util::future::{BoxFuture, FutureExt};
+use metrics_exporter_prometheus::PrometheusHandle;
+use penumbra_nft::types::{NFTMetadata, NFT};
+use std::{
+    collections::{BTreeMap, HashSet},
+    future::IntoFuture,
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
 };
-use std::sync::{Arc, Mutex};
-use uuid::Uuid;
+use tokio::sync::{watch, RwLock};
+use tower_http::{
+    catch_panic::CatchPanicLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::{DefaultOnResponse, TraceLayer},
+};
+use utoipa::OpenApi;
+
+mod app;
+mod approval;
+mod attributes;
+mod auth;
+mod backup;
+mod balance;
+mod batch;
+mod burn;
+mod cid;
+mod cli;
+mod clock;
+mod collections;
+mod compression;
+mod config;
+mod cooldown;
+mod cors;
+mod encryption;
+mod error;
+mod etag;
+mod events;
+mod expiry;
+mod extras;
+mod freeze;
+mod gateway;
+mod health;
+mod ibc;
+mod idempotency;
+mod ids;
+mod limits;
+mod list;
+mod metadata;
+mod mint;
+mod negotiate;
+mod nonce;
+mod offer;
+mod openapi;
+mod owners;
+mod persist;
+mod proof;
+mod qr;
+mod ratelimit;
+mod reservation;
+mod reveal;
+mod reveallog;
+mod royalty;
+mod schema;
+mod search;
+mod shares;
+mod shutdown;
+mod signature;
+mod staking;
+mod stats;
+mod store;
+mod swap;
+mod sweeper;
+mod telemetry;
+mod tenant;
+#[cfg(test)]
+mod testutil;
+mod timeout;
+mod tls;
+mod tokenmeta;
+mod traitschema;
+mod transfer;
+mod tx;
+mod upload;
+mod viewcache;
+mod webhook;
+mod ws;
+
+use app::AppState;
+use approval::approve_nft;
+use attributes::{encode_attributes, Attributes};
+use auth::AdminKey;
+use backup::{export_all, export_one, import_backup, Backup};
+use balance::{balance, credit};
+use batch::{airdrop_mint, mint_nft_batch, simulate_airdrop, AirdropSimulation};
+use burn::{burn_nft_batch, burn_signed, ensure_not_burned};
+use collections::{collection_counts, nfts_in_collection, CollectionCount};
+use config::{Cli, Command, Config, StoreKind};
+use encryption::StateKey;
+use error::NftError;
+use events::{EventFilter, EventKind, NftEvent};
+use extras::{CidChange, NftExtras, OwnershipRecord};
+use freeze::{force_unfreeze_nft, freeze_nft, unfreeze_nft};
+use gateway::Gateway;
+use health::Health;
+use ibc::{export_batch, export_framed, export_nft, exported_payload, import_nft};
+use ids::NftId;
+use list::{list_nfts, stream_summaries, NFTSummary, DEFAULT_LIMIT, MAX_LIMIT};
+use metadata::{repin_image, update_metadata, MetadataPatch};
+use mint::{check_mint, mint_nft, MintItem, MintOptions};
+use negotiate::Format;
+use nonce::next_nonce;
+use offer::{accept_offer, cancel_offer, lock_for_offer, OfferLock};
+use openapi::ApiDoc;
+use owners::nfts_by_owner;
+use persist::Backend;
+use proof::{reveal_attribute_proof, AttributeProof, ProofSigner};
+use ratelimit::RateLimiter;
+use reservation::{finalize_mint, reserve_mint};
+use reveal::{
+    is_revealed, reveal_all, reveal_public_view, reveal_view, reveal_views, set_public_policy,
+    AuditEntry, NftView, PublicPolicy, PublicView,
+};
+use reveallog::{reveal_history, RevealRecord};
+use royalty::{royalty_quote, Royalty, RoyaltyQuote};
+use search::{parse_search_query, search_nfts};
+use shares::{consent_transfer, ownership, share_nft, ConsentStatus, Ownership, Share};
+use signature::Auth;
+use staking::{
+    claim_signed, force_unstake_nft, stake_nft_batch, stake_signed, unstake_nft_batch,
+    unstake_signed, UnstakeOutcome,
+};
+use stats::{state_stats, StateStats};
+use swap::{swap_nfts, SwapSide};
+use tenant::tenant_path;
+use tokenmeta::{token_metadata, TokenMetadata};
+use traitschema::{set_schema, AttributeSchema};
+use transfer::{
+    airdrop_nft, check_transfer_signed, owner_history, transfer_all, transfer_from,
+    transfer_nft_batch, transfer_preview, transfer_signed, BatchOutcome, DrainReport,
+    TransferPreview,
+};
+use tx::{apply_tx, Operation, OperationResult};
+use upload::BlobStore;
+use viewcache::{CachedView, ViewCache, ViewKey};
+use webhook::Webhook;
+
+const STATE_PATH: &str = "state.json";
+
+type SharedState = Arc<RwLock<AppState>>;
 
 #[tokio::main]
 async fn main() {
-    let state = Arc::new(Mutex::new(NFTState::new()));
+    let cli = Cli::parse();
+    let config = match cli.command {
+        None => cli.serve,
+        Some(Command::Serve(config)) => *config,
+        Some(command) => {
+            if let Err(err) = cli::run(command) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
+        )
+        .init();
+
+    let addr = match config.addr() {
+        Ok(addr) => addr,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+    let prometheus = match telemetry::install() {
+        Ok(handle) => handle,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let signer = match ProofSigner::from_seed(config.proof_key.as_deref()) {
+        Ok(signer) => Arc::new(signer),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+    if config.proof_key.is_none() {
+        tracing::warn!("No proof key configured; attribute proofs won't verify after a restart");
+    }
+    let gateway = match Gateway::new(&config.ipfs_gateway, config.image_proxy) {
+        Ok(gateway) => Arc::new(gateway),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+    let cors = match cors::cors_layer(&config) {
+        Ok(cors) => cors,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+
+    let tls = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => match tls::load(cert, key).await {
+            Ok(tls) => Some(tls),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            }
+        },
+        _ => None,
+    };
+
+    let webhook = match (&config.webhook_url, &config.webhook_secret) {
+        (Some(url), Some(secret)) => match Webhook::new(url, secret, config.webhook_max_retries) {
+            Ok(webhook) => Some(webhook),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            }
+        },
+        _ => None,
+    };
+
+    // Checked now rather than on the first save, which would lose the write.
+    let key = StateKey::from_env().map_err(|err| err.to_string());
+    if let Err(err) = key.and_then(|key| encryption::check_store(config.store, key.as_ref())) {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    }
+    let mut tenants = HashSet::new();
+    for tenant in &config.tenants {
+        if !tenants.insert(tenant.name.as_str()) {
+            eprintln!("tenant {} is configured more than once", tenant.name);
+            std::process::exit(2);
+        }
+    }
+
+    // Routes that create NFTs need the admin key, if one is configured.
+    let admin_key = AdminKey(config.admin_key.as_deref().map(Arc::from));
+    if admin_key.0.is_none() {
+        tracing::warn!("No admin key configured; mint and airdrop routes are open");
+    }
+    // The unprefixed default namespace, then one per --tenant.
+    let mut namespaces = vec![open_namespace(&config, None, admin_key.clone())];
+    for tenant in &config.tenants {
+        let admin_key = match &tenant.admin_key {
+            Some(key) => AdminKey(Some(Arc::from(key.as_str()))),
+            None => admin_key.clone(),
+        };
+        namespaces.push(open_namespace(&config, Some(&tenant.name), admin_key));
+    }
+
+    // Hold every write lock until its state is loaded, so handlers wait
+    // for it while /health and /ready stay responsive.
+    let health = Arc::new(Health::new());
+    let mut loading = Vec::with_capacity(namespaces.len());
+    for namespace in &namespaces {
+        let guard = namespace.state.clone().write_owned().await;
+        loading.push((
+            guard,
+            namespace.backend,
+            namespace.view_cache.clone(),
+            namespace.tenant.clone().unwrap_or_default(),
+        ));
+    }
+    let loaded = health.clone();
+    let settings = config.clone();
+    let default_state = namespaces[0].state.clone();
+    let swept: Vec<SharedState> = namespaces.iter().map(|n| n.state.clone()).collect();
+    tokio::spawn(async move {
+        for (mut loading, backend, view_cache, tenant) in loading {
+            let result = tokio::task::spawn_blocking(move || backend.load()).await;
+            match result {
+                Ok(Ok(initial)) => {
+                    *loading = initial;
+                    configure(&mut loading, &settings, backend, view_cache, tenant);
+                }
+                Ok(Err(err)) => {
+                    tracing::error!("Failed to load {}: {}", backend.describe(), err);
+                    std::process::exit(1);
+                }
+                Err(err) => {
+                    tracing::error!("State loader panicked: {}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        loaded.set_ready();
+        // Loading replaced the event feed, so subscribe only now. Tenants'
+        // events aren't delivered.
+        if let Some(webhook) = webhook {
+            tokio::spawn(webhook.run(default_state));
+        }
+        if let Some(interval) = settings.sweep_interval() {
+            for state in swept {
+                tokio::spawn(sweeper::run(state, interval));
+            }
+        }
+    });
+
+    let blobs = Arc::new(BlobStore {
+        dir: config.blob_dir.clone(),
+        max_bytes: config.upload_limit_bytes,
+    });
+    // Only mutating routes are rate limited, per client across namespaces.
+    let limiter = Arc::new(RateLimiter::new(config.rate_limit_per_min));
+    let mut app = Router::new();
+    for namespace in &namespaces {
+        let routes = routes(&config, namespace.admin_key.clone(), limiter.clone())
+            .layer(Extension(namespace.admin_key.clone()))
+            .layer(Extension(namespace.view_cache.clone()))
+            .with_state(namespace.state.clone());
+        app = match &namespace.tenant {
+            Some(tenant) => app.nest(&format!("/t/{}", tenant), routes),
+            None => app.merge(routes),
+        };
+    }
+    let app = app
+        .route_layer(middleware::from_fn_with_state(
+            config.request_timeout(),
+            timeout::enforce,
+        ))
+        // Inside `track`, so panics and timeouts are counted as the 500s and
+        // 504s they become.
+        .route_layer(CatchPanicLayer::custom(error::panic_response))
+        .route_layer(middleware::from_fn(telemetry::track))
+        .layer(DefaultBodyLimit::max(config.body_limit_bytes))
+        .layer(Extension(health.clone()))
+        .layer(Extension(prometheus))
+        .layer(Extension(signer))
+        .layer(Extension(blobs))
+        .layer(Extension(gateway))
+        .layer(compression::compression_layer())
+        .layer(cors);
+    let app = with_request_logging(app);
+
+    let (stop_tx, stop_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        shutdown::shutdown_signal().await;
+        let _ = stop_tx.send(true);
+    });
+
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::error!("Failed to bind {}: {}", addr, err);
+            std::process::exit(1);
+        }
+    };
+    let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let server: BoxFuture<std::io::Result<()>> = match tls {
+        Some(tls) => {
+            tracing::info!("Listening on https://{}", addr);
+            let handle = axum_server::Handle::new();
+            let stopping = handle.clone();
+            let stop = stop_rx.clone();
+            tokio::spawn(async move {
+                shutdown::triggered(stop).await;
+                // The deadline below bounds the wait, as with plain HTTP.
+                stopping.graceful_shutdown(None);
+            });
+            let listener = listener.into_std().expect("listener converts to std");
+            axum_server::from_tcp_rustls(listener, tls)
+                .handle(handle)
+                .serve(make_service)
+                .boxed()
+        }
+        None => {
+            tracing::info!("Listening on http://{}", addr);
+            axum::serve(listener, make_service)
+                .with_graceful_shutdown(shutdown::triggered(stop_rx.clone()))
+                .into_future()
+                .boxed()
+        }
+    };
 
-    let app = Router::new()
+    // Once a signal arrives, give in-flight requests a bounded window.
+    let deadline = async {
+        shutdown::triggered(stop_rx).await;
+        tokio::time::sleep(config.shutdown_timeout()).await;
+    };
+    tokio::select! {
+        result = server => result.unwrap(),
+        _ = deadline => tracing::warn!("Timed out waiting for in-flight requests"),
+    }
+
+    // Never overwrite the file with a state that was not loaded from it.
+    if !health.is_ready() {
+        tracing::warn!("State was not loaded; skipping shutdown flush");
+        return;
+    }
+    // Handlers still running past the deadline finish before we get the lock.
+    for namespace in &namespaces {
+        let mut state = namespace.state.write().await;
+        match namespace.backend.save(&mut state) {
+            Ok(()) => tracing::info!(
+                "Persisted {} NFTs to {}",
+                state.ledger.nfts.len(),
+                namespace.backend.describe()
+            ),
+            Err(err) => tracing::error!("Failed to persist state on shutdown: {}", err),
+        }
+    }
+}
+
+// Outermost layers, applied last: assign a request id, trace the request in
+// a span carrying it, and echo it back.
+fn with_request_logging(app: Router) -> Router {
+    app.layer(PropagateRequestIdLayer::x_request_id())
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(|req: &axum::extract::Request| {
+                    let request_id = req
+                        .headers()
+                        .get("x-request-id")
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or("-");
+                    tracing::info_span!(
+                        "request",
+                        method = %req.method(),
+                        path = %req.uri().path(),
+                        request_id,
+                    )
+                })
+                .on_response(DefaultOnResponse::new().level(tracing::Level::INFO)),
+        )
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+}
+
+// One independently stored NFT state: the default routes' or a tenant's.
+struct Namespace {
+    tenant: Option<String>,
+    state: SharedState,
+    backend: &'static Backend,
+    view_cache: Arc<ViewCache>,
+    admin_key: AdminKey,
+}
+
+// Exits if the backend can't be opened, as a bad flag would.
+fn open_namespace(config: &Config, tenant: Option<&str>, admin_key: AdminKey) -> Namespace {
+    let path = |path: &Path| match tenant {
+        Some(tenant) => tenant_path(path, tenant),
+        None => path.to_path_buf(),
+    };
+    let backend = match config.store {
+        StoreKind::Json => Backend::File(path(Path::new(STATE_PATH))),
+        StoreKind::Memory => Backend::Store(Box::new(store::MemoryStore::default())),
+        StoreKind::Sled => {
+            let sled_path = path(&config.sled_path);
+            match store::SledStore::open(&sled_path) {
+                Ok(store) => Backend::Store(Box::new(store)),
+                Err(err) => {
+                    eprintln!("Failed to open {}: {}", sled_path.display(), err);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+    Namespace {
+        tenant: tenant.map(str::to_string),
+        state: Arc::new(RwLock::new(AppState::new())),
+        // Needed until the process exits; `save_state` writes through it.
+        backend: Box::leak(Box::new(backend)),
+        view_cache: Arc::new(ViewCache::new(config.view_cache_size)),
+        admin_key,
+    }
+}
+
+// Applies the startup settings a freshly loaded state doesn't persist.
+fn configure(
+    state: &mut AppState,
+    config: &Config,
+    backend: &'static Backend,
+    view_cache: Arc<ViewCache>,
+    tenant: String,
+) {
+    state.reward_rate = config.reward_rate;
+    state.stake_lockup_secs = config.stake_lockup_secs;
+    state.mint_fee = config.mint_fee;
+    state.mint_keys.ttl_secs = config.idempotency_ttl_secs;
+    state.reservation_ttl_secs = config.reservation_ttl_secs;
+    state.reveal_mode = config.default_reveal;
+    state.id_scheme = config.id_scheme;
+    state.public_fields = config.public_fields.clone();
+    state.field_limits = config.field_limits();
+    state.view_cache = view_cache;
+    state.backend = Some(backend);
+    state.tenant = tenant;
+}
+
+// Every route of one namespace, before its state is attached.
+fn routes(config: &Config, admin_key: AdminKey, limiter: Arc<RateLimiter>) -> Router<SharedState> {
+    // Batch bodies legitimately run larger than the default limit, and
+    // uploads need room for the image plus the form around it.
+    let batch_limit = DefaultBodyLimit::max(config.batch_body_limit_bytes);
+    let upload_limit = DefaultBodyLimit::max(config.upload_limit_bytes + 64 * 1024);
+    let admin = Router::new()
         .route("/mint", post(mint_handler))
+        .route("/mint/batch", post(mint_batch_handler).layer(batch_limit))
+        .route(
+            "/mint/upload",
+            post(mint_upload_handler).layer(upload_limit),
+        )
+        .route("/mint/reserve", post(reserve_handler))
+        .route("/mint/finalize", post(finalize_handler))
+        .route("/credit", post(credit_handler))
+        .route("/ibc/import", post(ibc_import_handler))
+        .route("/collections/:name/schema", put(set_schema_handler))
+        .route(
+            "/collections/:name/public-view",
+            put(set_public_policy_handler),
+        )
+        .route("/nft/:id/export", get(backup_export_handler))
+        .route("/export", get(backup_export_all_handler))
+        .route("/import", post(backup_import_handler).layer(batch_limit))
+        .route(
+            "/airdrop/mint",
+            post(airdrop_mint_handler).layer(batch_limit),
+        )
+        .route(
+            "/simulate/airdrop",
+            post(simulate_airdrop_handler).layer(batch_limit),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            admin_key.clone(),
+            auth::require_admin,
+        ));
+    // Overrides of owner and lockup checks never run without the key.
+    let overrides = Router::new()
+        .route("/airdrop", post(airdrop_handler))
+        .route("/admin/force-unstake/:id", post(force_unstake_handler))
+        .route("/admin/force-unfreeze/:id", post(force_unfreeze_handler))
+        .route("/admin/reveal-all", get(reveal_all_handler))
+        .route_layer(middleware::from_fn_with_state(
+            admin_key.clone(),
+            auth::require_admin_key,
+        ));
+
+    let writes = Router::new()
+        .merge(admin)
+        .merge(overrides)
         .route("/transfer", post(transfer_handler))
-        .route("/view/:id", get(view_handler))
+        .route("/transfer/from", post(transfer_from_handler))
+        .route("/transfer/all", post(transfer_all_handler))
+        .route(
+            "/transfer/batch",
+            post(transfer_batch_handler).layer(batch_limit),
+        )
+        .route("/approve", post(approve_handler))
+        .route("/nft/:id", patch(update_metadata_handler))
+        .route("/nft/:id/repin", post(repin_handler))
         .route("/stake/:id", post(stake_handler))
         .route("/unstake/:id", post(unstake_handler))
-        .route("/airdrop", post(airdrop_handler))
-        .route("/ibc/export/:id", get(ibc_export_handler))
-        .route("/ibc/import", post(ibc_import_handler))
-        .with_state(state);
+        .route("/stake/batch", post(stake_batch_handler).layer(batch_limit))
+        .route(
+            "/unstake/batch",
+            post(unstake_batch_handler).layer(batch_limit),
+        )
+        .route("/claim/:id", post(claim_handler))
+        .route("/freeze/:id", post(freeze_handler))
+        .route("/unfreeze/:id", post(unfreeze_handler))
+        .route("/burn/:id", delete(burn_handler))
+        .route("/burn/batch", post(burn_batch_handler).layer(batch_limit))
+        .route("/nft/:id/lock-for-offer", post(lock_offer_handler))
+        .route("/nft/:id/accept-offer", post(accept_offer_handler))
+        .route("/nft/:id/cancel-offer", post(cancel_offer_handler))
+        .route("/nft/:id/shares", post(share_handler))
+        .route("/nft/:id/consent", post(consent_handler))
+        .route("/swap", post(swap_handler))
+        .route("/tx", post(tx_handler).layer(batch_limit))
+        .route("/ibc/export/batch", post(ibc_export_batch_handler))
+        .route("/ibc/export/:id", post(ibc_bridge_out_handler))
+        .route_layer(middleware::from_fn_with_state(limiter, ratelimit::limit));
 
-    println!("Listening on http://127.0.0.1:3000");
-    axum::Server::bind(&"127.0.0.1:3000".parse().unwrap())
-        .serve(app.into_make_service())
-        .await
-        .unwrap();
+    Router::new()
+        .merge(writes)
+        .route("/view/:id", get(view_handler))
+        .route("/view/:id/public", get(public_view_handler))
+        .route("/view/batch", post(view_batch_handler))
+        .route("/nfts", get(list_handler))
+        .route("/nfts/stream", get(stream_handler))
+        .route("/nfts/by-owner/:address", get(by_owner_handler))
+        .route("/search", get(search_handler))
+        .route("/reveal/proof", post(proof_handler))
+        .route("/reveal/verify", post(verify_handler))
+        .route("/nft/:id/history", get(history_handler))
+        .route("/nft/:id/ownership", get(ownership_handler))
+        .route("/transfer/preview", post(transfer_preview_handler))
+        .route("/nft/:id/reveals", get(reveals_handler))
+        .route("/nft/:id/royalty", get(royalty_handler))
+        .route("/nft/:id/qr", get(qr_handler))
+        .route("/nft/:id/image", get(image_handler))
+        .route("/nft/:id/metadata.json", get(token_metadata_handler))
+        .route("/collections", get(collections_handler))
+        .route("/collections/:name", get(collection_handler))
+        .route("/collections/:name/schema", get(schema_handler))
+        .route("/stats", get(stats_handler))
+        .route("/events", get(events_handler))
+        .route("/ws/events", get(ws_events_handler))
+        .route("/nonce/:owner", get(nonce_handler))
+        .route("/balance/:address", get(balance_handler))
+        .route("/health", get(health_handler))
+        .route("/version", get(version_handler))
+        .route("/openapi.json", get(openapi_handler))
+        .route("/ready", get(ready_handler))
+        .route("/metrics", get(metrics_handler))
 }
 
-// POST /mint
+// POST /mint?dry_run=true
+// A repeated Idempotency-Key returns the first response without minting again;
+// reusing one with a different body is a 409.
+#[utoipa::path(
+    post,
+    path = "/mint",
+    params(
+        DryRunQuery,
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response"),
+    ),
+    request_body = MintRequest,
+    responses(
+        (status = 200, description = "Minted, or `would_succeed` on a dry run", body = MintResponse),
+        (status = 409, description = "Collection sold out, id taken, or Idempotency-Key reused for a different request", body = ErrorBody),
+        (status = 402, description = "Owner's balance can't cover --mint-fee", body = ErrorBody),
+        (status = 400, description = "Invalid request", body = ErrorBody),
+    )
+)]
+#[tracing::instrument(skip_all, fields(nft_id = tracing::field::Empty))]
 async fn mint_handler(
-    state: axum::extract::State<Arc<Mutex<NFTState>>>,
+    state: axum::extract::State<SharedState>,
+    axum::extract::Query(query): axum::extract::Query<DryRunQuery>,
+    headers: HeaderMap,
     Json(req): Json<MintRequest>,
-) -> Json<MintResponse> {
-    let mut state = state.lock().unwrap();
-    let metadata = NFTMetadata {
-        name: req.name,
-        description: req.description,
-        image_cid: req.image_cid,
-        attributes: req.attributes,
-        shielded: true,
-    };
-    let id = mint_nft(&mut state, req.owner, metadata, Some(5));
-    Json(MintResponse { id })
+) -> Result<Response, NftError> {
+    if query.dry_run {
+        let state = state.read().await;
+        check_mint(&state, &req.owner, &req.item.into_item())?;
+        let response = GenericResponse {
+            status: "would_succeed".into(),
+        };
+        return Ok(Json(response).into_response());
+    }
+    let key = headers
+        .get("idempotency-key")
+        .map(|value| value.to_str().map(str::to_string))
+        .transpose()
+        .map_err(|_| NftError::Invalid("Idempotency-Key must be visible ASCII".into()))?;
+    let fingerprint = req.fingerprint();
+    let mut state = state.write().await;
+    let now = state.now();
+    if let Some(key) = key.as_deref() {
+        if let Some((id, nft)) = state.mint_keys.get(key, &fingerprint, now)? {
+            tracing::info!(nft_id = %id, "replayed idempotent mint");
+            let response = MintResponse {
+                id: id.clone(),
+                nft: nft.clone(),
+            };
+            return Ok(Json(response).into_response());
+        }
+    }
+    let id = mint_nft(&mut state, req.owner, req.item.into_item())?;
+    tracing::Span::current().record("nft_id", id.as_str());
+    tracing::info!("minted");
+    let nft = state
+        .ledger
+        .get_nft(&id)
+        .cloned()
+        .ok_or_else(|| NftError::Storage(format!("minted NFT {} is missing", id)))?;
+    // Before saving: the NFT is minted either way, so a retry after a failed
+    // save must find it rather than mint a second one.
+    if let Some(key) = key {
+        let entry = (id.clone(), nft.clone());
+        state.mint_keys.insert(key, fingerprint, entry, now);
+    }
+    save_state(&mut state)?;
+    Ok(Json(MintResponse { id, nft }).into_response())
+}
+
+// POST /mint/upload
+// Multipart form: `owner`, `metadata` (the /mint fields as JSON, without
+// image_cid) and `image` (the file). The image's CID becomes its image_cid.
+#[tracing::instrument(skip_all, fields(nft_id = tracing::field::Empty))]
+async fn mint_upload_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(blobs): Extension<Arc<BlobStore>>,
+    mut form: axum::extract::Multipart,
+) -> Result<Json<MintResponse>, NftError> {
+    let invalid =
+        |err: axum::extract::multipart::MultipartError| NftError::Invalid(err.body_text());
+    let (mut owner, mut metadata, mut image_cid) = (None, None, None);
+    while let Some(field) = form.next_field().await.map_err(invalid)? {
+        match field.name() {
+            Some("owner") => owner = Some(field.text().await.map_err(invalid)?),
+            Some("metadata") => metadata = Some(field.text().await.map_err(invalid)?),
+            Some("image") => {
+                let content_type = field.content_type().map(str::to_string);
+                let bytes = field.bytes().await.map_err(invalid)?;
+                image_cid = Some(blobs.put(content_type.as_deref(), &bytes).await?);
+            }
+            _ => {}
+        }
+    }
+    let missing = |name: &str| NftError::Invalid(format!("form field {} is required", name));
+    let owner = owner.ok_or_else(|| missing("owner"))?;
+    let image_cid = image_cid.ok_or_else(|| missing("image"))?;
+    let mut fields: serde_json::Value =
+        serde_json::from_str(&metadata.ok_or_else(|| missing("metadata"))?)
+            .map_err(|e| NftError::Invalid(format!("metadata is not valid JSON: {}", e)))?;
+    if let Some(fields) = fields.as_object_mut() {
+        fields.insert("image_cid".into(), image_cid.into());
+    }
+    let item: MintItemRequest = serde_json::from_value(fields)
+        .map_err(|e| NftError::Invalid(format!("invalid metadata: {}", e)))?;
+
+    let mut state = state.write().await;
+    let id = mint_nft(&mut state, owner, item.into_item())?;
+    save_state(&mut state)?;
+    tracing::Span::current().record("nft_id", id.as_str());
+    tracing::info!("minted from upload");
+    let nft = state
+        .ledger
+        .get_nft(&id)
+        .cloned()
+        .ok_or_else(|| NftError::Storage(format!("minted NFT {} is missing", id)))?;
+    Ok(Json(MintResponse { id, nft }))
+}
+
+// POST /mint/batch
+#[tracing::instrument(skip_all, fields(owner = %req.owner))]
+async fn mint_batch_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<MintBatchRequest>,
+) -> Result<Json<MintBatchResponse>, NftError> {
+    let items = req
+        .items
+        .into_iter()
+        .map(MintItemRequest::into_item)
+        .collect();
+    let mut state = state.write().await;
+    let ids = mint_nft_batch(&mut state, &req.owner, items)?;
+    save_state(&mut state)?;
+    tracing::info!(count = ids.len(), "minted batch");
+    Ok(Json(MintBatchResponse { ids }))
+}
+
+// POST /mint/reserve
+#[tracing::instrument(skip_all, fields(collection = %req.collection))]
+async fn reserve_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<ReserveRequest>,
+) -> Result<Json<ReserveResponse>, NftError> {
+    let mut state = state.write().await;
+    let (reservation, expires_at) = reserve_mint(&mut state, &req.collection)?;
+    save_state(&mut state)?;
+    tracing::info!(%reservation, "reserved");
+    Ok(Json(ReserveResponse {
+        reservation,
+        expires_at,
+    }))
+}
+
+// POST /mint/finalize
+#[tracing::instrument(skip_all, fields(reservation = %req.reservation))]
+async fn finalize_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<FinalizeRequest>,
+) -> Result<Json<MintResponse>, NftError> {
+    let mut state = state.write().await;
+    let id = finalize_mint(
+        &mut state,
+        &req.reservation,
+        req.item.into_item(),
+        req.owner,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!(nft_id = %id, "finalized");
+    let nft = state
+        .ledger
+        .get_nft(&id)
+        .cloned()
+        .ok_or_else(|| NftError::Storage(format!("minted NFT {} is missing", id)))?;
+    Ok(Json(MintResponse { id, nft }))
 }
 
 // POST /transfer
+#[utoipa::path(
+    post,
+    path = "/transfer",
+    params(DryRunQuery),
+    request_body = TransferRequest,
+    responses(
+        (status = 200, body = GenericResponse),
+        (status = 403, description = "Not the owner or bad signature", body = ErrorBody),
+        (status = 423, description = "Staked, frozen, in cooldown, offer-locked or exported", body = ErrorBody),
+    )
+)]
+#[tracing::instrument(skip_all, fields(nft_id = %req.id))]
 async fn transfer_handler(
-    state: axum::extract::State<Arc<Mutex<NFTState>>>,
+    state: axum::extract::State<SharedState>,
+    axum::extract::Query(query): axum::extract::Query<DryRunQuery>,
     Json(req): Json<TransferRequest>,
-) -> Json<GenericResponse> {
-    let mut state = state.lock().unwrap();
-    let result = transfer_nft(&mut state, &req.id, &req.to);
-    Json(GenericResponse {
-        status: result.map(|_| "ok".into()).unwrap_or_else(|e| e),
-    })
+) -> Result<Json<GenericResponse>, NftError> {
+    if query.dry_run {
+        let state = state.read().await;
+        check_transfer_signed(
+            &state,
+            &req.id,
+            &req.from,
+            &req.to,
+            req.nonce,
+            &req.signature,
+        )?;
+        return Ok(Json(GenericResponse {
+            status: "would_succeed".into(),
+        }));
+    }
+    let mut state = state.write().await;
+    transfer_signed(
+        &mut state,
+        &req.id,
+        &req.from,
+        &req.to,
+        req.nonce,
+        &req.signature,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!(to = %req.to, "transferred");
+    Ok(Json(GenericResponse {
+        status: "ok".into(),
+    }))
+}
+
+// POST /transfer/batch
+// Best effort; the response maps each id to its outcome.
+#[tracing::instrument(skip_all, fields(from = %req.from, count = req.ids.len()))]
+async fn transfer_batch_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<TransferBatchRequest>,
+) -> Result<Json<BTreeMap<String, BatchOutcome>>, NftError> {
+    let mut state = state.write().await;
+    let results = transfer_nft_batch(
+        &mut state,
+        &req.ids,
+        &req.from,
+        &req.to,
+        req.nonce,
+        &req.signature,
+    )?;
+    save_state(&mut state)?;
+    let moved = results.values().filter(|outcome| outcome.ok).count();
+    tracing::info!(to = %req.to, moved, "transferred batch");
+    Ok(Json(results))
+}
+
+// POST /transfer/preview
+// The NFT as it would look after the transfer; nothing is changed.
+async fn transfer_preview_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<TransferPreviewRequest>,
+) -> Result<Json<TransferPreview>, NftError> {
+    let state = state.read().await;
+    let preview = transfer_preview(&state, &req.id, &req.to, req.viewing_key.as_deref())?;
+    Ok(Json(preview))
+}
+
+// POST /transfer/all
+#[tracing::instrument(skip_all, fields(from = %req.from))]
+async fn transfer_all_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<TransferAllRequest>,
+) -> Result<Json<DrainReport>, NftError> {
+    let mut state = state.write().await;
+    let report = transfer_all(&mut state, &req.from, &req.to, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!(to = %req.to, moved = report.moved, skipped = report.skipped, "drained");
+    Ok(Json(report))
+}
+
+// POST /transfer/from
+#[tracing::instrument(skip_all, fields(nft_id = %req.id))]
+async fn transfer_from_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<TransferFromRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    transfer_from(
+        &mut state,
+        &req.id,
+        &req.from,
+        &req.to,
+        &req.caller,
+        req.nonce,
+        &req.signature,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!(from = %req.from, to = %req.to, caller = %req.caller, "transferred");
+    Ok(Json(GenericResponse {
+        status: "ok".into(),
+    }))
 }
 
-// GET /view/:id
+// POST /approve
+#[tracing::instrument(skip_all, fields(nft_id = %req.id))]
+async fn approve_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<ApproveRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    approve_nft(
+        &mut state,
+        &req.id,
+        &req.spender,
+        &req.caller,
+        req.nonce,
+        &req.signature,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!(spender = %req.spender, "approved");
+    Ok(Json(GenericResponse {
+        status: "approved".into(),
+    }))
+}
+
+// GET /view/:id?viewing_key=...
+// Sends an ETag; a matching If-None-Match gets 304 Not Modified. JSON unless
+// Accept asks for application/msgpack.
+#[utoipa::path(
+    get,
+    path = "/view/{id}",
+    params(("id" = String, Path, description = "NFT id"), ViewQuery),
+    responses(
+        (status = 200, description = "The NFT, or only its id if shielded", body = NftView),
+        (status = 304, description = "Unchanged since If-None-Match"),
+        (status = 404, body = ErrorBody),
+    )
+)]
 async fn view_handler(
-    state: axum::extract::State<Arc<Mutex<NFTState>>>,
-    axum::extract::Path(id): axum::extract::Path<String>,
-) -> Json<Option<NFT>> {
-    let state = state.lock().unwrap();
-    Json(reveal_nft(&state, &id, None))
+    state: axum::extract::State<SharedState>,
+    Extension(cache): Extension<Arc<ViewCache>>,
+    NftId(id): NftId,
+    axum::extract::Query(query): axum::extract::Query<ViewQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let format = Format::from_headers(&headers);
+    let key = ViewKey {
+        id: id.clone(),
+        viewing_key: query.viewing_key.clone(),
+        format,
+    };
+    // Keyed views skip the cache so every reveal reaches the reveal log.
+    let cacheable = query.viewing_key.is_none();
+    if let Some(hit) = cache.get(&key).filter(|_| cacheable) {
+        return cached_view_response(&headers, format, hit);
+    }
+    let state = state.read().await;
+    let Some(view) = reveal_view(&state, &id, query.viewing_key.as_deref()) else {
+        return format.render(&None::<NftView>);
+    };
+    let tag = etag::view_etag(
+        &id,
+        state.version(&id),
+        matches!(view, NftView::Full { .. }),
+    );
+    let body = match format.encode(&Some(view)) {
+        Ok(body) => axum::body::Bytes::from(body),
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err).into_response(),
+    };
+    let cached = CachedView { etag: tag, body };
+    // `expired` flips with the clock rather than on a recorded change, so
+    // views of expiring NFTs are never cached. Inserting under the read lock
+    // means no write can invalidate the entry before it lands.
+    let expiring = state
+        .extras
+        .get(&id)
+        .is_some_and(|extras| extras.expires_at.is_some());
+    if cacheable && !expiring {
+        cache.insert(key, cached.clone());
+    }
+    cached_view_response(&headers, format, cached)
+}
+
+fn cached_view_response(headers: &HeaderMap, format: Format, view: CachedView) -> Response {
+    if etag::not_modified(headers, &view.etag) {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, view.etag)]).into_response();
+    }
+    ([(ETAG, view.etag)], format.respond(view.body)).into_response()
+}
+
+// GET /view/:id/public
+// Never needs a viewing key, so it never shows the owner or attributes.
+async fn public_view_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+) -> Result<Json<PublicView>, NftError> {
+    let state = state.read().await;
+    reveal_public_view(&state, &id)
+        .map(Json)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))
+}
+
+// POST /view/batch
+async fn view_batch_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<ViewBatchRequest>,
+) -> Result<Json<BTreeMap<String, Option<NftView>>>, NftError> {
+    let state = state.read().await;
+    let views = reveal_views(&state, &req.ids, req.viewing_key.as_deref())?;
+    Ok(Json(views))
+}
+
+// GET /nfts?after=<id>&limit=50 (or the older ?offset=0&limit=50)
+// JSON unless Accept asks for application/msgpack.
+async fn list_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Query(query): axum::extract::Query<ListQuery>,
+    headers: HeaderMap,
+) -> Result<Response, NftError> {
+    if query.after.is_some() && query.offset.is_some() {
+        return Err(NftError::Invalid(
+            "use either after or offset, not both".into(),
+        ));
+    }
+    let state = state.read().await;
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let page = list_nfts(&state, query.after.as_deref(), offset, limit);
+    let response = ListResponse {
+        total: page.total,
+        offset,
+        limit,
+        items: page.items,
+        next_cursor: page.next_cursor,
+    };
+    Ok(Format::from_headers(&headers).render(&response))
+}
+
+// GET /nfts/stream
+// Every live NFT summary as newline-delimited JSON, without buffering the lot.
+async fn stream_handler(state: axum::extract::State<SharedState>) -> Response {
+    (
+        [(CONTENT_TYPE, "application/x-ndjson")],
+        stream_summaries(state.0),
+    )
+        .into_response()
+}
+
+// GET /nfts/by-owner/:address
+async fn by_owner_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Path(address): axum::extract::Path<String>,
+) -> Json<Vec<NFTSummary>> {
+    let state = state.read().await;
+    Json(nfts_by_owner(&state, &address))
+}
+
+// GET /search?trait=Background&value=Blue[&trait=...&value=...][&viewing_key=...]
+async fn search_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::RawQuery(query): axum::extract::RawQuery,
+) -> Result<Json<Vec<NFTSummary>>, NftError> {
+    let query = parse_search_query(query.as_deref().unwrap_or("")).map_err(NftError::Invalid)?;
+    let state = state.read().await;
+    Ok(Json(search_nfts(
+        &state,
+        &query.filters,
+        query.viewing_key.as_deref(),
+    )))
+}
+
+// POST /reveal/proof
+async fn proof_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(signer): Extension<Arc<ProofSigner>>,
+    Json(req): Json<ProofRequest>,
+) -> Result<Json<AttributeProof>, NftError> {
+    let state = state.read().await;
+    let proof = reveal_attribute_proof(
+        &state,
+        &signer,
+        &req.id,
+        &req.trait_type,
+        req.viewing_key.as_deref(),
+    )?;
+    Ok(Json(proof))
+}
+
+// POST /reveal/verify
+async fn verify_handler(
+    Extension(signer): Extension<Arc<ProofSigner>>,
+    Json(proof): Json<AttributeProof>,
+) -> Json<VerifyResponse> {
+    Json(VerifyResponse {
+        valid: signer.verify(&proof),
+    })
+}
+
+// POST /nft/:id/repin
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn repin_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(admin_key): Extension<AdminKey>,
+    NftId(id): NftId,
+    headers: HeaderMap,
+    Json(req): Json<RepinRequest>,
+) -> Result<Json<RepinResponse>, NftError> {
+    let mut state = state.write().await;
+    let auth = if admin_key.authorizes(&headers) {
+        Auth::Admin
+    } else {
+        Auth::Owner {
+            nonce: req.nonce,
+            signature: &req.signature,
+        }
+    };
+    repin_image(&mut state, &id, &req.image_cid, &req.caller, auth)?;
+    save_state(&mut state)?;
+    tracing::info!("image repinned");
+    let image_cid = state
+        .ledger
+        .get_nft(&id)
+        .map(|nft| nft.metadata.image_cid.clone())
+        .unwrap_or_default();
+    let image_cid_history = state
+        .extras
+        .get(&id)
+        .map(|extras| extras.image_cid_history.clone())
+        .unwrap_or_default();
+    Ok(Json(RepinResponse {
+        image_cid,
+        image_cid_history,
+    }))
+}
+
+// GET /nft/:id/history
+async fn history_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+) -> Result<Json<Vec<OwnershipRecord>>, NftError> {
+    let state = state.read().await;
+    Ok(Json(owner_history(&state, &id)?))
+}
+
+// GET /nft/:id/ownership
+async fn ownership_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+) -> Result<Json<Ownership>, NftError> {
+    let state = state.read().await;
+    Ok(Json(ownership(&state, &id)?))
+}
+
+// GET /nft/:id/reveals?caller=...&nonce=...&signature=...
+// For the owner (or an admin): when viewing keys revealed the NFT, and which.
+async fn reveals_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(admin_key): Extension<AdminKey>,
+    NftId(id): NftId,
+    axum::extract::Query(query): axum::extract::Query<RevealsQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RevealRecord>>, NftError> {
+    let mut state = state.write().await;
+    let auth = if admin_key.authorizes(&headers) {
+        Auth::Admin
+    } else {
+        Auth::Owner {
+            nonce: query.nonce,
+            signature: &query.signature,
+        }
+    };
+    let records = reveal_history(&mut state, &id, &query.caller, auth)?;
+    if let Auth::Owner { .. } = auth {
+        save_state(&mut state)?;
+    }
+    Ok(Json(records))
+}
+
+// GET /nft/:id/royalty?price=...
+async fn royalty_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    axum::extract::Query(query): axum::extract::Query<RoyaltyQuery>,
+) -> Result<Json<RoyaltyQuote>, NftError> {
+    let state = state.read().await;
+    Ok(Json(royalty_quote(&state, &id, query.price)?))
+}
+
+// GET /collections
+async fn collections_handler(
+    state: axum::extract::State<SharedState>,
+) -> Json<Vec<CollectionCount>> {
+    let state = state.read().await;
+    Json(collection_counts(&state))
+}
+
+// GET /collections/:name
+async fn collection_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Json<Vec<NFTSummary>> {
+    let state = state.read().await;
+    Json(nfts_in_collection(&state, &name))
+}
+
+// GET /collections/:name/schema
+async fn schema_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Result<Json<AttributeSchema>, NftError> {
+    let state = state.read().await;
+    state
+        .attribute_schemas
+        .get(&name)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| NftError::NotFound(format!("collection {} has no attribute schema", name)))
+}
+
+// POST /credit
+#[tracing::instrument(skip_all, fields(address = %req.address, amount = req.amount))]
+async fn credit_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<CreditRequest>,
+) -> Result<Json<BalanceResponse>, NftError> {
+    let mut state = state.write().await;
+    let balance = credit(&mut state, &req.address, req.amount)?;
+    save_state(&mut state)?;
+    tracing::info!(balance, "credited");
+    Ok(Json(BalanceResponse {
+        address: req.address,
+        balance,
+    }))
+}
+
+// PUT /collections/:name/schema
+// Applies to later mints and metadata updates; existing NFTs aren't rechecked.
+#[tracing::instrument(skip_all, fields(collection = %name))]
+async fn set_schema_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+    Json(schema): Json<AttributeSchema>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    set_schema(&mut state, &name, schema)?;
+    save_state(&mut state)?;
+    tracing::info!("attribute schema set");
+    Ok(Json(GenericResponse {
+        status: "schema set".into(),
+    }))
+}
+
+// PUT /collections/:name/public-view
+// Body is `{"fields": ["name", ...]}`, or `"private"` to hide the collection
+// from GET /view/:id/public altogether.
+#[tracing::instrument(skip_all, fields(collection = %name))]
+async fn set_public_policy_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+    Json(policy): Json<PublicPolicy>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    set_public_policy(&mut state, &name, policy)?;
+    save_state(&mut state)?;
+    tracing::info!("public view policy set");
+    Ok(Json(GenericResponse {
+        status: "public view set".into(),
+    }))
+}
+
+// PATCH /nft/:id
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn update_metadata_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<UpdateMetadataRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    update_metadata(
+        &mut state,
+        &id,
+        req.patch,
+        &req.caller,
+        req.nonce,
+        &req.signature,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!("metadata updated");
+    Ok(Json(GenericResponse {
+        status: "updated".into(),
+    }))
 }
 
 // POST /stake/:id
+#[utoipa::path(
+    post,
+    path = "/stake/{id}",
+    params(("id" = String, Path, description = "NFT id")),
+    request_body = SignedCallerRequest,
+    responses(
+        (status = 200, body = GenericResponse),
+        (status = 400, description = "Invalid request", body = ErrorBody),
+        (status = 403, description = "Not signed by the owner", body = ErrorBody),
+    )
+)]
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
 async fn stake_handler(
-    state: axum::extract::State<Arc<Mutex<NFTState>>>,
-    axum::extract::Path(id): axum::extract::Path<String>,
-) -> Json<GenericResponse> {
-    let mut state = state.lock().unwrap();
-    let result = stake_nft(&mut state, &id);
-    Json(GenericResponse {
-        status: result.map(|_| "staked".into()).unwrap_or_else(|e| e),
-    })
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<SignedCallerRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    stake_signed(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!("staked");
+    Ok(Json(GenericResponse {
+        status: "staked".into(),
+    }))
 }
 
 // POST /unstake/:id
+#[utoipa::path(
+    post,
+    path = "/unstake/{id}",
+    params(("id" = String, Path, description = "NFT id")),
+    request_body = SignedCallerRequest,
+    responses(
+        (status = 200, description = "Unstaked, with the rewards claimed", body = ClaimResponse),
+        (status = 403, description = "Not signed by the owner", body = ErrorBody),
+        (status = 423, description = "Still inside the staking lockup", body = ErrorBody),
+    )
+)]
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
 async fn unstake_handler(
-    state: axum::extract::State<Arc<Mutex<NFTState>>>,
-    axum::extract::Path(id): axum::extract::Path<String>,
-) -> Json<GenericResponse> {
-    let mut state = state.lock().unwrap();
-    let result = unstake_nft(&mut state, &id);
-    Json(GenericResponse {
-        status: result.map(|_| "unstaked".into()).unwrap_or_else(|e| e),
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<SignedCallerRequest>,
+) -> Result<Json<ClaimResponse>, NftError> {
+    let mut state = state.write().await;
+    let claimed = unstake_signed(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!(claimed, "unstaked");
+    Ok(Json(ClaimResponse {
+        status: "unstaked".into(),
+        claimed,
+    }))
+}
+
+// POST /admin/force-unstake/:id
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn force_unstake_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+) -> Result<Json<ClaimResponse>, NftError> {
+    let mut state = state.write().await;
+    let claimed = force_unstake_nft(&mut state, &id)?;
+    save_state(&mut state)?;
+    tracing::warn!(claimed, "force-unstaked by admin");
+    Ok(Json(ClaimResponse {
+        status: "unstaked".into(),
+        claimed,
+    }))
+}
+
+// POST /stake/batch
+// Best effort; the response maps each id to its outcome. `caller` signs
+// `signature::batch_action_message` over the ids.
+#[tracing::instrument(skip_all, fields(count = req.ids.len()))]
+async fn stake_batch_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<SignedIdsRequest>,
+) -> Result<Json<BTreeMap<String, BatchOutcome>>, NftError> {
+    let mut state = state.write().await;
+    let results = stake_nft_batch(&mut state, &req.ids, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    let staked = results.values().filter(|outcome| outcome.ok).count();
+    tracing::info!(staked, "staked batch");
+    Ok(Json(results))
+}
+
+// POST /unstake/batch
+#[tracing::instrument(skip_all, fields(count = req.ids.len()))]
+async fn unstake_batch_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<SignedIdsRequest>,
+) -> Result<Json<BTreeMap<String, UnstakeOutcome>>, NftError> {
+    let mut state = state.write().await;
+    let results = unstake_nft_batch(&mut state, &req.ids, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    let unstaked = results.values().filter(|outcome| outcome.ok).count();
+    tracing::info!(unstaked, "unstaked batch");
+    Ok(Json(results))
+}
+
+// POST /claim/:id
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn claim_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<SignedCallerRequest>,
+) -> Result<Json<ClaimResponse>, NftError> {
+    let mut state = state.write().await;
+    let claimed = claim_signed(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!(claimed, "claimed rewards");
+    Ok(Json(ClaimResponse {
+        status: "claimed".into(),
+        claimed,
+    }))
+}
+
+// POST /freeze/:id
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn freeze_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<SignedCallerRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    freeze_nft(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!("frozen");
+    Ok(Json(GenericResponse {
+        status: "frozen".into(),
+    }))
+}
+
+// POST /unfreeze/:id
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn unfreeze_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<SignedCallerRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    unfreeze_nft(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!("unfrozen");
+    Ok(Json(GenericResponse {
+        status: "unfrozen".into(),
+    }))
+}
+
+// GET /admin/reveal-all?after=<id>&limit=50
+// Unredacted, shielded or not; the overrides router keeps it admin-only.
+async fn reveal_all_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Query(query): axum::extract::Query<AuditQuery>,
+) -> Json<AuditResponse> {
+    let state = state.read().await;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let page = reveal_all(&state, query.after.as_deref(), limit);
+    tracing::warn!(count = page.items.len(), "admin revealed all");
+    Json(AuditResponse {
+        total: page.total,
+        items: page.items,
+        next_cursor: page.next_cursor,
     })
 }
 
+// POST /admin/force-unfreeze/:id
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn force_unfreeze_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    force_unfreeze_nft(&mut state, &id)?;
+    save_state(&mut state)?;
+    tracing::warn!("force-unfrozen by admin");
+    Ok(Json(GenericResponse {
+        status: "unfrozen".into(),
+    }))
+}
+
 // POST /airdrop
+// Moves an existing NFT without its owner's signature, so like the other
+// overrides it needs the admin key.
+#[utoipa::path(
+    post,
+    path = "/airdrop",
+    request_body = AirdropRequest,
+    responses(
+        (status = 200, body = GenericResponse),
+        (status = 400, description = "Invalid request", body = ErrorBody),
+        (status = 401, description = "Missing or wrong admin key", body = ErrorBody),
+    )
+)]
+#[tracing::instrument(skip_all, fields(nft_id = %req.id))]
 async fn airdrop_handler(
-    state: axum::extract::State<Arc<Mutex<NFTState>>>,
+    state: axum::extract::State<SharedState>,
     Json(req): Json<AirdropRequest>,
-) -> Json<GenericResponse> {
-    let mut state = state.lock().unwrap();
-    let result = airdrop_nft(&mut state, &req.id, req.recipients);
-    Json(GenericResponse {
-        status: result.map(|_| "airdropped".into()).unwrap_or_else(|e| e),
-    })
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    airdrop_nft(&mut state, &req.id, req.recipients)?;
+    save_state(&mut state)?;
+    tracing::info!("airdropped");
+    Ok(Json(GenericResponse {
+        status: "airdropped".into(),
+    }))
+}
+
+// POST /airdrop/mint
+#[tracing::instrument(skip_all, fields(owner_source = %req.owner_source))]
+async fn airdrop_mint_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<AirdropMintRequest>,
+) -> Result<Json<MintBatchResponse>, NftError> {
+    let mut state = state.write().await;
+    let ids = airdrop_mint(
+        &mut state,
+        req.template.into_item(),
+        &req.owner_source,
+        req.recipients,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!(count = ids.len(), "airdropped copies");
+    Ok(Json(MintBatchResponse { ids }))
+}
+
+// POST /simulate/airdrop
+// Reports what POST /airdrop/mint would do with the same body; mints nothing.
+async fn simulate_airdrop_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<AirdropMintRequest>,
+) -> Json<AirdropSimulation> {
+    let state = state.read().await;
+    Json(simulate_airdrop(
+        &state,
+        &req.template.into_item(),
+        &req.owner_source,
+        &req.recipients,
+    ))
+}
+
+// DELETE /burn/:id?caller=<owner>&nonce=<n>&signature=<hex>
+// Signed by the owner as a /burn/batch of just this id.
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn burn_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    axum::extract::Query(req): axum::extract::Query<SignedCallerRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    burn_signed(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!("burned");
+    Ok(Json(GenericResponse {
+        status: "burned".into(),
+    }))
+}
+
+// POST /burn/batch
+// Signed by the owner over `(nonce, ids)`; ids it can't burn are reported, not fatal.
+#[tracing::instrument(skip_all, fields(caller = %req.caller, count = req.ids.len()))]
+async fn burn_batch_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<SignedIdsRequest>,
+) -> Result<Json<BTreeMap<String, BatchOutcome>>, NftError> {
+    let mut state = state.write().await;
+    let results = burn_nft_batch(&mut state, &req.ids, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    let burned = results.values().filter(|outcome| outcome.ok).count();
+    tracing::info!(burned, "batch burned");
+    Ok(Json(results))
+}
+
+// POST /ibc/export/:id
+// Bridges the NFT out; it stays locked here until imported back.
+#[utoipa::path(
+    post,
+    path = "/ibc/export/{id}",
+    params(("id" = String, Path, description = "NFT id")),
+    request_body = SignedCallerRequest,
+    responses(
+        (status = 200, description = "The IBC payload", body = String),
+        (status = 403, body = ErrorBody),
+    )
+)]
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn ibc_bridge_out_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<SignedCallerRequest>,
+) -> Result<Json<String>, NftError> {
+    let mut state = state.write().await;
+    let payload = export_nft(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!("exported over IBC");
+    Ok(Json(payload))
 }
 
-// GET /ibc/export/:id
-async fn ibc_export_handler(
-    state: axum::extract::State<Arc<Mutex<NFTState>>>,
-    axum::extract::Path(id): axum::extract::Path<String>,
-) -> Json<Option<String>> {
-    let state = state.lock().unwrap();
-    Json(state.get_nft(&id).map(export_nft_for_ibc))
+// POST /ibc/export/batch
+// Bridges out every id, signed once by the owner over
+// `signature::batch_action_message`. A map of id to payload, or with `framed`
+// one blob of them all.
+#[tracing::instrument(skip_all, fields(count = req.ids.len()))]
+async fn ibc_export_batch_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<IBCExportBatchRequest>,
+) -> Result<Response, NftError> {
+    let mut state = state.write().await;
+    let (caller, nonce, signature) = (&req.caller, req.nonce, &req.signature);
+    let response = if req.framed {
+        let blob = export_framed(&mut state, &req.ids, caller, nonce, signature)?;
+        Json(IBCFramedResponse { blob }).into_response()
+    } else {
+        let payloads = export_batch(&mut state, &req.ids, caller, nonce, signature)?;
+        Json(payloads).into_response()
+    };
+    save_state(&mut state)?;
+    tracing::info!("exported batch over IBC");
+    Ok(response)
+}
+
+// GET /nft/:id/qr?viewing_key=...
+// SVG QR code of the payload an export handed out, for wallets to scan. Only
+// exported NFTs have one, so scanning it can't bridge an NFT still spendable
+// here. The payload carries the full metadata, so a shielded NFT's needs a
+// viewing key that reveals it.
+async fn qr_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    axum::extract::Query(query): axum::extract::Query<ViewQuery>,
+) -> Result<Response, NftError> {
+    let state = state.read().await;
+    ensure_not_burned(&state, &id)?;
+    let nft = state
+        .ledger
+        .get_nft(&id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if !is_revealed(&state, nft, query.viewing_key.as_deref()) {
+        return Err(NftError::Forbidden(format!(
+            "NFT {} is shielded; a viewing key is required for its QR code",
+            id
+        )));
+    }
+    let payload = exported_payload(&state, &id).ok_or_else(|| {
+        NftError::Conflict(format!(
+            "NFT {} has not been exported; POST /ibc/export/{} first",
+            id, id
+        ))
+    })?;
+    let svg = qr::qr_svg(&payload).map_err(|err| match err {
+        qrcode::types::QrError::DataTooLong => NftError::TooLarge(format!(
+            "IBC payload for NFT {} is {} bytes, too large for a QR code; use the payload POST /ibc/export/{} returned instead",
+            id,
+            payload.len(),
+            id
+        )),
+        other => NftError::Storage(format!("failed to render QR code: {}", other)),
+    })?;
+    Ok(([(CONTENT_TYPE, "image/svg+xml")], svg).into_response())
+}
+
+// GET /nft/:id/image?viewing_key=...
+// Redirects to the image on the configured IPFS gateway, or proxies it. A
+// shielded NFT's image is only given out with a viewing key that reveals it.
+async fn image_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(gateway): Extension<Arc<Gateway>>,
+    NftId(id): NftId,
+    axum::extract::Query(query): axum::extract::Query<ViewQuery>,
+) -> Result<Response, NftError> {
+    let cid = {
+        let state = state.read().await;
+        let nft = match reveal_view(&state, &id, query.viewing_key.as_deref()) {
+            Some(NftView::Full { nft, .. }) => nft,
+            Some(NftView::Redacted { .. }) => {
+                return Err(NftError::Forbidden(format!(
+                    "NFT {} is shielded; a viewing key is required for its image",
+                    id
+                )))
+            }
+            None => return Err(NftError::NotFound(format!("NFT {} not found", id))),
+        };
+        nft.metadata.image_cid
+    };
+    if cid.is_empty() {
+        return Err(NftError::NotFound(format!("NFT {} has no image", id)));
+    }
+    gateway.respond(&cid).await.map_err(NftError::BadGateway)
+}
+
+// GET /nft/:id/metadata.json?viewing_key=...&gateway=true
+// The NFT as an ERC-721 tokenURI document. Shielded NFTs get a placeholder
+// unless the viewing key reveals them.
+async fn token_metadata_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(gateway): Extension<Arc<Gateway>>,
+    NftId(id): NftId,
+    axum::extract::Query(query): axum::extract::Query<TokenMetadataQuery>,
+) -> Result<Json<TokenMetadata>, NftError> {
+    let state = state.read().await;
+    let gateway = query.gateway.then_some(gateway.as_ref());
+    token_metadata(&state, &id, query.viewing_key.as_deref(), gateway)
+        .map(Json)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))
+}
+
+// POST /tx
+#[tracing::instrument(skip_all, fields(operations = req.operations.len()))]
+async fn tx_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<TxRequest>,
+) -> Result<Json<TxResponse>, NftError> {
+    let mut state = state.write().await;
+    let results = apply_tx(&mut state, req.operations)?;
+    save_state(&mut state)?;
+    tracing::info!("applied transaction");
+    Ok(Json(TxResponse { results }))
+}
+
+// POST /nft/:id/lock-for-offer
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn lock_offer_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<LockOfferRequest>,
+) -> Result<Json<OfferLock>, NftError> {
+    let mut state = state.write().await;
+    let lock = lock_for_offer(
+        &mut state,
+        &id,
+        &req.buyer,
+        req.expires_at,
+        &req.caller,
+        req.nonce,
+        &req.signature,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!(buyer = %lock.buyer, "locked for offer");
+    Ok(Json(lock))
+}
+
+// POST /nft/:id/accept-offer
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn accept_offer_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<SignedCallerRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    accept_offer(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!("offer accepted");
+    Ok(Json(GenericResponse {
+        status: "transferred".into(),
+    }))
+}
+
+// POST /nft/:id/cancel-offer
+#[tracing::instrument(skip_all, fields(nft_id = %id))]
+async fn cancel_offer_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<SignedCallerRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    cancel_offer(&mut state, &id, &req.caller, req.nonce, &req.signature)?;
+    save_state(&mut state)?;
+    tracing::info!("offer cancelled");
+    Ok(Json(GenericResponse {
+        status: "cancelled".into(),
+    }))
+}
+
+// POST /nft/:id/shares
+#[tracing::instrument(skip_all, fields(nft_id = %id, holders = req.shares.len()))]
+async fn share_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<ShareRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    share_nft(
+        &mut state,
+        &id,
+        req.shares,
+        &req.caller,
+        req.nonce,
+        &req.signature,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!("shared");
+    Ok(Json(GenericResponse {
+        status: "shared".into(),
+    }))
+}
+
+// POST /nft/:id/consent
+// A shareholder's vote to transfer; the majority vote moves the NFT.
+#[tracing::instrument(skip_all, fields(nft_id = %id, to = %req.to))]
+async fn consent_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+    Json(req): Json<ConsentRequest>,
+) -> Result<Json<ConsentStatus>, NftError> {
+    let mut state = state.write().await;
+    let status = consent_transfer(
+        &mut state,
+        &id,
+        &req.to,
+        &req.caller,
+        req.nonce,
+        &req.signature,
+    )?;
+    save_state(&mut state)?;
+    tracing::info!(
+        consented_bps = status.consented_bps,
+        transferred = status.transferred,
+        "transfer consent recorded"
+    );
+    Ok(Json(status))
+}
+
+// POST /swap
+#[tracing::instrument(skip_all, fields(nft_a = %req.nft_a, nft_b = %req.nft_b))]
+async fn swap_handler(
+    state: axum::extract::State<SharedState>,
+    Json(req): Json<SwapRequest>,
+) -> Result<Json<GenericResponse>, NftError> {
+    let mut state = state.write().await;
+    let a = SwapSide {
+        nft: req.nft_a,
+        owner: req.owner_a,
+        nonce: req.nonce_a,
+        signature: req.signature_a,
+    };
+    let b = SwapSide {
+        nft: req.nft_b,
+        owner: req.owner_b,
+        nonce: req.nonce_b,
+        signature: req.signature_b,
+    };
+    swap_nfts(&mut state, &a, &b)?;
+    save_state(&mut state)?;
+    tracing::info!("NFTs swapped");
+    Ok(Json(GenericResponse {
+        status: "swapped".into(),
+    }))
 }
 
 // POST /ibc/import
+#[utoipa::path(
+    post,
+    path = "/ibc/import",
+    request_body = IBCImportRequest,
+    responses(
+        (status = 200, body = GenericResponse),
+        (status = 401, description = "overwrite without the admin key", body = ErrorBody),
+        (status = 402, description = "Owner's balance can't cover --mint-fee", body = ErrorBody),
+        (status = 409, description = "Id already exists", body = ErrorBody),
+    )
+)]
+#[tracing::instrument(skip_all, fields(nft_id = tracing::field::Empty))]
 async fn ibc_import_handler(
-    state: axum::extract::State<Arc<Mutex<NFTState>>>,
+    state: axum::extract::State<SharedState>,
+    Extension(admin_key): Extension<AdminKey>,
+    headers: HeaderMap,
     Json(req): Json<IBCImportRequest>,
-) -> Json<GenericResponse> {
-    let mut state = state.lock().unwrap();
-    let nft = import_nft_from_ibc(&req.serialized);
-    let id = nft.id.clone();
-    state.nfts.insert(id.clone(), nft);
-    Json(GenericResponse {
+) -> Result<Json<GenericResponse>, NftError> {
+    // Like the other overrides, closed when no admin key is configured.
+    if req.overwrite && !admin_key.authorizes(&headers) {
+        return Err(NftError::Unauthorized(
+            "overwrite needs the admin bearer token".into(),
+        ));
+    }
+    let mut state = state.write().await;
+    let id = import_nft(&mut state, &req.serialized, req.overwrite)?;
+    save_state(&mut state)?;
+    tracing::Span::current().record("nft_id", id.as_str());
+    tracing::info!("imported over IBC");
+    Ok(Json(GenericResponse {
         status: format!("imported {}", id),
+    }))
+}
+
+// GET /nft/:id/export
+// A backup document that POST /import restores exactly, id and all.
+async fn backup_export_handler(
+    state: axum::extract::State<SharedState>,
+    NftId(id): NftId,
+) -> Result<Json<Backup>, NftError> {
+    let state = state.read().await;
+    Ok(Json(export_one(&state, &id)?))
+}
+
+// GET /export
+async fn backup_export_all_handler(state: axum::extract::State<SharedState>) -> Json<Backup> {
+    let state = state.read().await;
+    Json(export_all(&state))
+}
+
+// POST /import?overwrite=true
+#[tracing::instrument(skip_all, fields(count = backup.nfts.len()))]
+async fn backup_import_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(admin_key): Extension<AdminKey>,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<OverwriteQuery>,
+    Json(backup): Json<Backup>,
+) -> Result<Json<BackupImportResponse>, NftError> {
+    // Replacing NFTs needs the key even when the rest of /import is open.
+    if query.overwrite && !admin_key.authorizes(&headers) {
+        return Err(NftError::Unauthorized(
+            "overwrite needs the admin bearer token".into(),
+        ));
+    }
+    let mut state = state.write().await;
+    let imported = import_backup(&mut state, backup, query.overwrite)?;
+    save_state(&mut state)?;
+    tracing::info!("imported from backup");
+    Ok(Json(BackupImportResponse { imported }))
+}
+
+// GET /stats
+async fn stats_handler(state: axum::extract::State<SharedState>) -> Json<StateStats> {
+    let state = state.read().await;
+    Json(state_stats(&state))
+}
+
+// GET /events?since=<seq>&nft_id=<id>&kind=<kind>&limit=<n>
+async fn events_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Query(query): axum::extract::Query<EventsQuery>,
+) -> Json<Vec<NftEvent>> {
+    let state = state.read().await;
+    let filter = query.filter();
+    Json(
+        state
+            .events
+            .query(query.since.unwrap_or(0), &filter, query.limit),
+    )
+}
+
+// GET /ws/events?since=<seq>&nft_id=<id>&kind=<kind>
+async fn ws_events_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Query(query): axum::extract::Query<EventsQuery>,
+    upgrade: axum::extract::ws::WebSocketUpgrade,
+) -> axum::response::Response {
+    let state = state.0.clone();
+    let filter = query.filter();
+    upgrade.on_upgrade(move |socket| ws::stream_events(socket, state, query.since, filter))
+}
+
+// GET /nonce/:owner
+async fn nonce_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Path(owner): axum::extract::Path<String>,
+) -> Json<NonceResponse> {
+    let state = state.read().await;
+    let next_nonce = next_nonce(&state, &owner);
+    Json(NonceResponse { owner, next_nonce })
+}
+
+// GET /balance/:address
+async fn balance_handler(
+    state: axum::extract::State<SharedState>,
+    axum::extract::Path(address): axum::extract::Path<String>,
+) -> Json<BalanceResponse> {
+    let state = state.read().await;
+    let balance = balance(&state, &address);
+    Json(BalanceResponse { address, balance })
+}
+
+// GET /version
+async fn version_handler() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION"),
+        git_commit: option_env!("PNFT_GIT_COMMIT"),
+        state_schema_version: schema::STATE_SCHEMA_VERSION,
     })
 }
 
+// GET /openapi.json
+async fn openapi_handler() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+// GET /health
+async fn health_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(health): Extension<Arc<Health>>,
+) -> Json<HealthResponse> {
+    // Report the count only if it can be read without waiting on a writer.
+    let nfts = state.try_read().ok().map(|state| state.ledger.nfts.len());
+    Json(HealthResponse {
+        status: "ok".into(),
+        uptime_secs: health.uptime_secs(),
+        nfts,
+    })
+}
+
+// GET /ready
+async fn ready_handler(
+    Extension(health): Extension<Arc<Health>>,
+) -> (StatusCode, Json<ReadyResponse>) {
+    let ready = health.is_ready();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(ReadyResponse { ready }))
+}
+
+// Called by mutating handlers while they still hold the lock, so writes
+// to the backend are serialized.
+fn save_state(state: &mut AppState) -> Result<(), NftError> {
+    let backend = state
+        .backend
+        .ok_or_else(|| NftError::Storage("no storage backend configured".into()))?;
+    backend.save(state)?;
+    Ok(())
+}
+
+// GET /metrics
+async fn metrics_handler(
+    state: axum::extract::State<SharedState>,
+    Extension(prometheus): Extension<PrometheusHandle>,
+) -> String {
+    // Scrapes never wait on a writer; a busy lock keeps the last gauge value.
+    if let Ok(state) = state.try_read() {
+        metrics::gauge!(telemetry::NFTS).set(state.ledger.nfts.len() as f64);
+    }
+    prometheus.render()
+}
+
 // Request/Response structs
 
-#[derive(serde::Deserialize)]
-struct MintRequest {
-    owner: String,
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+struct MintItemRequest {
     name: String,
     description: String,
     image_cid: String,
-    attributes: String,
+    attributes: Attributes,
+    collection: Option<String>,
+    max_supply: Option<u32>,
+    // Seconds after minting before NFTs in the collection can be transferred.
+    transfer_cooldown_secs: Option<u64>,
+    royalty_bps: Option<u16>,
+    royalty_recipient: Option<String>,
+    // Defaults to shielded; `false` mints a public NFT anyone can view.
+    shielded: Option<bool>,
+    // Passed through to penumbra_nft's `mint_nft`; see `MintOptions`.
+    upstream_param: Option<u32>,
+    // Unix seconds; expired NFTs can be viewed but not transferred.
+    expires_at: Option<u64>,
 }
 
-#[derive(serde::Serialize)]
+impl MintItemRequest {
+    fn into_item(self) -> MintItem {
+        MintItem {
+            metadata: NFTMetadata {
+                name: self.name,
+                description: self.description,
+                image_cid: self.image_cid,
+                attributes: encode_attributes(&self.attributes.into_vec()),
+                shielded: self.shielded.unwrap_or(true),
+            },
+            extras: NftExtras {
+                collection: self.collection,
+                royalty: Royalty::from_parts(self.royalty_bps, self.royalty_recipient),
+                expires_at: self.expires_at,
+                ..Default::default()
+            },
+            max_supply: self.max_supply,
+            transfer_cooldown_secs: self.transfer_cooldown_secs,
+            options: MintOptions {
+                upstream_param: self.upstream_param.unwrap_or(mint::DEFAULT_UPSTREAM_PARAM),
+            },
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+struct MintRequest {
+    owner: String,
+    #[serde(flatten)]
+    item: MintItemRequest,
+}
+
+impl MintRequest {
+    // What an Idempotency-Key is tied to: a hash of the request as parsed, so
+    // whitespace and key order in the body don't matter.
+    fn fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let body = serde_json::to_vec(self).expect("requests always serialize");
+        hex::encode(Sha256::digest(body))
+    }
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
 struct MintResponse {
     id: String,
+    // The record as stored, including server-applied defaults.
+    #[schema(value_type = Object)]
+    nft: NFT,
+}
+
+#[derive(serde::Deserialize)]
+struct MintBatchRequest {
+    owner: String,
+    items: Vec<MintItemRequest>,
+}
+
+#[derive(serde::Serialize)]
+struct MintBatchResponse {
+    ids: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct ReserveRequest {
+    collection: String,
+}
+
+#[derive(serde::Serialize)]
+struct ReserveResponse {
+    reservation: String,
+    expires_at: u64,
 }
 
 #[derive(serde::Deserialize)]
+struct FinalizeRequest {
+    reservation: String,
+    owner: String,
+    #[serde(flatten)]
+    item: MintItemRequest,
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 struct TransferRequest {
+    #[serde(deserialize_with = "ids::normalized")]
     id: String,
+    from: String,
+    to: String,
+    nonce: u64,
+    // Hex ed25519 signature by `from` over `signature::transfer_message`.
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+struct TransferBatchRequest {
+    #[serde(deserialize_with = "ids::normalized_list")]
+    ids: Vec<String>,
+    from: String,
     to: String,
+    nonce: u64,
+    // Hex ed25519 signature by `from` over `signature::batch_transfer_message`.
+    signature: String,
 }
 
 #[derive(serde::Deserialize)]
+struct TransferPreviewRequest {
+    #[serde(deserialize_with = "ids::normalized")]
+    id: String,
+    to: String,
+    viewing_key: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct TransferAllRequest {
+    from: String,
+    to: String,
+    nonce: u64,
+    // Hex ed25519 signature by `from` over `signature::drain_message`.
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+struct TransferFromRequest {
+    #[serde(deserialize_with = "ids::normalized")]
+    id: String,
+    from: String,
+    to: String,
+    caller: String,
+    nonce: u64,
+    // Hex ed25519 signature by `caller`: over `signature::transfer_message`
+    // if it is the owner, `signature::transfer_from_message` if the spender.
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ApproveRequest {
+    #[serde(deserialize_with = "ids::normalized")]
+    id: String,
+    spender: String,
+    caller: String,
+    nonce: u64,
+    // Hex ed25519 signature by the owner over `signature::approve_message`.
+    signature: String,
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct DryRunQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ViewQuery {
+    viewing_key: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct TokenMetadataQuery {
+    viewing_key: Option<String>,
+    // Point `image` at the configured gateway instead of an ipfs:// URI.
+    #[serde(default)]
+    gateway: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct ViewBatchRequest {
+    #[serde(deserialize_with = "ids::normalized_list")]
+    ids: Vec<String>,
+    viewing_key: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct ListQuery {
+    after: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+struct AuditQuery {
+    #[serde(default, deserialize_with = "ids::normalized_opt")]
+    after: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(serde::Serialize)]
+struct AuditResponse {
+    total: usize,
+    items: Vec<AuditEntry>,
+    next_cursor: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct ListResponse {
+    total: usize,
+    offset: usize,
+    limit: usize,
+    items: Vec<NFTSummary>,
+    next_cursor: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct ProofRequest {
+    #[serde(deserialize_with = "ids::normalized")]
+    id: String,
+    trait_type: String,
+    viewing_key: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct VerifyResponse {
+    valid: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct LockOfferRequest {
+    caller: String,
+    buyer: String,
+    // Unix seconds; the lock releases itself after this.
+    expires_at: u64,
+    nonce: u64,
+    // Hex ed25519 signature by the owner over `signature::lock_offer_message`.
+    signature: String,
+}
+
+// Each owner signs `signature::swap_message` with their own nonce.
+#[derive(serde::Deserialize)]
+struct SwapRequest {
+    #[serde(deserialize_with = "ids::normalized")]
+    nft_a: String,
+    owner_a: String,
+    nonce_a: u64,
+    signature_a: String,
+    #[serde(deserialize_with = "ids::normalized")]
+    nft_b: String,
+    owner_b: String,
+    nonce_b: u64,
+    signature_b: String,
+}
+
+#[derive(serde::Deserialize)]
+struct RepinRequest {
+    caller: String,
+    image_cid: String,
+    // Not needed with the admin bearer token.
+    #[serde(default)]
+    nonce: u64,
+    // Hex ed25519 signature by the owner over `signature::repin_message`.
+    #[serde(default)]
+    signature: String,
+}
+
+#[derive(serde::Serialize)]
+struct RepinResponse {
+    image_cid: String,
+    image_cid_history: Vec<CidChange>,
+}
+
+#[derive(serde::Deserialize)]
+struct UpdateMetadataRequest {
+    caller: String,
+    nonce: u64,
+    // Hex ed25519 signature by the owner over `signature::metadata_patch_message`.
+    signature: String,
+    #[serde(flatten)]
+    patch: MetadataPatch,
+}
+
+#[derive(serde::Deserialize)]
+struct ShareRequest {
+    caller: String,
+    shares: Vec<Share>,
+    nonce: u64,
+    // Hex ed25519 signature by the owner over `signature::share_message`.
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ConsentRequest {
+    caller: String,
+    to: String,
+    nonce: u64,
+    // Hex ed25519 signature by the shareholder over `signature::consent_message`.
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+struct RevealsQuery {
+    #[serde(default)]
+    caller: String,
+    // Not needed with the admin key.
+    #[serde(default)]
+    nonce: u64,
+    // Hex ed25519 signature by the owner over
+    // `signature::action_message("reveals")`.
+    #[serde(default)]
+    signature: String,
+}
+
+// For batch burns and stakes: `caller` signs the whole list at once.
+#[derive(serde::Deserialize)]
+struct SignedIdsRequest {
+    #[serde(deserialize_with = "ids::normalized_list")]
+    ids: Vec<String>,
+    caller: String,
+    nonce: u64,
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+struct OverwriteQuery {
+    #[serde(default)]
+    overwrite: bool,
+}
+
+#[derive(serde::Serialize)]
+struct BackupImportResponse {
+    imported: Vec<String>,
+}
+
+// For actions on one NFT with no other input; `caller` signs
+// `signature::action_message` naming the action.
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+struct SignedCallerRequest {
+    caller: String,
+    nonce: u64,
+    signature: String,
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 struct AirdropRequest {
+    #[serde(deserialize_with = "ids::normalized")]
     id: String,
     recipients: Vec<String>,
 }
 
 #[derive(serde::Deserialize)]
+struct AirdropMintRequest {
+    owner_source: String,
+    recipients: Vec<String>,
+    #[serde(flatten)]
+    template: MintItemRequest,
+}
+
+#[derive(serde::Deserialize)]
+struct TxRequest {
+    operations: Vec<Operation>,
+}
+
+#[derive(serde::Serialize)]
+struct TxResponse {
+    results: Vec<OperationResult>,
+}
+
+#[derive(serde::Deserialize)]
+struct IBCExportBatchRequest {
+    #[serde(deserialize_with = "ids::normalized_list")]
+    ids: Vec<String>,
+    #[serde(default)]
+    framed: bool,
+    caller: String,
+    nonce: u64,
+    signature: String,
+}
+
+#[derive(serde::Serialize)]
+struct IBCFramedResponse {
+    blob: String,
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 struct IBCImportRequest {
     serialized: String,
+    // Replace an existing NFT with the same id instead of rejecting with 409.
+    // Needs the admin key.
+    #[serde(default)]
+    overwrite: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct RoyaltyQuery {
+    price: u64,
+}
+
+#[derive(serde::Deserialize)]
+struct EventsQuery {
+    since: Option<u64>,
+    #[serde(default, deserialize_with = "ids::normalized_opt")]
+    nft_id: Option<String>,
+    kind: Option<EventKind>,
+    // Ignored by /ws/events, which streams without end.
+    limit: Option<usize>,
+}
+
+impl EventsQuery {
+    fn filter(&self) -> EventFilter {
+        EventFilter {
+            nft_id: self.nft_id.clone(),
+            kind: self.kind,
+        }
+    }
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct ClaimResponse {
+    status: String,
+    claimed: u64,
 }
 
 #[derive(serde::Serialize)]
+struct VersionResponse {
+    version: &'static str,
+    // Unset when built outside a git checkout.
+    git_commit: Option<&'static str>,
+    state_schema_version: u32,
+}
+
+#[derive(serde::Serialize)]
+struct HealthResponse {
+    status: String,
+    uptime_secs: u64,
+    nfts: Option<usize>,
+}
+
+#[derive(serde::Serialize)]
+struct ReadyResponse {
+    ready: bool,
+}
+
+#[derive(serde::Serialize)]
+struct NonceResponse {
+    owner: String,
+    next_nonce: u64,
+}
+
+#[derive(serde::Deserialize)]
+struct CreditRequest {
+    address: String,
+    amount: u64,
+}
+
+#[derive(serde::Serialize)]
+struct BalanceResponse {
+    address: String,
+    balance: u64,
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
 struct GenericResponse {
     status: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, Key};
+    use axum::{body::Body, http::Request};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    // The default namespace's routes, saving to memory, without an admin key
+    // or rate limit.
+    fn app(state: AppState) -> (Router, SharedState) {
+        app_with_key(state, None)
+    }
+
+    fn app_with_key(mut state: AppState, admin_key: Option<&str>) -> (Router, SharedState) {
+        let config = Cli::parse_from(["pnft-cli-rpc"]).serve;
+        let backend = Backend::Store(Box::new(store::MemoryStore::default()));
+        state.backend = Some(Box::leak(Box::new(backend)));
+        let view_cache = state.view_cache.clone();
+        let state = Arc::new(RwLock::new(state));
+        let admin_key = AdminKey(admin_key.map(Arc::from));
+        let app = routes(&config, admin_key.clone(), Arc::new(RateLimiter::new(0)))
+            .layer(Extension(admin_key))
+            .layer(Extension(view_cache))
+            .with_state(state.clone());
+        (app, state)
+    }
+
+    async fn send(app: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    async fn text(app: &Router, request: Request<Body>) -> String {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    fn post(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::post(uri)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn mint_body(owner: &str) -> serde_json::Value {
+        serde_json::json!({
+            "owner": owner, "name": "test", "description": "test",
+            "image_cid": testutil::CID, "attributes": [], "shielded": false,
+        })
+    }
+
+    #[tokio::test]
+    async fn views_run_alongside_a_transfer() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let (app, state) = app(state);
+        let signature = alice.sign(&signature::transfer_message("", &id, "bob", 1));
+        let transfer = post(
+            "/transfer",
+            serde_json::json!({
+                "id": id, "from": alice.address(), "to": "bob",
+                "nonce": 1, "signature": signature,
+            }),
+        );
+        let views: Vec<_> = (0..64)
+            .map(|_| {
+                let (app, uri) = (app.clone(), format!("/view/{}", id));
+                tokio::spawn(async move { send(&app, get(&uri)).await })
+            })
+            .collect();
+        let (status, _) = send(&app, transfer).await;
+        assert_eq!(status, StatusCode::OK);
+        for view in views {
+            let (status, body) = view.await.unwrap();
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["id"], id.as_str());
+        }
+        assert_eq!(state.read().await.ledger.get_nft(&id).unwrap().owner, "bob");
+    }
+
+    #[tokio::test]
+    async fn mint_returns_the_record_view_shows() {
+        let (app, _) = app(testutil::state().0);
+        let (status, minted) = send(&app, post("/mint", mint_body("alice"))).await;
+        assert_eq!(status, StatusCode::OK);
+        let id = minted["id"].as_str().unwrap();
+        let (_, view) = send(&app, get(&format!("/view/{}", id))).await;
+        let nft = minted["nft"].as_object().unwrap();
+        assert_eq!(nft["id"], id);
+        for (field, value) in nft {
+            assert_eq!(&view[field], value, "{} differs", field);
+        }
+    }
+
+    #[tokio::test]
+    async fn ready_flips_to_200_once_loaded() {
+        let (app, state) = app(testutil::state().0);
+        let health = Arc::new(Health::new());
+        let app = app.layer(Extension(health.clone()));
+        // As at startup, the loader holds the write lock until it is done.
+        let loading = state.write().await;
+        let (status, body) = send(&app, get("/ready")).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["ready"], false);
+        assert_eq!(send(&app, get("/health")).await.0, StatusCode::OK);
+
+        drop(loading);
+        health.set_ready();
+        let (status, body) = send(&app, get("/ready")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["ready"], true);
+    }
+
+    // Collects what a test subscriber writes.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_are_logged_with_their_id() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let routes = Router::new().route("/version", axum::routing::get(version_handler));
+        let app = with_request_logging(routes);
+        let response = app.oneshot(get("/version")).await.unwrap();
+        let request_id = response.headers()["x-request-id"].to_str().unwrap();
+        let logged = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(logged.contains("finished processing request"), "{}", logged);
+        assert!(logged.contains("path=/version"), "{}", logged);
+        assert!(logged.contains(request_id), "{}", logged);
+    }
+
+    // The only test installing the global recorder. Other tests mint too, so
+    // the counter is only checked to have grown.
+    #[tokio::test]
+    async fn metrics_count_mints() {
+        let (app, _) = app(testutil::state().0);
+        let app = app.layer(Extension(telemetry::install().unwrap()));
+        let mints = |metrics: String| {
+            metrics
+                .lines()
+                .find_map(|line| line.strip_prefix("pnft_mints_total "))
+                .map_or(0, |count| count.parse::<u64>().unwrap())
+        };
+        let before = mints(text(&app, get("/metrics")).await);
+        let (status, _) = send(&app, post("/mint", mint_body("alice"))).await;
+        assert_eq!(status, StatusCode::OK);
+        let after = mints(text(&app, get("/metrics")).await);
+        assert!(after > before, "{} -> {}", before, after);
+    }
+
+    #[tokio::test]
+    async fn websocket_streams_a_mint_event() {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let (app, _) = app(testutil::state().0);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, app.clone()).into_future());
+        // `since=0` replays the log, so a mint landing before the
+        // subscription is still delivered.
+        let url = format!("ws://{}/ws/events?since=0", addr);
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let (_, minted) = send(&app, post("/mint", mint_body("alice"))).await;
+
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), socket.next())
+            .await
+            .expect("an event frame")
+            .unwrap()
+            .unwrap();
+        let Message::Text(json) = frame else {
+            panic!("expected a text frame, got {:?}", frame);
+        };
+        let event: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(event["kind"], "mint");
+        assert_eq!(event["nft_id"], minted["id"]);
+    }
+
+    // penumbra_nft gives the value its meaning; this crate only has to pass
+    // the caller's choice through, defaulting to the historical `Some(5)`.
+    #[test]
+    fn upstream_param_is_passed_through() {
+        let item = |body: serde_json::Value| {
+            let request: MintRequest = serde_json::from_value(body).unwrap();
+            request.item.into_item()
+        };
+        let mut body = mint_body("alice");
+        assert_eq!(
+            item(body.clone()).options.upstream_param,
+            mint::DEFAULT_UPSTREAM_PARAM
+        );
+        body["upstream_param"] = 9.into();
+        assert_eq!(item(body).options.upstream_param, 9);
+    }
+
+    #[tokio::test]
+    async fn matching_if_none_match_gets_304() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let (app, _) = app(state);
+        let uri = format!("/view/{}", id);
+        let response = app.clone().oneshot(get(&uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[ETAG].clone();
+
+        let conditional = |tag| {
+            Request::get(&uri)
+                .header(axum::http::header::IF_NONE_MATCH, tag)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(conditional(etag.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[ETAG], etag);
+        let stale = axum::http::HeaderValue::from_static("W/\"stale\"");
+        let response = app.oneshot(conditional(stale)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn idempotency_key_replays_the_first_mint() {
+        let (app, state) = app(testutil::state().0);
+        let mint = |key: &str| {
+            let mut request = post("/mint", mint_body("alice"));
+            let key = axum::http::HeaderValue::from_str(key).unwrap();
+            request.headers_mut().insert("idempotency-key", key);
+            request
+        };
+        let (_, first) = send(&app, mint("one")).await;
+        let (_, again) = send(&app, mint("one")).await;
+        assert_eq!(first["id"], again["id"]);
+        assert_eq!(state.read().await.ledger.nfts.len(), 1);
+
+        let (_, other) = send(&app, mint("two")).await;
+        assert_ne!(other["id"], first["id"]);
+        assert_eq!(state.read().await.ledger.nfts.len(), 2);
+
+        let mut reused = post("/mint", mint_body("bob"));
+        let key = axum::http::HeaderValue::from_static("one");
+        reused.headers_mut().insert("idempotency-key", key);
+        let (status, _) = send(&app, reused).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(state.read().await.ledger.nfts.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn a_retry_after_a_failed_save_doesnt_mint_twice() {
+        let (app, state) = app(testutil::state().0);
+        let working = state.read().await.backend;
+        let broken = Backend::File("/nonexistent/pnft/state.json".into());
+        state.write().await.backend = Some(Box::leak(Box::new(broken)));
+        let mint = || {
+            let mut request = post("/mint", mint_body("alice"));
+            let key = axum::http::HeaderValue::from_static("retried");
+            request.headers_mut().insert("idempotency-key", key);
+            request
+        };
+
+        let (status, _) = send(&app, mint()).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        state.write().await.backend = working;
+        let (status, retried) = send(&app, mint()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(state.read().await.ledger.nfts.len(), 1);
+        assert!(state
+            .read()
+            .await
+            .ledger
+            .get_nft(retried["id"].as_str().unwrap())
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn qr_is_served_as_svg() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let (app, _) = app(state);
+        let (status, _) = send(&app, get(&format!("/nft/{}/qr", id))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let export = serde_json::json!({
+            "caller": alice.address(),
+            "nonce": 1,
+            "signature": alice.sign(&signature::action_message("", "ibc-export", &id, 1)),
+        });
+        let (_, payload) = send(&app, post(&format!("/ibc/export/{}", id), export)).await;
+        let payload = payload.as_str().unwrap();
+        let response = app.oneshot(get(&format!("/nft/{}/qr", id))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "image/svg+xml");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, qr::qr_svg(payload).unwrap().as_bytes());
+    }
+
+    #[tokio::test]
+    async fn view_decodes_the_same_as_json_and_msgpack() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let (app, _) = app(state);
+        let view = |accept: &'static str| {
+            let request = Request::get(format!("/view/{}", id))
+                .header(axum::http::header::ACCEPT, accept)
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let response = view("application/json").await.unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        let json = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+
+        let response = view(negotiate::MSGPACK).await.unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], negotiate::MSGPACK);
+        let msgpack = response.into_body().collect().await.unwrap().to_bytes();
+        let msgpack: serde_json::Value = rmp_serde::from_slice(&msgpack).unwrap();
+        assert_eq!(msgpack, json);
+        assert_eq!(json["id"], id.as_str());
+    }
+
+    #[tokio::test]
+    async fn concurrent_mints_never_pass_max_supply() {
+        let (app, state) = app(testutil::state().0);
+        let mut body = mint_body("alice");
+        body["collection"] = "apes".into();
+        body["max_supply"] = 5.into();
+        let mints: Vec<_> = (0..20)
+            .map(|_| {
+                let (app, body) = (app.clone(), body.clone());
+                tokio::spawn(async move { send(&app, post("/mint", body)).await.0 })
+            })
+            .collect();
+        let mut minted = 0;
+        for mint in mints {
+            match mint.await.unwrap() {
+                StatusCode::OK => minted += 1,
+                status => assert_eq!(status, StatusCode::CONFLICT),
+            }
+        }
+        assert_eq!(minted, 5);
+        let state = state.read().await;
+        assert_eq!(state.collections["apes"].minted, 5);
+        assert_eq!(state.ledger.nfts.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn large_lists_are_gzipped_on_request() {
+        let (mut state, _) = testutil::state();
+        for _ in 0..50 {
+            testutil::mint(&mut state, "alice");
+        }
+        let (app, _) = app(state);
+        let app = app.layer(compression::compression_layer());
+        let list = |uri: &str| {
+            let request = Request::get(uri)
+                .header(axum::http::header::ACCEPT_ENCODING, "gzip")
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let response = list("/nfts").await.unwrap();
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_ENCODING],
+            "gzip"
+        );
+        // Too small to be worth it.
+        let response = list("/health").await.unwrap();
+        assert!(!response
+            .headers()
+            .contains_key(axum::http::header::CONTENT_ENCODING));
+    }
+
+    #[tokio::test]
+    async fn version_reports_the_crate_and_schema_versions() {
+        let (app, _) = app(testutil::state().0);
+        let (status, body) = send(&app, get("/version")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(body["state_schema_version"], schema::STATE_SCHEMA_VERSION);
+        assert_eq!(body["git_commit"].as_str(), option_env!("PNFT_GIT_COMMIT"));
+    }
+
+    #[tokio::test]
+    async fn bodies_over_the_limit_get_413() {
+        let config = Cli::parse_from(["pnft-cli-rpc"]).serve;
+        let (app, state) = app(testutil::state().0);
+        let app = app.layer(DefaultBodyLimit::max(config.body_limit_bytes));
+        let mut body = mint_body("alice");
+        body["description"] = "x".repeat(config.body_limit_bytes).into();
+        let (status, _) = send(&app, post("/mint", body.clone())).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(state.read().await.ledger.nfts.is_empty());
+        // Batch routes get the larger limit.
+        let batch = serde_json::json!({ "owner": "alice", "items": [body] });
+        let (status, _) = send(&app, post("/mint/batch", batch)).await;
+        assert_ne!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn repeated_views_hit_the_cache_until_the_nft_changes() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        state.view_cache = Arc::new(ViewCache::new(16));
+        let cache = state.view_cache.clone();
+        let (app, state) = app(state);
+        let key = ViewKey {
+            id: id.clone(),
+            viewing_key: None,
+            format: Format::from_headers(&HeaderMap::new()),
+        };
+        let uri = format!("/view/{}", id);
+        let (_, first) = send(&app, get(&uri)).await;
+        assert!(cache.get(&key).is_some());
+        {
+            // A hit never waits on the state lock.
+            let _writer = state.write().await;
+            let (status, second) = send(&app, get(&uri)).await;
+            assert_eq!((status, second), (StatusCode::OK, first));
+        }
+
+        let signature = alice.sign(&signature::transfer_message("", &id, "bob", 1));
+        let transfer = serde_json::json!({
+            "id": id, "from": alice.address(), "to": "bob",
+            "nonce": 1, "signature": signature,
+        });
+        assert_eq!(
+            send(&app, post("/transfer", transfer)).await.0,
+            StatusCode::OK
+        );
+        assert!(cache.get(&key).is_none());
+        let (_, third) = send(&app, get(&uri)).await;
+        assert_eq!(third["owner"], "bob");
+    }
+
+    #[tokio::test]
+    async fn uploaded_png_is_minted_under_its_content_cid() {
+        let dir = std::env::temp_dir().join(format!("pnft-blobs-{}", std::process::id()));
+        let blobs = Arc::new(BlobStore {
+            dir: dir.clone(),
+            max_bytes: 64,
+        });
+        let (app, _) = app(testutil::state().0);
+        let app = app.layer(Extension(blobs));
+        let upload = |content_type: &str, image: &[u8]| {
+            let mut body = Vec::new();
+            for (name, value) in [
+                ("owner", "alice"),
+                (
+                    "metadata",
+                    r#"{"name":"up","description":"d","attributes":[],"shielded":false}"#,
+                ),
+            ] {
+                body.extend_from_slice(
+                    format!(
+                        "--b\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                        name, value
+                    )
+                    .as_bytes(),
+                );
+            }
+            body.extend_from_slice(
+                format!(
+                    "--b\r\nContent-Disposition: form-data; name=\"image\"; filename=\"a\"\r\nContent-Type: {}\r\n\r\n",
+                    content_type
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(image);
+            body.extend_from_slice(b"\r\n--b--\r\n");
+            Request::post("/mint/upload")
+                .header(CONTENT_TYPE, "multipart/form-data; boundary=b")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let png = b"\x89PNG\r\n\x1a\nnot really pixels";
+        let (status, minted) = send(&app, upload("image/png", png)).await;
+        assert_eq!(status, StatusCode::OK);
+        let cid = cid::raw_cid(png);
+        assert_eq!(minted["nft"]["metadata"]["image_cid"], cid.as_str());
+        assert_eq!(std::fs::read(dir.join(&cid)).unwrap(), png);
+
+        let (status, _) = send(&app, upload("text/plain", png)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(
+            &app,
+            upload("image/png", &[png.as_slice(), &[0; 64]].concat()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn openapi_describes_mint() {
+        let (app, _) = app(testutil::state().0);
+        let (status, doc) = send(&app, get("/openapi.json")).await;
+        assert_eq!(status, StatusCode::OK);
+        let doc: utoipa::openapi::OpenApi = serde_json::from_value(doc).unwrap();
+        let mint = doc.paths.paths["/mint"].operations[&utoipa::openapi::PathItemType::Post]
+            .request_body
+            .as_ref()
+            .unwrap();
+        let schema = &mint.content["application/json"].schema;
+        assert!(
+            matches!(schema, utoipa::openapi::RefOr::Ref(r) if r.ref_location.ends_with("/MintRequest"))
+        );
+        let schemas = &doc.components.unwrap().schemas;
+        assert!(schemas.contains_key("MintRequest"));
+        assert!(schemas.contains_key("MintResponse"));
+    }
+
+    #[tokio::test]
+    async fn image_redirects_to_the_gateway() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let (app, _) = app(state);
+        let gateway = Gateway::new("https://gateway.example/ipfs/", false).unwrap();
+        let app = app.layer(Extension(Arc::new(gateway)));
+        let response = app
+            .clone()
+            .oneshot(get(&format!("/nft/{}/image", id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(
+            response.headers()[axum::http::header::LOCATION],
+            format!("https://gateway.example/ipfs/{}", testutil::CID).as_str()
+        );
+        let (status, _) = send(&app, get("/nft/missing/image")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn a_panic_under_the_lock_leaves_later_requests_working() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let (app, state) = app(state);
+        async fn boom(state: axum::extract::State<SharedState>) -> StatusCode {
+            let _writer = state.write().await;
+            panic!("boom")
+        }
+        let boom = Router::new()
+            .route("/boom", axum::routing::get(boom))
+            .with_state(state);
+        let app = app
+            .merge(boom)
+            .layer(CatchPanicLayer::custom(error::panic_response));
+        let (status, body) = send(&app, get("/boom")).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["code"], "internal_error");
+        let (status, view) = send(&app, get(&format!("/view/{}", id))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(view["id"], id.as_str());
+        let (status, _) = send(&app, post("/mint", mint_body("bob"))).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn admin_force_unstake_bypasses_the_lockup() {
+        let (mut state, _) = testutil::state();
+        state.stake_lockup_secs = 3600;
+        let id = testutil::mint(&mut state, "alice");
+        staking::stake_nft(&mut state, &id).unwrap();
+        let (app, state) = app_with_key(state, Some("secret"));
+        let force = |token: Option<&str>| {
+            let mut request = Request::post(format!("/admin/force-unstake/{}", id));
+            if let Some(token) = token {
+                request = request.header(
+                    axum::http::header::AUTHORIZATION,
+                    format!("Bearer {}", token),
+                );
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        for token in [None, Some("wrong")] {
+            assert_eq!(send(&app, force(token)).await.0, StatusCode::UNAUTHORIZED);
+        }
+        assert!(staking::is_staked(&*state.read().await, &id));
+
+        let (status, body) = send(&app, force(Some("secret"))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "unstaked");
+        let state = state.read().await;
+        assert!(!staking::is_staked(&state, &id));
+        let last = state.events.since(0).last().unwrap();
+        assert_eq!(
+            (last.kind, last.nft_id.as_str()),
+            (EventKind::ForceUnstake, id.as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn ids_are_found_despite_whitespace_and_case() {
+        let (app, _) = app(testutil::state().0);
+        let (_, minted) = send(&app, post("/mint", mint_body("alice"))).await;
+        let id = minted["id"].as_str().unwrap();
+        let messy = format!("%20{}%20", id.to_ascii_uppercase());
+        let (status, view) = send(&app, get(&format!("/view/{}", messy))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(view["id"], id);
+        let request = serde_json::json!({ "ids": [format!(" {} ", id.to_ascii_uppercase())] });
+        let (_, views) = send(&app, post("/view/batch", request)).await;
+        assert_eq!(views[id]["id"], id);
+    }
+
+    #[tokio::test]
+    async fn reveal_all_needs_the_admin_key_and_shows_shielded_metadata() {
+        let (mut state, _) = testutil::state();
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        item.metadata.description = "the secret".to_string();
+        let id = mint_nft(&mut state, "alice".to_string(), item).unwrap();
+        let (open, _) = app(testutil::state().0);
+        let (app, state) = app_with_key(state, Some("secret"));
+        let reveal_all = |token: Option<&str>| {
+            let mut request = Request::get("/admin/reveal-all");
+            if let Some(token) = token {
+                request = request.header(
+                    axum::http::header::AUTHORIZATION,
+                    format!("Bearer {}", token),
+                );
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        for token in [None, Some("wrong")] {
+            assert_eq!(
+                send(&app, reveal_all(token)).await.0,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        // Closed outright when no admin key is configured.
+        assert_eq!(
+            send(&open, reveal_all(None)).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert!(state.read().await.reveal_log.records(&id).is_empty());
+
+        let (status, page) = send(&app, reveal_all(Some("secret"))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["items"][0]["metadata"]["description"], "the secret");
+        let records = state.read().await.reveal_log.records(&id);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].key_fingerprint, reveallog::ADMIN_FINGERPRINT);
+    }
+
+    #[tokio::test]
+    async fn tenants_never_see_each_others_nfts() {
+        let (default, _) = app(testutil::state().0);
+        let (acme, acme_state) = app_with_key(testutil::state().0, Some("acme-key"));
+        let app = Router::new().merge(default).nest("/t/acme", acme);
+        let mint = |uri: &str, owner: &str, token: Option<&str>| {
+            let mut request = Request::post(uri).header(CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                request = request.header(
+                    axum::http::header::AUTHORIZATION,
+                    format!("Bearer {}", token),
+                );
+            }
+            request
+                .body(Body::from(mint_body(owner).to_string()))
+                .unwrap()
+        };
+
+        let (status, _) = send(&app, mint("/t/acme/mint", "alice", None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, minted) = send(&app, mint("/t/acme/mint", "alice", Some("acme-key"))).await;
+        assert_eq!(status, StatusCode::OK);
+        let acme_id = minted["id"].as_str().unwrap().to_string();
+        let (_, minted) = send(&app, mint("/mint", "bob", None)).await;
+        let default_id = minted["id"].as_str().unwrap().to_string();
+
+        let (_, view) = send(&app, get(&format!("/t/acme/view/{}", acme_id))).await;
+        assert_eq!(view["owner"], "alice");
+        let (_, view) = send(&app, get(&format!("/view/{}", acme_id))).await;
+        assert!(view.is_null());
+        let (_, view) = send(&app, get(&format!("/t/acme/view/{}", default_id))).await;
+        assert!(view.is_null());
+        let (_, list) = send(&app, get("/t/acme/nfts")).await;
+        assert_eq!(list["total"], 1);
+        assert_eq!(acme_state.read().await.ledger.nfts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn staking_someone_elses_nft_is_forbidden() {
+        let (mut state, _) = testutil::state();
+        let owner = Key::new(1);
+        let stranger = Key::new(2);
+        let id = testutil::mint(&mut state, &owner.address());
+        let (app, state) = app(state);
+        let signed = |key: &Key, action: &str, nonce: u64| {
+            serde_json::json!({
+                "caller": key.address(),
+                "nonce": nonce,
+                "signature": key.sign(&signature::action_message("", action, &id, nonce)),
+            })
+        };
+
+        let uri = format!("/stake/{}", id);
+        let (status, _) = send(&app, post(&uri, signed(&stranger, "stake", 1))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(!staking::is_staked(&*state.read().await, &id));
+        let (status, _) = send(&app, post(&uri, signed(&owner, "stake", 1))).await;
+        assert_eq!(status, StatusCode::OK);
+        for action in ["claim", "unstake"] {
+            let uri = format!("/{}/{}", action, id);
+            let (status, _) = send(&app, post(&uri, signed(&stranger, action, 1))).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+        assert!(staking::is_staked(&*state.read().await, &id));
+    }
+
+    #[tokio::test]
+    async fn backup_overwrite_needs_the_admin_key() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let (app, state) = app(state);
+        let (_, mut backup) = send(&app, get(&format!("/nft/{}/export", id))).await;
+        backup["nfts"][0]["nft"]["owner"] = "mallory".into();
+
+        let (status, _) = send(&app, post("/import?overwrite=true", backup)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let state = state.read().await;
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, "alice");
+    }
+
+    #[tokio::test]
+    async fn airdropping_an_existing_nft_needs_the_admin_key() {
+        let cases = [
+            (None, StatusCode::UNAUTHORIZED, "alice"),
+            (Some("secret"), StatusCode::OK, "mallory"),
+        ];
+        for (admin_key, expected, owner) in cases {
+            let (mut state, _) = testutil::state();
+            let id = testutil::mint(&mut state, "alice");
+            let (app, state) = app_with_key(state, admin_key);
+            let body = serde_json::json!({ "id": id, "recipients": ["mallory"] });
+            let mut request = Request::post("/airdrop").header(CONTENT_TYPE, "application/json");
+            if let Some(key) = admin_key {
+                request =
+                    request.header(axum::http::header::AUTHORIZATION, format!("Bearer {}", key));
+            }
+            let request = request.body(Body::from(body.to_string())).unwrap();
+            assert_eq!(send(&app, request).await.0, expected);
+            assert_eq!(state.read().await.ledger.get_nft(&id).unwrap().owner, owner);
+        }
+    }
+
+    #[tokio::test]
+    async fn shielded_qr_needs_a_viewing_key() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        let id = mint::mint_nft(&mut state, alice.address(), item).unwrap();
+        let signature = alice.sign(&signature::action_message("", "ibc-export", &id, 1));
+        export_nft(&mut state, &id, &alice.address(), 1, &signature).unwrap();
+        let (app, _) = app(state);
+
+        let (status, _) = send(&app, get(&format!("/nft/{}/qr", id))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let uri = format!("/nft/{}/qr?viewing_key=viewing-key", id);
+        let response = app.oneshot(get(&uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn burned_nft_has_no_qr_code() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = alice.sign(&signature::action_message("", "ibc-export", &id, 1));
+        export_nft(&mut state, &id, &alice.address(), 1, &signature).unwrap();
+        // However it came to be tombstoned while exported.
+        state.extras_mut(&id).status = extras::NftStatus::Burned;
+        let (app, _) = app(state);
+        let (status, body) = send(&app, get(&format!("/nft/{}/qr", id))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body["message"].as_str().unwrap().contains("burned"));
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{
+    app::AppState,
+    attributes::{encode_attributes, Attributes},
+    burn::ensure_not_burned,
+    cid::{strip_scheme, validate_cid},
+    error::NftError,
+    events::EventKind,
+    extras::CidChange,
+    nonce::{accept_nonce, check_nonce},
+    signature::{metadata_patch_message, repin_message, verify_signature, Auth},
+    traitschema::check_attributes,
+};
+
+// Fields the owner may change after mint. `image_cid` is deliberately absent;
+// see `repin_image`.
+#[derive(serde::Deserialize)]
+pub struct MetadataPatch {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub attributes: Option<Attributes>,
+}
+
+// `caller` must be the owner, signing `metadata_patch_message`.
+pub fn update_metadata(
+    state: &mut AppState,
+    id: &str,
+    patch: MetadataPatch,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    let owner = state
+        .ledger
+        .get_nft(id)
+        .map(|nft| nft.owner.clone())
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if owner != caller {
+        return Err(NftError::Forbidden(format!(
+            "only the owner of NFT {} can update it",
+            id
+        )));
+    }
+    let attributes = patch.attributes.map(Attributes::into_vec);
+    let encoded = attributes.as_deref().map(encode_attributes);
+    let message = metadata_patch_message(
+        &state.tenant,
+        id,
+        patch.name.as_deref(),
+        patch.description.as_deref(),
+        encoded.as_deref(),
+        nonce,
+    );
+    verify_signature(caller, &message, signature)?;
+    check_nonce(state, caller, nonce)?;
+    if let Some(attributes) = &attributes {
+        let collection = state.extras.get(id).and_then(|e| e.collection.as_deref());
+        check_attributes(state, collection, attributes)?;
+    }
+    let mut metadata = state
+        .ledger
+        .get_nft(id)
+        .expect("checked above")
+        .metadata
+        .clone();
+    if let Some(name) = patch.name {
+        metadata.name = name;
+    }
+    if let Some(description) = patch.description {
+        metadata.description = description;
+    }
+    if let Some(encoded) = encoded {
+        metadata.attributes = encoded;
+    }
+    state.field_limits.check(&metadata)?;
+    state
+        .ledger
+        .nfts
+        .get_mut(id)
+        .expect("checked above")
+        .metadata = metadata;
+    accept_nonce(state, caller, nonce);
+    state.record(EventKind::MetadataUpdate, id, Some(caller), None);
+    Ok(())
+}
+
+// Points the NFT at a re-pinned copy of its image. Only the owner, signing
+// `repin_message` over the CID without any `ipfs://` prefix, or an admin may
+// do this; the CID being replaced is kept for provenance.
+pub fn repin_image(
+    state: &mut AppState,
+    id: &str,
+    new_cid: &str,
+    caller: &str,
+    auth: Auth,
+) -> Result<(), NftError> {
+    validate_cid(new_cid)?;
+    ensure_not_burned(state, id)?;
+    let new_cid = strip_scheme(new_cid.trim()).to_string();
+    let now = state.now();
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if let Auth::Owner { nonce, signature } = auth {
+        if nft.owner != caller {
+            return Err(NftError::Forbidden(format!(
+                "only the owner of NFT {} can repin it",
+                id
+            )));
+        }
+        verify_signature(
+            caller,
+            &repin_message(&state.tenant, id, &new_cid, nonce),
+            signature,
+        )?;
+        check_nonce(state, caller, nonce)?;
+    }
+    if nft.metadata.image_cid == new_cid {
+        return Err(NftError::Invalid(format!(
+            "NFT {} already uses image {}",
+            id, new_cid
+        )));
+    }
+    if let Auth::Owner { nonce, .. } = auth {
+        accept_nonce(state, caller, nonce);
+    }
+    let nft = state.ledger.nfts.get_mut(id).expect("checked above");
+    let previous = std::mem::replace(&mut nft.metadata.image_cid, new_cid);
+    state.extras_mut(id).image_cid_history.push(CidChange {
+        cid: previous,
+        replaced_at: now,
+    });
+    state.record(EventKind::Repin, id, Some(caller), None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::{decode_attributes, Attribute, AttributeValue},
+        testutil::{self, Key, CID},
+    };
+
+    fn patch(name: Option<&str>, attributes: Option<Vec<Attribute>>) -> MetadataPatch {
+        MetadataPatch {
+            name: name.map(str::to_string),
+            description: None,
+            attributes: attributes.map(Attributes::Structured),
+        }
+    }
+
+    fn sign_patch(
+        key: &Key,
+        id: &str,
+        name: Option<&str>,
+        attributes: Option<&str>,
+        nonce: u64,
+    ) -> String {
+        key.sign(&metadata_patch_message(
+            "", id, name, None, attributes, nonce,
+        ))
+    }
+
+    #[test]
+    fn partial_update_changes_only_the_given_fields() {
+        let mut state = AppState::new();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = sign_patch(&alice, &id, Some("renamed"), None, 1);
+        update_metadata(
+            &mut state,
+            &id,
+            patch(Some("renamed"), None),
+            &alice.address(),
+            1,
+            &signature,
+        )
+        .unwrap();
+        let metadata = &state.ledger.get_nft(&id).unwrap().metadata;
+        assert_eq!(metadata.name, "renamed");
+        assert_eq!(metadata.description, "test");
+        assert_eq!(metadata.image_cid, CID);
+    }
+
+    #[test]
+    fn attributes_are_signed_in_their_stored_form() {
+        let mut state = AppState::new();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let attributes = vec![Attribute {
+            trait_type: "eyes".into(),
+            value: AttributeValue::String("green".into()),
+        }];
+        let encoded = encode_attributes(&attributes);
+        let signature = sign_patch(&alice, &id, None, Some(&encoded), 1);
+        update_metadata(
+            &mut state,
+            &id,
+            patch(None, Some(attributes.clone())),
+            &alice.address(),
+            1,
+            &signature,
+        )
+        .unwrap();
+        let stored = &state.ledger.get_nft(&id).unwrap().metadata.attributes;
+        assert_eq!(decode_attributes(stored), attributes);
+    }
+
+    #[test]
+    fn update_needs_the_owner_signature() {
+        let mut state = AppState::new();
+        let alice = Key::new(1);
+        let mallory = Key::new(2);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = sign_patch(&mallory, &id, Some("mine"), None, 1);
+        let err = update_metadata(
+            &mut state,
+            &id,
+            patch(Some("mine"), None),
+            &mallory.address(),
+            1,
+            &signature,
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        let err = update_metadata(
+            &mut state,
+            &id,
+            patch(Some("mine"), None),
+            &alice.address(),
+            1,
+            &signature,
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        // A signature over one patch doesn't authorize another.
+        let signature = sign_patch(&alice, &id, Some("fine"), None, 1);
+        let err = update_metadata(
+            &mut state,
+            &id,
+            patch(Some("mine"), None),
+            &alice.address(),
+            1,
+            &signature,
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(state.ledger.get_nft(&id).unwrap().metadata.name, "test");
+    }
+
+    const NEW_CID: &str = "bafkreigh2akiscaildcqabsyg3dfr6chu3fgpregiymsck7e7aqa4s52zy";
+
+    fn owner_auth(signature: &str, nonce: u64) -> Auth<'_> {
+        Auth::Owner { nonce, signature }
+    }
+
+    #[test]
+    fn repin_records_the_replaced_cid() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = alice.sign(&repin_message("", &id, NEW_CID, 1));
+        let uri = format!("ipfs://{}", NEW_CID);
+        repin_image(
+            &mut state,
+            &id,
+            &uri,
+            &alice.address(),
+            owner_auth(&signature, 1),
+        )
+        .unwrap();
+        assert_eq!(
+            state.ledger.get_nft(&id).unwrap().metadata.image_cid,
+            NEW_CID
+        );
+        let history = &state.extras[&id].image_cid_history;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].cid, CID);
+        assert_eq!(history[0].replaced_at, testutil::START);
+    }
+
+    #[test]
+    fn repin_is_owner_or_admin_only() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let mallory = Key::new(2);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = mallory.sign(&repin_message("", &id, NEW_CID, 1));
+        let err = repin_image(
+            &mut state,
+            &id,
+            NEW_CID,
+            &mallory.address(),
+            owner_auth(&signature, 1),
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        let err = repin_image(
+            &mut state,
+            &id,
+            NEW_CID,
+            &alice.address(),
+            owner_auth(&signature, 1),
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(state.ledger.get_nft(&id).unwrap().metadata.image_cid, CID);
+        repin_image(&mut state, &id, NEW_CID, "admin", Auth::Admin).unwrap();
+        assert_eq!(
+            state.ledger.get_nft(&id).unwrap().metadata.image_cid,
+            NEW_CID
+        );
+    }
+
+    #[test]
+    fn repin_validates_the_new_cid() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let err = repin_image(&mut state, &id, "not-a-cid", "admin", Auth::Admin);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        let err = repin_image(&mut state, &id, CID, "admin", Auth::Admin);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        assert!(state.extras[&id].image_cid_history.is_empty());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{
+    app::AppState,
+    attributes::decode_attributes,
+    balance::{charge_mint_fee, check_mint_fees},
+    cid::{strip_scheme, validate_cid},
+    collections::{check_supply, CollectionInfo},
+    error::NftError,
+    events::EventKind,
+    extras::{NftExtras, OwnershipRecord},
+    ids::{ensure_unused, normalize_id, planned_id, rekey},
+    royalty::validate_royalty,
+    telemetry,
+    traitschema::check_attributes,
+};
+use penumbra_nft::{mint, types::NFTMetadata};
+
+pub const DEFAULT_UPSTREAM_PARAM: u32 = 5;
+
+// penumbra_nft's `mint_nft` takes a trailing `Option<u32>` whose meaning is
+// defined by that crate, not this one. This server always passed `Some(5)`,
+// which stays the default; callers can now choose it per mint.
+#[derive(Clone, Copy, Debug)]
+pub struct MintOptions {
+    pub upstream_param: u32,
+}
+
+impl Default for MintOptions {
+    fn default() -> Self {
+        MintOptions {
+            upstream_param: DEFAULT_UPSTREAM_PARAM,
+        }
+    }
+}
+
+// Everything needed to mint one NFT: the upstream metadata plus the
+// fields this crate tracks for it.
+#[derive(Clone)]
+pub struct MintItem {
+    pub metadata: NFTMetadata,
+    pub extras: NftExtras,
+    // Only honoured by the mint that creates the collection.
+    pub max_supply: Option<u32>,
+    // Likewise only honoured by the mint that creates the collection.
+    pub transfer_cooldown_secs: Option<u64>,
+    pub options: MintOptions,
+}
+
+// Checks that run before anything is minted.
+pub fn validate_item(item: &MintItem) -> Result<(), NftError> {
+    validate_cid(&item.metadata.image_cid)?;
+    match &item.extras.collection {
+        Some(collection) if collection.trim().is_empty() => {
+            return Err(NftError::Invalid(
+                "collection name must not be empty".into(),
+            ));
+        }
+        None if item.max_supply.is_some() => {
+            return Err(NftError::Invalid("max_supply requires a collection".into()));
+        }
+        None if item.transfer_cooldown_secs.is_some() => {
+            return Err(NftError::Invalid(
+                "transfer_cooldown_secs requires a collection".into(),
+            ));
+        }
+        _ => {}
+    }
+    if let Some(royalty) = &item.extras.royalty {
+        validate_royalty(royalty)?;
+    }
+    Ok(())
+}
+
+// Everything `mint_nft` checks before minting `item` to `owner`, and all a
+// dry run checks, so the two can't disagree. Returns the id the mint will
+// get when the id scheme fixes it in advance.
+pub fn check_mint(
+    state: &AppState,
+    owner: &str,
+    item: &MintItem,
+) -> Result<Option<String>, NftError> {
+    check_mint_paid_by(state, owner, owner, item)
+}
+
+// `check_mint` for a mint whose fee `payer` covers.
+pub fn check_mint_paid_by(
+    state: &AppState,
+    owner: &str,
+    payer: &str,
+    item: &MintItem,
+) -> Result<Option<String>, NftError> {
+    check_template(state, item)?;
+    if let Some(collection) = &item.extras.collection {
+        check_supply(state, collection, item.max_supply, 1)?;
+    }
+    check_mint_fees(state, [payer])?;
+    let mut metadata = item.metadata.clone();
+    metadata.image_cid = strip_scheme(metadata.image_cid.trim()).to_string();
+    let planned = planned_id(state, owner, &metadata, item.extras.collection.as_deref());
+    if let Some(planned) = &planned {
+        ensure_unused(state, planned)?;
+    }
+    Ok(planned)
+}
+
+// `check_mint` short of collection supply, which callers minting many copies
+// check for all of them at once.
+pub fn check_template(state: &AppState, item: &MintItem) -> Result<(), NftError> {
+    validate_item(item)?;
+    state.field_limits.check(&item.metadata)?;
+    if item.extras.expires_at.is_some_and(|at| at <= state.now()) {
+        return Err(NftError::Invalid("expires_at must be in the future".into()));
+    }
+    if let Some(collection) = &item.extras.collection {
+        check_cooldown(state, collection, item.transfer_cooldown_secs)?;
+        let attrs = decode_attributes(&item.metadata.attributes);
+        check_attributes(state, Some(collection), &attrs)?;
+    }
+    Ok(())
+}
+
+// Like max_supply, a collection's cooldown is fixed by the mint that creates it.
+fn check_cooldown(state: &AppState, name: &str, cooldown: Option<u64>) -> Result<(), NftError> {
+    match state.collections.get(name) {
+        Some(info) if cooldown.is_some() && cooldown != info.transfer_cooldown_secs => {
+            Err(NftError::Invalid(format!(
+                "transfer_cooldown_secs can only be set by the first mint into collection {}",
+                name
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn mint_nft(state: &mut AppState, owner: String, item: MintItem) -> Result<String, NftError> {
+    let payer = owner.clone();
+    mint_nft_paid_by(state, owner, &payer, item)
+}
+
+// `mint_nft`, charging the mint fee to `payer` rather than the new owner.
+pub fn mint_nft_paid_by(
+    state: &mut AppState,
+    owner: String,
+    payer: &str,
+    item: MintItem,
+) -> Result<String, NftError> {
+    let planned = check_mint_paid_by(state, &owner, payer, &item)?;
+    let MintItem {
+        mut metadata,
+        mut extras,
+        max_supply,
+        transfer_cooldown_secs,
+        options,
+    } = item;
+    metadata.image_cid = strip_scheme(metadata.image_cid.trim()).to_string();
+    if let Some(collection) = &extras.collection {
+        let info = state
+            .collections
+            .entry(collection.clone())
+            .or_insert_with(|| CollectionInfo {
+                max_supply,
+                minted: 0,
+                transfer_cooldown_secs,
+            });
+        info.minted += 1;
+        // Serials come from the running count under the write lock, so
+        // concurrent mints can't share one. Burns never reuse a serial.
+        if let Some(cap) = info.max_supply {
+            extras.edition = Some(info.minted);
+            extras.edition_of = Some(cap);
+        }
+    }
+    extras.minted_at = Some(state.now());
+    extras.owner_history = vec![OwnershipRecord {
+        owner: owner.clone(),
+        acquired_at: Some(state.now()),
+    }];
+    let mut id = mint::mint_nft(
+        &mut state.ledger,
+        owner.clone(),
+        metadata,
+        Some(options.upstream_param),
+    );
+    // Whatever penumbra_nft assigned is stored normalized, as lookups expect.
+    let target = planned.unwrap_or_else(|| normalize_id(&id));
+    if target != id {
+        rekey(state, &id, &target);
+        id = target;
+    }
+    state.extras.insert(id.clone(), extras);
+    state.owner_index.insert(&owner, &id);
+    charge_mint_fee(state, payer);
+    state.record(EventKind::Mint, &id, None, Some(&owner));
+    metrics::counter!(telemetry::MINTS).increment(1);
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        burn::burn_nft,
+        config::IdScheme,
+        reveal::{reveal_view, NftView},
+        testutil,
+    };
+
+    #[test]
+    fn capped_collection_mints_get_sequential_serials() {
+        let (mut state, _) = testutil::state();
+        let mut item = testutil::item("numbered");
+        item.extras.collection = Some("apes".to_string());
+        item.max_supply = Some(10);
+        let mint =
+            |state: &mut AppState| mint_nft(state, "alice".to_string(), item.clone()).unwrap();
+        let ids: Vec<String> = (0..4).map(|_| mint(&mut state)).collect();
+        let editions: Vec<Option<u32>> = ids.iter().map(|id| state.extras[id].edition).collect();
+        assert_eq!(editions, [Some(1), Some(2), Some(3), Some(4)]);
+        assert_eq!(
+            state.extras[&ids[0]].edition_label().as_deref(),
+            Some("#1 of 10")
+        );
+
+        // Burning never frees a serial for reuse.
+        burn_nft(&mut state, &ids[3]).unwrap();
+        let next = mint(&mut state);
+        assert_eq!(state.extras[&next].edition, Some(5));
+    }
+
+    #[test]
+    fn shielded_flag_is_stored_and_decides_the_reveal() {
+        let (mut state, _) = testutil::state();
+        let public = testutil::mint(&mut state, "alice");
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        let shielded = mint_nft(&mut state, "alice".to_string(), item).unwrap();
+
+        assert!(!state.ledger.get_nft(&public).unwrap().metadata.shielded);
+        assert!(state.ledger.get_nft(&shielded).unwrap().metadata.shielded);
+        assert!(matches!(
+            reveal_view(&state, &public, None),
+            Some(NftView::Full { .. })
+        ));
+        assert!(matches!(
+            reveal_view(&state, &shielded, None),
+            Some(NftView::Redacted { .. })
+        ));
+    }
+
+    #[test]
+    fn dry_run_checks_a_taken_content_id() {
+        let (mut state, _) = testutil::state();
+        state.id_scheme = IdScheme::ContentHash;
+        let id = mint_nft(&mut state, "alice".to_string(), testutil::item("one")).unwrap();
+        let planned = check_mint(&state, "bob", &testutil::item("one")).unwrap();
+        assert!(planned.is_some_and(|planned| planned != id));
+        let err = check_mint(&state, "alice", &testutil::item("one"));
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+    }
+
+    #[test]
+    fn dry_run_checks_the_mint_fee() {
+        let (mut state, _) = testutil::state();
+        state.mint_fee = 5;
+        let err = check_mint(&state, "alice", &testutil::item("one"));
+        assert!(matches!(err, Err(NftError::PaymentRequired(_))));
+        state.balances.insert("alice".to_string(), 5);
+        assert_eq!(
+            check_mint(&state, "alice", &testutil::item("one")).unwrap(),
+            None
+        );
+        assert!(state.ledger.nfts.is_empty());
+        assert_eq!(state.balances["alice"], 5);
+    }
+}
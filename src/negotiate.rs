@@ -0,0 +1,73 @@
+use axum::{
+    body::Body,
+    http::{
+        header::{ACCEPT, CONTENT_TYPE, VARY},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+pub const MSGPACK: &str = "application/msgpack";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    Json,
+    MessagePack,
+}
+
+impl Format {
+    // The first of JSON or MessagePack the Accept header lists. JSON unless
+    // MessagePack is named before it; `*/*` and no header mean JSON.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let accept = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','));
+        for media in accept {
+            let media = media.split(';').next().unwrap_or("").trim();
+            match media {
+                MSGPACK | "application/x-msgpack" => return Format::MessagePack,
+                "application/json" | "*/*" => return Format::Json,
+                _ => {}
+            }
+        }
+        Format::Json
+    }
+
+    pub fn render<T: Serialize>(self, value: &T) -> Response {
+        match self.encode(value) {
+            Ok(body) => self.respond(body),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err).into_response(),
+        }
+    }
+
+    // Encodes `value` with field names kept, so both encodings decode to the
+    // same structure.
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Format::Json => {
+                serde_json::to_vec(value).map_err(|err| format!("failed to encode JSON: {}", err))
+            }
+            Format::MessagePack => rmp_serde::to_vec_named(value)
+                .map_err(|err| format!("failed to encode MessagePack: {}", err)),
+        }
+    }
+
+    // A 200 carrying a body already encoded in this format.
+    pub fn respond(self, body: impl Into<Body>) -> Response {
+        let content_type = match self {
+            Format::Json => "application/json",
+            Format::MessagePack => MSGPACK,
+        };
+        (
+            [
+                (CONTENT_TYPE, HeaderValue::from_static(content_type)),
+                (VARY, HeaderValue::from_static("accept")),
+            ],
+            body.into(),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{app::AppState, error::NftError};
+
+// The nonce a signed request from `owner` must use next. `check_nonce` never
+// accepts u64::MAX, so there always is one.
+pub fn next_nonce(state: &AppState, owner: &str) -> u64 {
+    state
+        .nonces
+        .get(owner)
+        .map_or(1, |last| last.saturating_add(1))
+}
+
+// Rejects `nonce` unless it is strictly greater than every nonce accepted
+// from `owner` before. u64::MAX is refused outright: accepting it would leave
+// the owner no nonce to sign with next.
+pub fn check_nonce(state: &AppState, owner: &str, nonce: u64) -> Result<(), NftError> {
+    if nonce == u64::MAX {
+        return Err(NftError::Invalid(format!(
+            "nonce must be below {}",
+            u64::MAX
+        )));
+    }
+    match state.nonces.get(owner) {
+        Some(&last) if nonce <= last => Err(NftError::Invalid(format!(
+            "nonce {} must be greater than the last used nonce {} for {}",
+            nonce, last, owner
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Call once the signed request has succeeded.
+pub fn accept_nonce(state: &mut AppState, owner: &str, nonce: u64) {
+    state.nonces.insert(owner.to_string(), nonce);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    #[test]
+    fn nonces_start_at_one_and_must_increase() {
+        let (mut state, _) = testutil::state();
+        assert_eq!(next_nonce(&state, "alice"), 1);
+        check_nonce(&state, "alice", 1).unwrap();
+        accept_nonce(&mut state, "alice", 5);
+        assert_eq!(next_nonce(&state, "alice"), 6);
+        assert!(check_nonce(&state, "alice", 5).is_err());
+        assert!(check_nonce(&state, "alice", 4).is_err());
+        check_nonce(&state, "alice", 6).unwrap();
+        // Other owners count separately.
+        check_nonce(&state, "bob", 1).unwrap();
+    }
+
+    #[test]
+    fn max_nonce_is_refused() {
+        let (mut state, _) = testutil::state();
+        assert!(check_nonce(&state, "alice", u64::MAX).is_err());
+        accept_nonce(&mut state, "alice", u64::MAX - 1);
+        assert_eq!(next_nonce(&state, "alice"), u64::MAX);
+        assert!(check_nonce(&state, "alice", u64::MAX).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{
+    app::AppState,
+    burn::ensure_not_burned,
+    error::NftError,
+    events::EventKind,
+    expiry::ensure_not_expired,
+    freeze::ensure_not_frozen,
+    ibc::ensure_not_exported,
+    nonce::{accept_nonce, check_nonce},
+    shares::ensure_not_shared,
+    signature::{action_message, lock_offer_message, verify_signature},
+    transfer::transfer_nft,
+};
+use serde::{Deserialize, Serialize};
+
+// Escrow for a live marketplace offer: until `expires_at` the NFT can only
+// move to `buyer`. An expired lock is simply ignored.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OfferLock {
+    pub buyer: String,
+    pub expires_at: u64,
+}
+
+// The lock on `id`, if it hasn't expired yet.
+pub fn active_offer<'a>(state: &'a AppState, id: &str) -> Option<&'a OfferLock> {
+    let now = state.now();
+    state
+        .extras
+        .get(id)?
+        .offer
+        .as_ref()
+        .filter(|lock| lock.expires_at > now)
+}
+
+// Checked by every path that moves an NFT to a new owner.
+pub fn ensure_offer_allows(state: &AppState, id: &str, to: &str) -> Result<(), NftError> {
+    match active_offer(state, id) {
+        Some(lock) if lock.buyer != to => Err(NftError::Locked(format!(
+            "NFT {} is locked for an offer to {} until {}",
+            id, lock.buyer, lock.expires_at
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// `caller` must be the owner, signing `lock_offer_message`.
+pub fn lock_for_offer(
+    state: &mut AppState,
+    id: &str,
+    buyer: &str,
+    expires_at: u64,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<OfferLock, NftError> {
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if nft.owner != caller {
+        return Err(NftError::Forbidden(format!(
+            "only the owner of NFT {} can lock it for an offer",
+            id
+        )));
+    }
+    if buyer == caller {
+        return Err(NftError::Invalid("the buyer must not be the owner".into()));
+    }
+    if expires_at <= state.now() {
+        return Err(NftError::Invalid("expires_at must be in the future".into()));
+    }
+    ensure_not_burned(state, id)?;
+    ensure_not_frozen(state, id)?;
+    ensure_not_exported(state, id)?;
+    ensure_not_shared(state, id)?;
+    ensure_not_expired(state, id)?;
+    if let Some(lock) = active_offer(state, id) {
+        return Err(NftError::Locked(format!(
+            "NFT {} is already locked for an offer to {} until {}",
+            id, lock.buyer, lock.expires_at
+        )));
+    }
+    let message = lock_offer_message(&state.tenant, id, buyer, expires_at, nonce);
+    verify_signature(caller, &message, signature)?;
+    check_nonce(state, caller, nonce)?;
+    accept_nonce(state, caller, nonce);
+    let lock = OfferLock {
+        buyer: buyer.to_string(),
+        expires_at,
+    };
+    state.extras_mut(id).offer = Some(lock.clone());
+    state.record(EventKind::OfferLock, id, Some(caller), Some(buyer));
+    Ok(lock)
+}
+
+// The named buyer takes the NFT, signing `action_message("accept-offer")`.
+// Releasing the lock is part of the transfer.
+pub fn accept_offer(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    let lock = active_offer(state, id)
+        .ok_or_else(|| NftError::Invalid(format!("NFT {} has no live offer", id)))?;
+    if lock.buyer != caller {
+        return Err(NftError::Forbidden(format!(
+            "{} is not authorized to accept the offer on NFT {}",
+            caller, id
+        )));
+    }
+    verify_signature(
+        caller,
+        &action_message(&state.tenant, "accept-offer", id, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    transfer_nft(state, id, caller)?;
+    accept_nonce(state, caller, nonce);
+    Ok(())
+}
+
+// Either side may walk away from a live offer, signing
+// `action_message("cancel-offer")`.
+pub fn cancel_offer(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    let owner = state
+        .ledger
+        .get_nft(id)
+        .map(|nft| nft.owner.clone())
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    let lock = active_offer(state, id)
+        .ok_or_else(|| NftError::Invalid(format!("NFT {} has no live offer", id)))?;
+    if caller != owner && caller != lock.buyer {
+        return Err(NftError::Forbidden(format!(
+            "only the owner or buyer can cancel the offer on NFT {}",
+            id
+        )));
+    }
+    verify_signature(
+        caller,
+        &action_message(&state.tenant, "cancel-offer", id, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    accept_nonce(state, caller, nonce);
+    state.extras_mut(id).offer = None;
+    state.record(EventKind::OfferCancel, id, Some(caller), None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, Key, START};
+
+    struct Offer {
+        id: String,
+        owner: Key,
+        buyer: Key,
+    }
+
+    fn locked(state: &mut AppState) -> Offer {
+        let owner = Key::new(1);
+        let buyer = Key::new(2);
+        let id = testutil::mint(state, &owner.address());
+        let expires_at = START + 3600;
+        let message = lock_offer_message("", &id, &buyer.address(), expires_at, 1);
+        lock_for_offer(
+            state,
+            &id,
+            &buyer.address(),
+            expires_at,
+            &owner.address(),
+            1,
+            &owner.sign(&message),
+        )
+        .unwrap();
+        Offer { id, owner, buyer }
+    }
+
+    #[test]
+    fn lock_blocks_transfers_to_anyone_else() {
+        let (mut state, _) = testutil::state();
+        let offer = locked(&mut state);
+        let err = transfer_nft(&mut state, &offer.id, "carol");
+        assert!(matches!(err, Err(NftError::Locked(_))));
+        assert_eq!(
+            state.ledger.get_nft(&offer.id).unwrap().owner,
+            offer.owner.address()
+        );
+    }
+
+    #[test]
+    fn accept_transfers_to_the_buyer() {
+        let (mut state, _) = testutil::state();
+        let offer = locked(&mut state);
+        let buyer = offer.buyer.address();
+        let signature = offer
+            .buyer
+            .sign(&action_message("", "accept-offer", &offer.id, 1));
+        accept_offer(&mut state, &offer.id, &buyer, 1, &signature).unwrap();
+        assert_eq!(state.ledger.get_nft(&offer.id).unwrap().owner, buyer);
+        assert!(active_offer(&state, &offer.id).is_none());
+    }
+
+    #[test]
+    fn accept_needs_the_buyer_signature() {
+        let (mut state, _) = testutil::state();
+        let offer = locked(&mut state);
+        let buyer = offer.buyer.address();
+        let forged = Key::new(9).sign(&action_message("", "accept-offer", &offer.id, 1));
+        let err = accept_offer(&mut state, &offer.id, &buyer, 1, &forged);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        // The owner's signature doesn't stand in for the buyer's.
+        let signature = offer
+            .owner
+            .sign(&action_message("", "accept-offer", &offer.id, 2));
+        let err = accept_offer(&mut state, &offer.id, &offer.owner.address(), 2, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(active_offer(&state, &offer.id).is_some());
+    }
+
+    #[test]
+    fn lock_needs_the_owner_signature() {
+        let (mut state, _) = testutil::state();
+        let owner = Key::new(1);
+        let id = testutil::mint(&mut state, &owner.address());
+        let expires_at = START + 60;
+        let forged = Key::new(2).sign(&lock_offer_message("", &id, "buyer", expires_at, 1));
+        let err = lock_for_offer(
+            &mut state,
+            &id,
+            "buyer",
+            expires_at,
+            &owner.address(),
+            1,
+            &forged,
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(active_offer(&state, &id).is_none());
+    }
+
+    #[test]
+    fn buyer_can_cancel_with_its_signature() {
+        let (mut state, _) = testutil::state();
+        let offer = locked(&mut state);
+        let buyer = offer.buyer.address();
+        let forged = offer
+            .owner
+            .sign(&action_message("", "cancel-offer", &offer.id, 1));
+        assert!(cancel_offer(&mut state, &offer.id, &buyer, 1, &forged).is_err());
+        let signature = offer
+            .buyer
+            .sign(&action_message("", "cancel-offer", &offer.id, 1));
+        cancel_offer(&mut state, &offer.id, &buyer, 1, &signature).unwrap();
+        assert!(active_offer(&state, &offer.id).is_none());
+    }
+
+    #[test]
+    fn owner_cancels_with_its_next_nonce() {
+        let (mut state, _) = testutil::state();
+        let offer = locked(&mut state);
+        let owner = offer.owner.address();
+        // Nonce 1 went on the lock.
+        let replayed = offer
+            .owner
+            .sign(&action_message("", "cancel-offer", &offer.id, 1));
+        let err = cancel_offer(&mut state, &offer.id, &owner, 1, &replayed);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        let signature = offer
+            .owner
+            .sign(&action_message("", "cancel-offer", &offer.id, 2));
+        cancel_offer(&mut state, &offer.id, &owner, 2, &signature).unwrap();
+        assert!(active_offer(&state, &offer.id).is_none());
+    }
+
+    #[test]
+    fn expiry_releases_the_lock() {
+        let (mut state, clock) = testutil::state();
+        let offer = locked(&mut state);
+        clock.advance(3600);
+        assert!(active_offer(&state, &offer.id).is_none());
+        transfer_nft(&mut state, &offer.id, "carol").unwrap();
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{
+    attributes::{Attribute, AttributeValue, Attributes},
+    error::ErrorBody,
+    reveal::NftView,
+    AirdropRequest, ClaimResponse, GenericResponse, IBCImportRequest, MintItemRequest, MintRequest,
+    MintResponse, SignedCallerRequest, TransferRequest,
+};
+use utoipa::OpenApi;
+
+// Served at GET /openapi.json. Paths and schemas come from the `utoipa`
+// attributes on the handlers and their request types, so the spec follows
+// the code; a handler missing here is simply undocumented.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Penumbra NFT RPC"),
+    paths(
+        crate::mint_handler,
+        crate::transfer_handler,
+        crate::view_handler,
+        crate::stake_handler,
+        crate::unstake_handler,
+        crate::airdrop_handler,
+        crate::ibc_bridge_out_handler,
+        crate::ibc_import_handler,
+    ),
+    components(schemas(
+        AirdropRequest,
+        Attribute,
+        AttributeValue,
+        Attributes,
+        SignedCallerRequest,
+        ClaimResponse,
+        ErrorBody,
+        GenericResponse,
+        IBCImportRequest,
+        MintItemRequest,
+        MintRequest,
+        MintResponse,
+        NftView,
+        TransferRequest,
+    ))
+)]
+pub struct ApiDoc;
@@ -0,0 +1,133 @@
+use crate::{app::AppState, burn::live_nfts, list::NFTSummary, reveal::is_revealed};
+use std::collections::{HashMap, HashSet};
+
+// Owner address -> ids it holds. Derived from the ledger, so it isn't
+// persisted; it is rebuilt on load and kept current by every ownership change.
+#[derive(Debug, Default, PartialEq)]
+pub struct OwnerIndex {
+    by_owner: HashMap<String, HashSet<String>>,
+}
+
+impl OwnerIndex {
+    pub fn build(state: &AppState) -> Self {
+        let mut index = OwnerIndex::default();
+        for nft in live_nfts(state) {
+            match state.extras.get(&nft.id).and_then(|e| e.shares.as_ref()) {
+                Some(shares) => {
+                    for share in shares {
+                        index.insert(&share.address, &nft.id);
+                    }
+                }
+                None => index.insert(&nft.owner, &nft.id),
+            }
+        }
+        index
+    }
+
+    pub fn insert(&mut self, owner: &str, id: &str) {
+        self.by_owner
+            .entry(owner.to_string())
+            .or_default()
+            .insert(id.to_string());
+    }
+
+    pub fn remove(&mut self, owner: &str, id: &str) {
+        if let Some(ids) = self.by_owner.get_mut(owner) {
+            ids.remove(id);
+            if ids.is_empty() {
+                self.by_owner.remove(owner);
+            }
+        }
+    }
+
+    // Removing first keeps a transfer to self a no-op.
+    pub fn move_nft(&mut self, id: &str, from: &str, to: &str) {
+        self.remove(from, id);
+        self.insert(to, id);
+    }
+
+    pub fn ids(&self, owner: &str) -> impl Iterator<Item = &String> {
+        self.by_owner.get(owner).into_iter().flatten()
+    }
+}
+
+// Everything `address` owns, alone or as a shareholder, ordered by id.
+// Unrevealed shielded NFTs are left out, as from /search: listing them here
+// would give away their owner.
+pub fn nfts_by_owner(state: &AppState, address: &str) -> Vec<NFTSummary> {
+    let mut ids: Vec<&String> = state.owner_index.ids(address).collect();
+    ids.sort();
+    ids.into_iter()
+        .filter_map(|id| state.ledger.get_nft(id))
+        .filter(|nft| is_revealed(state, nft, None))
+        .map(|nft| NFTSummary::revealed(state, nft))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mint::mint_nft,
+        signature::transfer_message,
+        testutil::{self, Key},
+        transfer::transfer_signed,
+    };
+
+    fn scan(state: &AppState, owner: &str) -> Vec<String> {
+        let mut ids: Vec<String> = live_nfts(state)
+            .filter(|nft| nft.owner == owner)
+            .map(|nft| nft.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn index_matches_a_scan_after_transfers() {
+        let (mut state, _) = testutil::state();
+        let (alice, bob) = (Key::new(1), Key::new(2));
+        let ids: Vec<String> = (0..4)
+            .map(|_| testutil::mint(&mut state, &alice.address()))
+            .collect();
+        for (nonce, id) in ids[..3].iter().enumerate() {
+            let nonce = nonce as u64 + 1;
+            let signature = alice.sign(&transfer_message("", id, &bob.address(), nonce));
+            transfer_signed(
+                &mut state,
+                id,
+                &alice.address(),
+                &bob.address(),
+                nonce,
+                &signature,
+            )
+            .unwrap();
+        }
+        let signature = bob.sign(&transfer_message("", &ids[0], "carol", 1));
+        transfer_signed(&mut state, &ids[0], &bob.address(), "carol", 1, &signature).unwrap();
+
+        for owner in [alice.address(), bob.address(), "carol".to_string()] {
+            let listed: Vec<String> = nfts_by_owner(&state, &owner)
+                .into_iter()
+                .map(|summary| summary.id)
+                .collect();
+            assert_eq!(listed, scan(&state, &owner), "{}", owner);
+        }
+        assert_eq!(scan(&state, "carol"), [ids[0].clone()]);
+        assert_eq!(state.owner_index, OwnerIndex::build(&state));
+    }
+
+    #[test]
+    fn unrevealed_shielded_nfts_are_left_out() {
+        let (mut state, _) = testutil::state();
+        let public = testutil::mint(&mut state, "alice");
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        mint_nft(&mut state, "alice".to_string(), item).unwrap();
+        let ids: Vec<String> = nfts_by_owner(&state, "alice")
+            .into_iter()
+            .map(|summary| summary.id)
+            .collect();
+        assert_eq!(ids, [public]);
+    }
+}
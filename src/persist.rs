@@ -0,0 +1,234 @@
+use crate::{
+    app::AppState,
+    encryption::{unseal, StateKey},
+    owners::OwnerIndex,
+    schema,
+    store::{NftRecord, StateStore},
+};
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+pub trait Persist: Sized {
+    fn save_to_file(&self, path: &Path) -> io::Result<()>;
+    fn load_from_file(path: &Path) -> io::Result<Self>;
+}
+
+// Loads `path` if it exists, otherwise starts from an empty state.
+pub fn load_or_new(path: &Path) -> io::Result<AppState> {
+    if path.exists() {
+        AppState::load_from_file(path)
+    } else {
+        Ok(AppState::new())
+    }
+}
+
+impl Persist for AppState {
+    // Writes to a sibling temp file and renames it over `path`, so readers
+    // never observe a half-written file. Encrypted when PNFT_STATE_KEY is set.
+    fn save_to_file(&self, path: &Path) -> io::Result<()> {
+        let mut doc = serde_json::to_value(self)?;
+        schema::stamp(&mut doc);
+        let json = serde_json::to_vec_pretty(&doc)?;
+        let bytes = match StateKey::from_env()? {
+            Some(key) => key.seal(&json)?,
+            None => json,
+        };
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    }
+
+    fn load_from_file(path: &Path) -> io::Result<Self> {
+        let json = unseal(fs::read(path)?, StateKey::from_env()?.as_ref())?;
+        let mut doc: serde_json::Value = serde_json::from_slice(&json)?;
+        let from = schema::migrate(&mut doc)?;
+        let mut state: AppState = serde_json::from_value(doc)?;
+        state.owner_index = OwnerIndex::build(&state);
+        if from < schema::STATE_SCHEMA_VERSION {
+            state.save_to_file(path)?;
+        }
+        Ok(state)
+    }
+}
+
+// Where the server keeps its state between runs.
+pub enum Backend {
+    // The whole state as one JSON document, rewritten on every save.
+    File(PathBuf),
+    // NFT records kept individually; only the ones changed since the last
+    // save are written. Everything else is stored as one metadata blob.
+    Store(Box<dyn StateStore>),
+}
+
+impl Backend {
+    pub fn load(&self) -> io::Result<AppState> {
+        match self {
+            Backend::File(path) => load_or_new(path),
+            Backend::Store(store) => load_from_store(store.as_ref()),
+        }
+    }
+
+    pub fn save(&self, state: &mut AppState) -> io::Result<()> {
+        match self {
+            Backend::File(path) => {
+                state.dirty.clear();
+                state.save_to_file(path)
+            }
+            Backend::Store(store) => save_to_store(store.as_ref(), state),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Backend::File(path) => path.display().to_string(),
+            Backend::Store(_) => "state store".into(),
+        }
+    }
+}
+
+fn load_from_store(store: &dyn StateStore) -> io::Result<AppState> {
+    // Reassemble the whole document so migrations see the records too.
+    let mut doc = match store.load_meta()? {
+        Some(meta) => serde_json::from_slice(&meta)?,
+        None => serde_json::json!({ "schema_version": schema::STATE_SCHEMA_VERSION }),
+    };
+    let mut nfts = serde_json::Map::new();
+    let mut extras = serde_json::Map::new();
+    for (id, record) in store.iter()? {
+        nfts.insert(id.clone(), serde_json::to_value(record.nft)?);
+        if !record.extras.is_null() {
+            extras.insert(id, record.extras);
+        }
+    }
+    if let Some(doc) = doc.as_object_mut() {
+        doc.insert("nfts".into(), nfts.into());
+        doc.insert("extras".into(), extras.into());
+    }
+    let from = schema::migrate(&mut doc)?;
+    let mut state: AppState = serde_json::from_value(doc)?;
+    state.owner_index = OwnerIndex::build(&state);
+    if from < schema::STATE_SCHEMA_VERSION {
+        // Rewrite every record in the new shape on the next save.
+        state.dirty = state.ledger.nfts.keys().cloned().collect();
+    }
+    Ok(state)
+}
+
+fn save_to_store(store: &dyn StateStore, state: &mut AppState) -> io::Result<()> {
+    // Marks are only cleared once everything is written, so a failed save
+    // is retried in full by the next one.
+    for id in state.dirty.clone() {
+        let Some(nft) = state.ledger.get_nft(&id) else {
+            store.remove(&id)?;
+            continue;
+        };
+        let record = NftRecord {
+            nft: nft.clone(),
+            extras: serde_json::to_value(state.extras.get(&id).cloned().unwrap_or_default())?,
+        };
+        // A rolled-back transaction can leave ids marked without changes.
+        if store.get(&id)?.as_ref() != Some(&record) {
+            store.insert(&id, &record)?;
+        }
+    }
+    // The rest of the state, minus what the records already hold.
+    let mut meta = serde_json::to_value(&*state)?;
+    if let Some(meta) = meta.as_object_mut() {
+        meta.insert("nfts".into(), serde_json::Value::Object(Default::default()));
+        meta.insert(
+            "extras".into(),
+            serde_json::Value::Object(Default::default()),
+        );
+    }
+    schema::stamp(&mut meta);
+    store.save_meta(&serde_json::to_vec(&meta)?)?;
+    store.flush()?;
+    state.dirty.clear();
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::{decode_attributes, AttributeValue},
+        store::MemoryStore,
+        testutil,
+    };
+
+    #[test]
+    fn store_records_are_migrated_as_stored() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let store = MemoryStore::default();
+        let record = NftRecord {
+            nft: state.ledger.get_nft(&id).unwrap().clone(),
+            extras: serde_json::json!({ "staked_at": 7, "unstake_after": 9 }),
+        };
+        store.insert(&id, &record).unwrap();
+        store.save_meta(br#"{"schema_version":10}"#).unwrap();
+        let loaded = load_from_store(&store).unwrap();
+        assert_eq!(loaded.staking[&id].accrues_from, Some(7));
+        assert!(loaded.dirty.contains(&id));
+    }
+
+    #[test]
+    fn saved_state_loads_back_equal() {
+        let mut state = AppState::new();
+        let id = testutil::mint(&mut state, "alice");
+        state.nonces.insert("alice".to_string(), 3);
+        let path = std::env::temp_dir().join(format!("pnft-persist-{}.json", std::process::id()));
+        state.save_to_file(&path).unwrap();
+        let loaded = AppState::load_from_file(&path);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.ledger.get_nft(&id), state.ledger.get_nft(&id));
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&state).unwrap()
+        );
+    }
+
+    #[test]
+    fn unversioned_fixture_migrates_losslessly() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/state-v0.json");
+        let path = std::env::temp_dir().join(format!("pnft-fixture-{}.json", std::process::id()));
+        fs::copy(fixture, &path).unwrap();
+        let loaded = AppState::load_from_file(&path);
+        let rewritten: serde_json::Value =
+            serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        let old = loaded.ledger.get_nft("nft-1").unwrap();
+        assert_eq!(
+            (old.owner.as_str(), old.metadata.name.as_str()),
+            ("alice", "Old Ape")
+        );
+        let attributes = decode_attributes(&old.metadata.attributes);
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes[0].trait_type, "legacy");
+        assert_eq!(
+            attributes[0].value,
+            AttributeValue::String("rare, gold".into())
+        );
+        let stake = &loaded.staking["nft-1"];
+        assert_eq!(stake.accrues_from, Some(1_600_000_000));
+        assert_eq!(stake.unstake_after, Some(1_600_000_600));
+
+        // Already-structured attributes come through untouched.
+        let cat = loaded.ledger.get_nft("nft-2").unwrap();
+        assert!(cat.metadata.shielded);
+        assert_eq!(
+            decode_attributes(&cat.metadata.attributes)[0].trait_type,
+            "eyes"
+        );
+        assert!(!loaded.staking.contains_key("nft-2"));
+        assert_eq!(loaded.nonces["alice"], 4);
+        assert_eq!(rewritten["schema_version"], schema::STATE_SCHEMA_VERSION);
+    }
+}
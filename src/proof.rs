@@ -0,0 +1,150 @@
+use crate::{
+    app::AppState,
+    attributes::{decode_attributes, AttributeValue},
+    error::NftError,
+    reveal::is_revealed,
+    signature::verify_signature,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+
+// A single attribute disclosed by a viewing-key holder, signed by this
+// server so a third party can check it without the key.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttributeProof {
+    pub nft_id: String,
+    pub trait_type: String,
+    pub value: AttributeValue,
+    pub issued_at: u64,
+    // Hex ed25519 public key of the issuing server.
+    pub signer: String,
+    pub signature: String,
+}
+
+impl AttributeProof {
+    fn message(&self) -> Vec<u8> {
+        let value = serde_json::to_string(&self.value).expect("attribute values serialize");
+        format!(
+            "pnft-attribute-proof\n{}\n{}\n{}\n{}",
+            self.nft_id, self.trait_type, value, self.issued_at
+        )
+        .into_bytes()
+    }
+}
+
+// The server's proof-signing key. Without a configured seed a fresh key is
+// generated, and proofs issued before a restart no longer verify.
+pub struct ProofSigner {
+    key: SigningKey,
+}
+
+impl ProofSigner {
+    pub fn from_seed(seed: Option<&str>) -> Result<Self, String> {
+        let key = match seed {
+            Some(seed) => {
+                let bytes: [u8; 32] = hex::decode(seed)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| "proof key must be 32 hex-encoded bytes".to_string())?;
+                SigningKey::from_bytes(&bytes)
+            }
+            None => SigningKey::generate(&mut rand::rngs::OsRng),
+        };
+        Ok(ProofSigner { key })
+    }
+
+    pub fn public_key(&self) -> String {
+        hex::encode(self.key.verifying_key().as_bytes())
+    }
+
+    // True only for proofs this server signed and nobody altered since.
+    pub fn verify(&self, proof: &AttributeProof) -> bool {
+        proof.signer == self.public_key()
+            && verify_signature(&proof.signer, &proof.message(), &proof.signature).is_ok()
+    }
+}
+
+// Discloses one trait of `id`. Shielded NFTs need a viewing key that reveals
+// them, exactly as for /view.
+pub fn reveal_attribute_proof(
+    state: &AppState,
+    signer: &ProofSigner,
+    id: &str,
+    trait_type: &str,
+    viewing_key: Option<&str>,
+) -> Result<AttributeProof, NftError> {
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if !is_revealed(state, nft, viewing_key) {
+        return Err(NftError::Forbidden(format!(
+            "viewing key not authorized for NFT {}",
+            id
+        )));
+    }
+    let value = decode_attributes(&nft.metadata.attributes)
+        .into_iter()
+        .find(|attr| attr.trait_type == trait_type)
+        .map(|attr| attr.value)
+        .ok_or_else(|| {
+            NftError::NotFound(format!("attribute {} not found on NFT {}", trait_type, id))
+        })?;
+    let mut proof = AttributeProof {
+        nft_id: id.to_string(),
+        trait_type: trait_type.to_string(),
+        value,
+        issued_at: state.now(),
+        signer: signer.public_key(),
+        signature: String::new(),
+    };
+    proof.signature = hex::encode(signer.key.sign(&proof.message()).to_bytes());
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mint::mint_nft, testutil};
+
+    fn shielded(state: &mut AppState) -> String {
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        item.metadata.attributes =
+            r#"[{"trait_type":"eyes","value":"red"},{"trait_type":"level","value":3}]"#.to_string();
+        mint_nft(state, "alice".to_string(), item).unwrap()
+    }
+
+    #[test]
+    fn proof_for_one_attribute_verifies() {
+        let (mut state, _) = testutil::state();
+        let signer = ProofSigner::from_seed(None).unwrap();
+        let id = shielded(&mut state);
+        let err = reveal_attribute_proof(&state, &signer, &id, "eyes", None);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+
+        let proof =
+            reveal_attribute_proof(&state, &signer, &id, "eyes", Some("viewing-key")).unwrap();
+        assert_eq!(proof.value, AttributeValue::String("red".to_string()));
+        assert_eq!(proof.issued_at, testutil::START);
+        assert!(signer.verify(&proof));
+    }
+
+    #[test]
+    fn tampered_proof_is_rejected() {
+        let (mut state, _) = testutil::state();
+        let signer = ProofSigner::from_seed(Some(&"07".repeat(32))).unwrap();
+        let id = shielded(&mut state);
+        let proof = || reveal_attribute_proof(&state, &signer, &id, "level", Some("key")).unwrap();
+
+        let mut tampered = proof();
+        tampered.value = AttributeValue::Number(99.0);
+        assert!(!signer.verify(&tampered));
+        let mut tampered = proof();
+        tampered.nft_id = "other".to_string();
+        assert!(!signer.verify(&tampered));
+        // A proof from some other server doesn't verify here either.
+        let other = ProofSigner::from_seed(None).unwrap();
+        assert!(!other.verify(&proof()));
+    }
+}
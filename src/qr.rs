@@ -0,0 +1,22 @@
+use qrcode::{render::svg, types::QrError, QrCode};
+
+// Renders `payload` as an SVG QR code. Payloads beyond QR capacity are
+// reported rather than truncated.
+pub fn qr_svg(payload: &str) -> Result<String, QrError> {
+    let code = QrCode::new(payload.as_bytes())?;
+    Ok(code.render::<svg::Color>().min_dimensions(256, 256).build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_a_small_payload_and_refuses_an_oversized_one() {
+        let svg = qr_svg("02deadbeef{}").unwrap();
+        assert!(svg.contains("<svg"), "{}", svg);
+        assert_ne!(svg, qr_svg("02deadbeef[]").unwrap());
+        let err = qr_svg(&"x".repeat(8 * 1024));
+        assert!(matches!(err, Err(QrError::DataTooLong)));
+    }
+}
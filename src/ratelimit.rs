@@ -0,0 +1,126 @@
+use crate::error::NftError;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header::RETRY_AFTER, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+// Past this many tracked clients, expired windows are dropped on insert.
+const PRUNE_THRESHOLD: usize = 1024;
+
+// Fixed one-minute window per client IP. A limit of 0 disables it.
+pub struct RateLimiter {
+    per_minute: u32,
+    clients: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        RateLimiter {
+            per_minute,
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Counts a request from `ip`; on refusal returns how long until its
+    // window resets.
+    pub fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if clients.len() >= PRUNE_THRESHOLD && !clients.contains_key(&ip) {
+            clients.retain(|_, (start, _)| now.duration_since(*start) < WINDOW);
+        }
+        let (start, count) = clients.entry(ip).or_insert((now, 0));
+        if now.duration_since(*start) >= WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.per_minute {
+            return Err(WINDOW - now.duration_since(*start));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+// Rejects over-limit requests with 429 and a `Retry-After` in whole seconds.
+// Requests without a peer address (no connect info) are let through.
+pub async fn limit(State(limiter): State<Arc<RateLimiter>>, req: Request, next: Next) -> Response {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    if let Some(ip) = peer {
+        if let Err(wait) = limiter.check(ip, Instant::now()) {
+            let secs = wait.as_secs_f64().ceil().max(1.0) as u64;
+            let mut response =
+                NftError::RateLimited(format!("rate limit exceeded; retry in {}s", secs))
+                    .into_response();
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+            return response;
+        }
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn request(ip: [u8; 4]) -> axum::http::Request<Body> {
+        let mut request = axum::http::Request::post("/mint")
+            .body(Body::empty())
+            .unwrap();
+        let addr = SocketAddr::from((ip, 4000));
+        request.extensions_mut().insert(ConnectInfo(addr));
+        request
+    }
+
+    #[tokio::test]
+    async fn request_past_the_limit_gets_429() {
+        let limiter = Arc::new(RateLimiter::new(3));
+        let app = Router::new()
+            .route("/mint", post(|| async { "minted" }))
+            .route_layer(middleware::from_fn_with_state(limiter, limit));
+        for _ in 0..3 {
+            let response = app.clone().oneshot(request([10, 0, 0, 1])).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = app.clone().oneshot(request([10, 0, 0, 1])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(RETRY_AFTER));
+        // Other clients have their own window.
+        let response = app.oneshot(request([10, 0, 0, 2])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn window_resets_after_a_minute_and_zero_disables() {
+        let limiter = RateLimiter::new(1);
+        let ip = IpAddr::from([10, 0, 0, 1]);
+        let start = Instant::now();
+        limiter.check(ip, start).unwrap();
+        assert!(limiter.check(ip, start).is_err());
+        limiter.check(ip, start + WINDOW).unwrap();
+
+        let unlimited = RateLimiter::new(0);
+        for _ in 0..100 {
+            unlimited.check(ip, start).unwrap();
+        }
+    }
+}
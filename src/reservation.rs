@@ -0,0 +1,154 @@
+use crate::{
+    app::AppState,
+    collections::check_supply,
+    error::NftError,
+    events::EventKind,
+    mint::{mint_nft, MintItem},
+};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_RESERVATION_TTL_SECS: u64 = 15 * 60;
+
+// A held slot in a collection, counted against its supply until it is
+// finalized or expires.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Reservation {
+    pub collection: String,
+    pub expires_at: u64,
+}
+
+// Unexpired reservations held against `collection`.
+pub fn reserved_count(state: &AppState, collection: &str) -> u32 {
+    let now = state.now();
+    state
+        .reservations
+        .values()
+        .filter(|r| r.collection == collection && r.expires_at > now)
+        .count() as u32
+}
+
+// Drops expired reservations, recording a ReservationExpire for each. They
+// already stopped counting against supply; this only reclaims the memory.
+pub fn reclaim_expired(state: &mut AppState) -> usize {
+    let now = state.now();
+    let mut expired: Vec<(String, String)> = state
+        .reservations
+        .iter()
+        .filter(|(_, r)| r.expires_at <= now)
+        .map(|(id, r)| (id.clone(), r.collection.clone()))
+        .collect();
+    // Events land in a stable order.
+    expired.sort();
+    for (id, collection) in &expired {
+        state.reservations.remove(id);
+        state.record(EventKind::ReservationExpire, id, None, Some(collection));
+    }
+    expired.len()
+}
+
+// Holds one slot in an existing collection and returns the reservation id.
+pub fn reserve_mint(state: &mut AppState, collection: &str) -> Result<(String, u64), NftError> {
+    reclaim_expired(state);
+    if !state.collections.contains_key(collection) {
+        return Err(NftError::NotFound(format!(
+            "collection {} not found",
+            collection
+        )));
+    }
+    check_supply(state, collection, None, 1)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let expires_at = state.now().saturating_add(state.reservation_ttl_secs);
+    state.reservations.insert(
+        id.clone(),
+        Reservation {
+            collection: collection.to_string(),
+            expires_at,
+        },
+    );
+    Ok((id, expires_at))
+}
+
+// Consumes `reservation` and mints `item` into its collection. A failed mint
+// leaves the reservation in place so the caller can retry.
+pub fn finalize_mint(
+    state: &mut AppState,
+    reservation: &str,
+    mut item: MintItem,
+    owner: String,
+) -> Result<String, NftError> {
+    let held = state
+        .reservations
+        .remove(reservation)
+        .ok_or_else(|| NftError::NotFound(format!("reservation {} not found", reservation)))?;
+    if held.expires_at <= state.now() {
+        return Err(NftError::Conflict(format!(
+            "reservation {} expired",
+            reservation
+        )));
+    }
+    if item
+        .extras
+        .collection
+        .as_ref()
+        .is_some_and(|c| *c != held.collection)
+    {
+        state.reservations.insert(reservation.to_string(), held);
+        return Err(NftError::Invalid(
+            "collection does not match the reservation".into(),
+        ));
+    }
+    item.extras.collection = Some(held.collection.clone());
+    item.max_supply = None;
+    item.transfer_cooldown_secs = None;
+    mint_nft(state, owner, item).inspect_err(|_| {
+        state.reservations.insert(reservation.to_string(), held);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    // A collection capped at two with one NFT already minted.
+    fn one_slot_left(state: &mut AppState) -> MintItem {
+        let mut item = testutil::item("member");
+        item.extras.collection = Some("apes".to_string());
+        item.max_supply = Some(2);
+        mint_nft(state, "alice".to_string(), item.clone()).unwrap();
+        item.max_supply = None;
+        item
+    }
+
+    #[test]
+    fn reserved_slot_is_finalized_once() {
+        let (mut state, _) = testutil::state();
+        let item = one_slot_left(&mut state);
+        let (reservation, _) = reserve_mint(&mut state, "apes").unwrap();
+        let err = mint_nft(&mut state, "bob".to_string(), item.clone());
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+
+        let id =
+            finalize_mint(&mut state, &reservation, item.clone(), "carol".to_string()).unwrap();
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, "carol");
+        assert_eq!(state.collections["apes"].minted, 2);
+        let err = finalize_mint(&mut state, &reservation, item, "carol".to_string());
+        assert!(matches!(err, Err(NftError::NotFound(_))));
+        assert_eq!(state.collections["apes"].minted, 2);
+    }
+
+    #[test]
+    fn expired_reservation_gives_its_slot_back() {
+        let (mut state, clock) = testutil::state();
+        let item = one_slot_left(&mut state);
+        let (reservation, expires_at) = reserve_mint(&mut state, "apes").unwrap();
+        assert_eq!(reserved_count(&state, "apes"), 1);
+        clock.set(expires_at);
+        assert_eq!(reserved_count(&state, "apes"), 0);
+
+        mint_nft(&mut state, "bob".to_string(), item.clone()).unwrap();
+        let err = finalize_mint(&mut state, &reservation, item, "carol".to_string());
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        assert!(state.reservations.is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+use crate::{
+    app::AppState,
+    config::{PublicField, RevealMode},
+    error::NftError,
+    expiry::is_expired,
+    extras::NftStatus,
+    staking::is_staked,
+};
+use penumbra_nft::{types::NFT, view::reveal_nft};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Most ids one POST /view/batch may ask for.
+pub const MAX_VIEW_BATCH: usize = 100;
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+#[serde(untagged)]
+pub enum NftView {
+    Full {
+        #[serde(flatten)]
+        #[schema(value_type = Object)]
+        nft: NFT,
+        // e.g. "#3 of 100"; only for NFTs in a capped collection.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        edition: Option<String>,
+        expired: bool,
+        status: NftStatus,
+    },
+    Redacted {
+        id: String,
+        shielded: bool,
+        status: NftStatus,
+    },
+}
+
+// Public NFTs are always revealed, and so is everything under
+// `RevealMode::PublicOnly`. Otherwise shielded ones only when `reveal_nft`
+// accepts the viewing key, which is recorded in the NFT's reveal log.
+pub fn is_revealed(state: &AppState, nft: &NFT, viewing_key: Option<&str>) -> bool {
+    revealed(state, nft, viewing_key).is_some()
+}
+
+fn revealed(state: &AppState, nft: &NFT, viewing_key: Option<&str>) -> Option<NFT> {
+    if !nft.metadata.shielded || state.reveal_mode == RevealMode::PublicOnly {
+        return Some(nft.clone());
+    }
+    let viewing_key = viewing_key?;
+    let nft = reveal_nft(&state.ledger, &nft.id, Some(viewing_key))?;
+    state.reveal_log.append(&nft.id, state.now(), viewing_key);
+    Some(nft)
+}
+
+// Unrevealed shielded NFTs show only their id.
+pub fn reveal_view(state: &AppState, id: &str, viewing_key: Option<&str>) -> Option<NftView> {
+    let nft = state.ledger.get_nft(id)?;
+    let status = state
+        .extras
+        .get(id)
+        .map(|extras| extras.status)
+        .unwrap_or_default();
+    let revealed = revealed(state, nft, viewing_key);
+    let view = match revealed {
+        Some(mut nft) => {
+            // The stake entry, not the ledger flag, says whether it's staked.
+            nft.staked = is_staked(state, id);
+            NftView::Full {
+                nft,
+                edition: state
+                    .extras
+                    .get(id)
+                    .and_then(|extras| extras.edition_label()),
+                expired: is_expired(state, id),
+                status,
+            }
+        }
+        None => NftView::Redacted {
+            id: nft.id.clone(),
+            shielded: true,
+            status,
+        },
+    };
+    Some(view)
+}
+
+#[derive(serde::Serialize)]
+pub struct AuditEntry {
+    #[serde(flatten)]
+    pub nft: NFT,
+    pub status: NftStatus,
+}
+
+pub struct AuditPage {
+    // Every NFT in the state, burned ones included.
+    pub total: usize,
+    pub items: Vec<AuditEntry>,
+    // Pass as `after` to get the next page; `None` once exhausted.
+    pub next_cursor: Option<String>,
+}
+
+// One page, ordered by id, of every NFT exactly as stored: shielding is
+// ignored, so only admins may call this. Each shielded NFT returned gets an
+// admin entry in its reveal log.
+pub fn reveal_all(state: &AppState, after: Option<&str>, limit: usize) -> AuditPage {
+    let total = state.ledger.nfts.len();
+    let mut ids: Vec<&String> = state
+        .ledger
+        .nfts
+        .keys()
+        .filter(|id| after.is_none_or(|after| id.as_str() > after))
+        .collect();
+    ids.sort();
+    let remaining = ids.len();
+    let now = state.now();
+    let items: Vec<AuditEntry> = ids
+        .into_iter()
+        .take(limit)
+        .filter_map(|id| state.ledger.get_nft(id))
+        .map(|nft| {
+            if nft.metadata.shielded {
+                state.reveal_log.append_admin(&nft.id, now);
+            }
+            AuditEntry {
+                nft: nft.clone(),
+                status: state
+                    .extras
+                    .get(&nft.id)
+                    .map(|extras| extras.status)
+                    .unwrap_or_default(),
+            }
+        })
+        .collect();
+    let next_cursor = (remaining > items.len())
+        .then(|| items.last().map(|item| item.nft.id.clone()))
+        .flatten();
+    AuditPage {
+        total,
+        items,
+        next_cursor,
+    }
+}
+
+// A collection's choice of what GET /view/:id/public shows.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublicPolicy {
+    Fields(BTreeSet<PublicField>),
+    // Nothing is shown without a viewing key; the public view is a 404.
+    Private,
+}
+
+// The curated subset of an NFT that is safe to show without a viewing key,
+// whether or not it is shielded. Fields left out by the policy are omitted.
+#[derive(serde::Serialize)]
+pub struct PublicView {
+    pub id: String,
+    pub shielded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_cid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<NftStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staked: Option<bool>,
+}
+
+// None when the NFT doesn't exist or its collection is private. The fields
+// come from the collection's policy, else the configured defaults.
+pub fn reveal_public_view(state: &AppState, id: &str) -> Option<PublicView> {
+    let nft = state.ledger.get_nft(id)?;
+    let extras = state.extras.get(id).cloned().unwrap_or_default();
+    let policy = extras
+        .collection
+        .as_ref()
+        .and_then(|name| state.public_views.get(name));
+    let fields: BTreeSet<PublicField> = match policy {
+        Some(PublicPolicy::Private) => return None,
+        Some(PublicPolicy::Fields(fields)) => fields.clone(),
+        None => state.public_fields.iter().copied().collect(),
+    };
+    let show = |field| fields.contains(&field);
+    Some(PublicView {
+        id: nft.id.clone(),
+        shielded: nft.metadata.shielded,
+        name: show(PublicField::Name).then(|| nft.metadata.name.clone()),
+        description: show(PublicField::Description).then(|| nft.metadata.description.clone()),
+        image_cid: show(PublicField::ImageCid).then(|| nft.metadata.image_cid.clone()),
+        collection: extras
+            .collection
+            .clone()
+            .filter(|_| show(PublicField::Collection)),
+        edition: extras
+            .edition_label()
+            .filter(|_| show(PublicField::Edition)),
+        status: show(PublicField::Status).then_some(extras.status),
+        staked: show(PublicField::Staked).then(|| is_staked(state, id)),
+    })
+}
+
+pub fn set_public_policy(
+    state: &mut AppState,
+    collection: &str,
+    policy: PublicPolicy,
+) -> Result<(), NftError> {
+    if collection.trim().is_empty() {
+        return Err(NftError::Invalid(
+            "collection name must not be empty".into(),
+        ));
+    }
+    state.public_views.insert(collection.to_string(), policy);
+    Ok(())
+}
+
+// `reveal_view` for each id, with one viewing key tried against all of them.
+// Missing ids map to None; duplicates collapse into one entry.
+pub fn reveal_views(
+    state: &AppState,
+    ids: &[String],
+    viewing_key: Option<&str>,
+) -> Result<BTreeMap<String, Option<NftView>>, NftError> {
+    if ids.len() > MAX_VIEW_BATCH {
+        return Err(NftError::Invalid(format!(
+            "batch view takes at most {} ids, got {}",
+            MAX_VIEW_BATCH,
+            ids.len()
+        )));
+    }
+    Ok(ids
+        .iter()
+        .map(|id| (id.clone(), reveal_view(state, id, viewing_key)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mint::mint_nft, testutil};
+
+    fn shielded(state: &mut AppState) -> String {
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        mint_nft(state, "alice".to_string(), item).unwrap()
+    }
+
+    #[test]
+    fn unshielded_nft_is_visible_without_a_key() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let view = reveal_view(&state, &id, None);
+        assert!(matches!(view, Some(NftView::Full { .. })));
+        assert!(state.reveal_log.records(&id).is_empty());
+    }
+
+    #[test]
+    fn redact_shielded_hides_a_shielded_nft_without_a_key() {
+        let (mut state, _) = testutil::state();
+        let id = shielded(&mut state);
+        let view = reveal_view(&state, &id, None);
+        assert!(matches!(
+            view,
+            Some(NftView::Redacted { shielded: true, .. })
+        ));
+        let view = reveal_view(&state, &id, Some("viewing-key"));
+        assert!(matches!(view, Some(NftView::Full { .. })));
+    }
+
+    #[test]
+    fn public_only_reveals_a_shielded_nft() {
+        let (mut state, _) = testutil::state();
+        state.reveal_mode = RevealMode::PublicOnly;
+        let id = shielded(&mut state);
+        let view = reveal_view(&state, &id, None);
+        assert!(matches!(view, Some(NftView::Full { .. })));
+    }
+
+    #[test]
+    fn batch_view_maps_every_id_and_nulls_the_missing() {
+        let (mut state, _) = testutil::state();
+        let public = testutil::mint(&mut state, "alice");
+        let hidden = shielded(&mut state);
+        let ids = vec![public.clone(), hidden.clone(), "missing".to_string()];
+        let views = reveal_views(&state, &ids, None).unwrap();
+        assert_eq!(views.len(), 3);
+        assert!(matches!(views[&public], Some(NftView::Full { .. })));
+        assert!(matches!(views[&hidden], Some(NftView::Redacted { .. })));
+        assert!(views["missing"].is_none());
+        assert_eq!(
+            serde_json::to_value(&views).unwrap()["missing"],
+            serde_json::Value::Null
+        );
+
+        let views = reveal_views(&state, &ids, Some("viewing-key")).unwrap();
+        assert!(matches!(views[&hidden], Some(NftView::Full { .. })));
+        let too_many = vec![public; MAX_VIEW_BATCH + 1];
+        let err = reveal_views(&state, &too_many, None);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+    }
+
+    fn shielded_in(state: &mut AppState, collection: &str) -> String {
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        item.extras.collection = Some(collection.to_string());
+        mint_nft(state, "alice".to_string(), item).unwrap()
+    }
+
+    #[test]
+    fn public_view_of_a_shielded_nft_shows_only_the_default_fields() {
+        let (mut state, _) = testutil::state();
+        let id = shielded_in(&mut state, "apes");
+        let view = serde_json::to_value(reveal_public_view(&state, &id).unwrap()).unwrap();
+        assert_eq!(
+            view,
+            serde_json::json!({
+                "id": id, "shielded": true, "name": "hidden",
+                "collection": "apes", "status": "active",
+            })
+        );
+        assert!(state.reveal_log.records(&id).is_empty());
+    }
+
+    #[test]
+    fn collection_policy_picks_the_fields_or_hides_the_nft() {
+        let (mut state, _) = testutil::state();
+        let fields = [PublicField::ImageCid, PublicField::Staked].into();
+        set_public_policy(&mut state, "apes", PublicPolicy::Fields(fields)).unwrap();
+        set_public_policy(&mut state, "vault", PublicPolicy::Private).unwrap();
+        let ape = shielded_in(&mut state, "apes");
+        let view = reveal_public_view(&state, &ape).unwrap();
+        assert_eq!(view.image_cid.as_deref(), Some(testutil::CID));
+        assert_eq!(view.staked, Some(false));
+        assert_eq!((view.name, view.collection), (None, None));
+
+        let vaulted = shielded_in(&mut state, "vault");
+        assert!(reveal_public_view(&state, &vaulted).is_none());
+    }
+}
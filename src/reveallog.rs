@@ -0,0 +1,169 @@
+use crate::{
+    app::AppState,
+    error::NftError,
+    nonce::{accept_nonce, check_nonce},
+    signature::{action_message, verify_signature, Auth},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::Mutex};
+
+// Oldest records are dropped past this, so a busy NFT can't grow the state
+// without bound.
+pub const MAX_REVEALS_PER_NFT: usize = 1000;
+
+// Stands in for a key fingerprint on reveals by GET /admin/reveal-all.
+pub const ADMIN_FINGERPRINT: &str = "admin";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RevealRecord {
+    pub timestamp: u64,
+    // Leading hex of the viewing key's SHA-256; never the key itself.
+    pub key_fingerprint: String,
+}
+
+// NFT id -> every time a viewing key revealed it, oldest first. Reveals
+// happen on reads, which hold only the state's read lock, hence the mutex.
+// Persisted with the rest of the state on the next save.
+#[derive(Default)]
+pub struct RevealLog(Mutex<HashMap<String, Vec<RevealRecord>>>);
+
+impl RevealLog {
+    pub fn append(&self, id: &str, timestamp: u64, viewing_key: &str) {
+        self.push(id, timestamp, key_fingerprint(viewing_key));
+    }
+
+    // A reveal by the admin, which needs no viewing key.
+    pub fn append_admin(&self, id: &str, timestamp: u64) {
+        self.push(id, timestamp, ADMIN_FINGERPRINT.to_string());
+    }
+
+    fn push(&self, id: &str, timestamp: u64, key_fingerprint: String) {
+        let mut log = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let records = log.entry(id.to_string()).or_default();
+        if records.len() >= MAX_REVEALS_PER_NFT {
+            records.remove(0);
+        }
+        records.push(RevealRecord {
+            timestamp,
+            key_fingerprint,
+        });
+    }
+
+    pub fn records(&self, id: &str) -> Vec<RevealRecord> {
+        let log = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        log.get(id).cloned().unwrap_or_default()
+    }
+}
+
+impl Serialize for RevealLog {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let log = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        log.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RevealLog {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashMap::deserialize(deserializer).map(|log| RevealLog(Mutex::new(log)))
+    }
+}
+
+// Enough to tell keys apart without making them guessable.
+pub fn key_fingerprint(viewing_key: &str) -> String {
+    hex::encode(&Sha256::digest(viewing_key.as_bytes())[..8])
+}
+
+// The log for one NFT; only its owner, signing
+// `action_message("reveals")`, or an admin may read it.
+pub fn reveal_history(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    auth: Auth,
+) -> Result<Vec<RevealRecord>, NftError> {
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if let Auth::Owner { nonce, signature } = auth {
+        if nft.owner != caller {
+            return Err(NftError::Forbidden(format!(
+                "only the owner of NFT {} can see who revealed it",
+                id
+            )));
+        }
+        verify_signature(
+            caller,
+            &action_message(&state.tenant, "reveals", id, nonce),
+            signature,
+        )?;
+        check_nonce(state, caller, nonce)?;
+        accept_nonce(state, caller, nonce);
+    }
+    Ok(state.reveal_log.records(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mint::mint_nft,
+        reveal::reveal_view,
+        testutil::{self, Key},
+    };
+
+    fn shielded(state: &mut AppState, owner: &str) -> String {
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        mint_nft(state, owner.to_string(), item).unwrap()
+    }
+
+    #[test]
+    fn keyed_reveal_appends_a_record() {
+        let (mut state, _) = testutil::state();
+        let id = shielded(&mut state, "alice");
+        reveal_view(&state, &id, Some("viewing-key")).unwrap();
+        let records = state.reveal_log.records(&id);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timestamp, testutil::START);
+        assert_eq!(records[0].key_fingerprint, key_fingerprint("viewing-key"));
+    }
+
+    #[test]
+    fn redacted_view_does_not_append() {
+        let (mut state, _) = testutil::state();
+        let id = shielded(&mut state, "alice");
+        reveal_view(&state, &id, None).unwrap();
+        assert!(state.reveal_log.records(&id).is_empty());
+    }
+
+    #[test]
+    fn history_needs_the_owners_signature() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = shielded(&mut state, &alice.address());
+        reveal_view(&state, &id, Some("viewing-key")).unwrap();
+
+        let forged = Key::new(2).sign(&action_message("", "reveals", &id, 1));
+        let auth = Auth::Owner {
+            nonce: 1,
+            signature: &forged,
+        };
+        let err = reveal_history(&mut state, &id, &alice.address(), auth);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+
+        let signature = alice.sign(&action_message("", "reveals", &id, 1));
+        let auth = Auth::Owner {
+            nonce: 1,
+            signature: &signature,
+        };
+        let records = reveal_history(&mut state, &id, &alice.address(), auth).unwrap();
+        assert_eq!(records.len(), 1);
+        let err = reveal_history(&mut state, &id, &alice.address(), auth);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+
+        let records = reveal_history(&mut state, &id, "", Auth::Admin).unwrap();
+        assert_eq!(records.len(), 1);
+    }
+}
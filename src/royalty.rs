@@ -0,0 +1,122 @@
+use crate::{app::AppState, error::NftError};
+use serde::{Deserialize, Serialize};
+
+pub const MAX_BPS: u16 = 10_000;
+
+// Secondary-sale royalty set at mint. Informational only: nothing here
+// collects it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Royalty {
+    pub bps: u16,
+    pub recipient: String,
+}
+
+impl Royalty {
+    // From the optional request fields. Either one alone still yields a
+    // royalty, so `validate_royalty` can reject bps without a recipient.
+    pub fn from_parts(bps: Option<u16>, recipient: Option<String>) -> Option<Royalty> {
+        if bps.is_none() && recipient.is_none() {
+            return None;
+        }
+        Some(Royalty {
+            bps: bps.unwrap_or(0),
+            recipient: recipient.unwrap_or_default(),
+        })
+    }
+}
+
+pub fn validate_royalty(royalty: &Royalty) -> Result<(), NftError> {
+    if royalty.bps > MAX_BPS {
+        return Err(NftError::Invalid(format!(
+            "royalty_bps {} exceeds {}",
+            royalty.bps, MAX_BPS
+        )));
+    }
+    if royalty.bps > 0 && royalty.recipient.trim().is_empty() {
+        return Err(NftError::Invalid(
+            "royalty_bps requires a royalty_recipient".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoyaltyQuote {
+    pub recipient: String,
+    pub bps: u16,
+    pub amount: u64,
+}
+
+// Royalty owed on a sale of `id` at `price`, rounded down. NFTs minted
+// without one quote an empty recipient and zero.
+pub fn royalty_quote(state: &AppState, id: &str, price: u64) -> Result<RoyaltyQuote, NftError> {
+    if state.ledger.get_nft(id).is_none() {
+        return Err(NftError::NotFound(format!("NFT {} not found", id)));
+    }
+    let royalty = state
+        .extras
+        .get(id)
+        .and_then(|extras| extras.royalty.as_ref());
+    Ok(match royalty {
+        Some(royalty) => RoyaltyQuote {
+            recipient: royalty.recipient.clone(),
+            bps: royalty.bps,
+            amount: (price as u128 * royalty.bps as u128 / MAX_BPS as u128) as u64,
+        },
+        None => RoyaltyQuote {
+            recipient: String::new(),
+            bps: 0,
+            amount: 0,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mint::mint_nft, testutil};
+
+    fn royalty(bps: u16) -> Royalty {
+        Royalty {
+            bps,
+            recipient: "artist".into(),
+        }
+    }
+
+    #[test]
+    fn bps_over_10000_is_rejected() {
+        validate_royalty(&royalty(MAX_BPS)).unwrap();
+        let err = validate_royalty(&royalty(10_001));
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        let err = validate_royalty(&Royalty::from_parts(Some(250), None).unwrap());
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+    }
+
+    #[test]
+    fn quote_takes_bps_of_the_price_rounded_down() {
+        let (mut state, _) = testutil::state();
+        let mut item = testutil::item("royal");
+        item.extras.royalty = Some(royalty(250));
+        let id = mint_nft(&mut state, "alice".to_string(), item).unwrap();
+        let quote = royalty_quote(&state, &id, 1_999).unwrap();
+        assert_eq!((quote.recipient.as_str(), quote.bps), ("artist", 250));
+        assert_eq!(quote.amount, 49);
+        assert_eq!(
+            royalty_quote(&state, &id, u64::MAX).unwrap().amount,
+            u64::MAX / 40
+        );
+    }
+
+    #[test]
+    fn missing_royalty_quotes_nothing() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        let quote = royalty_quote(&state, &id, 1_000).unwrap();
+        assert_eq!(
+            (quote.recipient.as_str(), quote.bps, quote.amount),
+            ("", 0, 0)
+        );
+        let err = royalty_quote(&state, "missing", 1_000);
+        assert!(matches!(err, Err(NftError::NotFound(_))));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::attributes::{decode_attributes, encode_attributes, Attribute};
+use serde_json::{json, Value};
+use std::io;
+
+// Bump whenever the persisted shape of `AppState` changes. Files written
+// before versioning carry no number and count as 0.
+pub const STATE_SCHEMA_VERSION: u32 = 12;
+
+const FIELD: &str = "schema_version";
+
+// Records the current schema version in a serialized state document.
+pub fn stamp(doc: &mut Value) {
+    if let Some(doc) = doc.as_object_mut() {
+        doc.insert(FIELD.into(), STATE_SCHEMA_VERSION.into());
+    }
+}
+
+// A newer file may hold fields this binary would silently drop, so loading
+// it is refused rather than risking data loss on the next save.
+pub fn check(doc: &Value) -> io::Result<u32> {
+    let version = match doc.get(FIELD) {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| invalid(format!("{} must be a non-negative integer", FIELD)))?,
+    };
+    if version > STATE_SCHEMA_VERSION {
+        return Err(invalid(format!(
+            "state schema version {} is newer than this binary supports ({}); upgrade the server",
+            version, STATE_SCHEMA_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+// `MIGRATIONS[n]` upgrades a version-n document to version n + 1. Steps only
+// ever append; a released step is never edited.
+// Growing the shape with a defaulted field still takes a step, if only
+// `unchanged`, so an older binary refuses the file instead of dropping it.
+const MIGRATIONS: [fn(&mut Value); STATE_SCHEMA_VERSION as usize] = [
+    structure_attributes,
+    // 1 -> 2: extras gain `image_cid_history`.
+    unchanged,
+    // 2 -> 3: extras gain `offer`.
+    unchanged,
+    // 3 -> 4: extras gain `ibc_status` and `export_digest`.
+    unchanged,
+    // 4 -> 5: extras gain `unstake_after`.
+    unchanged,
+    // 5 -> 6: extras gain `status`; burned NFTs stay in `nfts` as tombstones.
+    unchanged,
+    // 6 -> 7: top-level `reveal_log`.
+    unchanged,
+    // 7 -> 8: extras gain `shares` and `share_consents`.
+    unchanged,
+    // 8 -> 9: top-level `attribute_schemas`.
+    unchanged,
+    // 9 -> 10: top-level `public_views`.
+    unchanged,
+    move_stakes_out_of_extras,
+    // 11 -> 12: top-level `balances`.
+    unchanged,
+];
+
+// Brings an older document up to the current shape in place and returns the
+// version it started at.
+pub fn migrate(doc: &mut Value) -> io::Result<u32> {
+    let from = check(doc)?;
+    for step in &MIGRATIONS[from as usize..] {
+        step(doc);
+    }
+    stamp(doc);
+    Ok(from)
+}
+
+// 0 -> 1: free-form attribute strings become the structured encoding, the
+// old text kept whole as a single "legacy" trait.
+fn structure_attributes(doc: &mut Value) {
+    let Some(nfts) = doc.get_mut("nfts").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for nft in nfts.values_mut() {
+        let Some(attributes) = nft.pointer_mut("/metadata/attributes") else {
+            continue;
+        };
+        let Some(raw) = attributes.as_str() else {
+            continue;
+        };
+        if serde_json::from_str::<Vec<Attribute>>(raw).is_err() {
+            *attributes = Value::String(encode_attributes(&decode_attributes(raw)));
+        }
+    }
+}
+
+// 10 -> 11: stakes move to the top-level `staking` map. `staked_at` and
+// `unstake_after` leave the extras, and every NFT the ledger has staked gets
+// an entry.
+fn move_stakes_out_of_extras(doc: &mut Value) {
+    let staked: Vec<String> = doc
+        .get("nfts")
+        .and_then(Value::as_object)
+        .map(|nfts| {
+            nfts.iter()
+                .filter(|(_, nft)| nft.get("staked").and_then(Value::as_bool) == Some(true))
+                .map(|(id, _)| id.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let Some(doc) = doc.as_object_mut() else {
+        return;
+    };
+    let mut staking = serde_json::Map::new();
+    if let Some(extras) = doc.get_mut("extras").and_then(Value::as_object_mut) {
+        for (id, entry) in extras.iter_mut() {
+            let Some(entry) = entry.as_object_mut() else {
+                continue;
+            };
+            let staked_at = entry.remove("staked_at").unwrap_or(Value::Null);
+            let unstake_after = entry.remove("unstake_after").unwrap_or(Value::Null);
+            if !staked_at.is_null() {
+                staking.insert(
+                    id.clone(),
+                    json!({
+                        // Claims used to move staked_at, so the original
+                        // stake time is lost.
+                        "staked_at": null,
+                        "accrues_from": staked_at,
+                        "unstake_after": unstake_after,
+                    }),
+                );
+            }
+        }
+    }
+    for id in staked {
+        staking
+            .entry(id)
+            .or_insert_with(|| json!({ "staked_at": null, "accrues_from": null }));
+    }
+    if !staking.is_empty() {
+        doc.insert("staking".into(), Value::Object(staking));
+    }
+}
+
+// For steps that only add fields whose absence already means the default.
+fn unchanged(_doc: &mut Value) {}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_1_document_migrates_unchanged() {
+        let mut doc = json!({
+            "schema_version": 1,
+            "nfts": {},
+            "extras": { "a": { "ibc_status": "exported", "status": "burned" } },
+            "balances": { "alice": 3 },
+        });
+        let mut expected = doc.clone();
+        expected["schema_version"] = STATE_SCHEMA_VERSION.into();
+        assert_eq!(migrate(&mut doc).unwrap(), 1);
+        assert_eq!(doc, expected);
+    }
+
+    #[test]
+    fn unversioned_document_runs_every_step() {
+        let mut doc = json!({
+            "nfts": { "a": { "staked": true, "metadata": { "attributes": "rare" } } },
+        });
+        assert_eq!(migrate(&mut doc).unwrap(), 0);
+        assert_eq!(doc[FIELD], STATE_SCHEMA_VERSION);
+        assert!(doc.pointer("/staking/a").is_some());
+        assert!(doc
+            .pointer("/nfts/a/metadata/attributes")
+            .and_then(Value::as_str)
+            .is_some_and(|raw| raw.contains("legacy")));
+    }
+
+    #[test]
+    fn stakes_move_out_of_the_extras() {
+        let mut doc = json!({
+            "schema_version": 10,
+            "nfts": { "a": { "staked": true }, "b": { "staked": true }, "c": {} },
+            "extras": {
+                "a": { "staked_at": 7, "unstake_after": 9, "frozen": true },
+                "c": { "frozen": true },
+            },
+        });
+        migrate(&mut doc).unwrap();
+        assert_eq!(
+            doc["staking"],
+            json!({
+                "a": { "staked_at": null, "accrues_from": 7, "unstake_after": 9 },
+                "b": { "staked_at": null, "accrues_from": null },
+            })
+        );
+        assert_eq!(doc["extras"]["a"], json!({ "frozen": true }));
+    }
+
+    #[test]
+    fn newer_document_is_refused() {
+        let doc = json!({ "schema_version": STATE_SCHEMA_VERSION + 1 });
+        assert!(check(&doc).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    app::AppState, attributes::decode_attributes, burn::live_nfts, list::NFTSummary,
+    reveal::is_revealed,
+};
+use penumbra_nft::types::NFT;
+
+// NFTs having every `(trait_type, value)` pair, ordered by id. Shielded NFTs
+// are only searched when `viewing_key` reveals them, so their traits can't
+// be probed without it.
+pub fn search_nfts(
+    state: &AppState,
+    filters: &[(String, String)],
+    viewing_key: Option<&str>,
+) -> Vec<NFTSummary> {
+    let mut matches: Vec<&NFT> = live_nfts(state)
+        .filter(|nft| is_revealed(state, nft, viewing_key))
+        .filter(|nft| {
+            let attributes = decode_attributes(&nft.metadata.attributes);
+            filters.iter().all(|(trait_type, value)| {
+                attributes
+                    .iter()
+                    .any(|attr| &attr.trait_type == trait_type && attr.value.matches(value))
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| a.id.cmp(&b.id));
+    matches
+        .into_iter()
+        .map(|nft| NFTSummary::revealed(state, nft))
+        .collect()
+}
+
+pub struct SearchQuery {
+    pub filters: Vec<(String, String)>,
+    pub viewing_key: Option<String>,
+}
+
+// Reads `trait`/`value` pairs in order from a raw query string, plus an
+// optional `viewing_key`. A `trait` without a following `value` is an error.
+pub fn parse_search_query(query: &str) -> Result<SearchQuery, String> {
+    let mut filters = Vec::new();
+    let mut pending: Option<String> = None;
+    let mut viewing_key = None;
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        match key.as_ref() {
+            "trait" => {
+                if let Some(trait_type) = pending.replace(value.into_owned()) {
+                    return Err(format!("trait {:?} has no value", trait_type));
+                }
+            }
+            "value" => {
+                let trait_type = pending
+                    .take()
+                    .ok_or_else(|| "value given without a preceding trait".to_string())?;
+                filters.push((trait_type, value.into_owned()));
+            }
+            "viewing_key" => viewing_key = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    if let Some(trait_type) = pending {
+        return Err(format!("trait {:?} has no value", trait_type));
+    }
+    Ok(SearchQuery {
+        filters,
+        viewing_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::AppState, mint::mint_nft, testutil};
+
+    fn mint_with(state: &mut AppState, attributes: &str, shielded: bool) -> String {
+        let mut item = testutil::item("searched");
+        item.metadata.attributes = attributes.to_string();
+        item.metadata.shielded = shielded;
+        mint_nft(state, "alice".to_string(), item).unwrap()
+    }
+
+    fn filter(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(t, v)| (t.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn ids(state: &AppState, pairs: &[(&str, &str)], viewing_key: Option<&str>) -> Vec<String> {
+        search_nfts(state, &filter(pairs), viewing_key)
+            .into_iter()
+            .map(|summary| summary.id)
+            .collect()
+    }
+
+    #[test]
+    fn filters_are_anded() {
+        let (mut state, _) = testutil::state();
+        let red_big = mint_with(
+            &mut state,
+            r#"[{"trait_type":"hat","value":"red"},{"trait_type":"size","value":3}]"#,
+            false,
+        );
+        let red = mint_with(&mut state, r#"[{"trait_type":"hat","value":"red"}]"#, false);
+        mint_with(
+            &mut state,
+            r#"[{"trait_type":"hat","value":"blue"}]"#,
+            false,
+        );
+
+        let mut both = vec![red_big.clone(), red];
+        both.sort();
+        assert_eq!(ids(&state, &[("hat", "red")], None), both);
+        assert_eq!(
+            ids(&state, &[("hat", "red"), ("size", "3")], None),
+            [red_big]
+        );
+        assert!(ids(&state, &[("hat", "red"), ("size", "4")], None).is_empty());
+    }
+
+    #[test]
+    fn shielded_nfts_need_a_viewing_key() {
+        let (mut state, _) = testutil::state();
+        let id = mint_with(&mut state, r#"[{"trait_type":"hat","value":"red"}]"#, true);
+        assert!(ids(&state, &[("hat", "red")], None).is_empty());
+        assert_eq!(ids(&state, &[("hat", "red")], Some("viewing-key")), [id]);
+    }
+
+    #[test]
+    fn query_pairs_each_trait_with_its_value() {
+        let query = parse_search_query("trait=hat&value=red&trait=size&value=3").unwrap();
+        assert_eq!(query.filters, filter(&[("hat", "red"), ("size", "3")]));
+        assert!(parse_search_query("trait=hat").is_err());
+        assert!(parse_search_query("value=red").is_err());
+    }
+}
@@ -0,0 +1,359 @@
+use crate::{
+    app::AppState,
+    burn::ensure_not_burned,
+    error::NftError,
+    events::EventKind,
+    nonce::{accept_nonce, check_nonce},
+    royalty::MAX_BPS,
+    signature::{consent_message, share_message, verify_signature},
+    transfer::transfer_nft,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Share {
+    pub address: String,
+    pub bps: u32,
+}
+
+// Who holds an NFT. penumbra_nft only knows a single `owner`; for a shared
+// NFT that stays the address that split it, and the shares live in extras.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ownership {
+    Sole(String),
+    Shared(Vec<Share>),
+}
+
+// At least two distinct holders, each with a positive share, summing to
+// exactly 10000 basis points.
+pub fn validate_shares(shares: &[Share]) -> Result<(), NftError> {
+    if shares.len() < 2 {
+        return Err(NftError::Invalid(
+            "shared ownership needs at least two shareholders".into(),
+        ));
+    }
+    let mut seen = HashSet::new();
+    for share in shares {
+        if share.address.trim().is_empty() {
+            return Err(NftError::Invalid(
+                "shareholder address must not be empty".into(),
+            ));
+        }
+        if share.bps == 0 {
+            return Err(NftError::Invalid(format!(
+                "share for {} must be above 0 bps",
+                share.address
+            )));
+        }
+        if !seen.insert(share.address.as_str()) {
+            return Err(NftError::Invalid(format!(
+                "{} is listed more than once",
+                share.address
+            )));
+        }
+    }
+    let total: u32 = shares.iter().map(|share| share.bps).sum();
+    if total != u32::from(MAX_BPS) {
+        return Err(NftError::Invalid(format!(
+            "shares must sum to {} bps, got {}",
+            MAX_BPS, total
+        )));
+    }
+    Ok(())
+}
+
+pub fn ownership(state: &AppState, id: &str) -> Result<Ownership, NftError> {
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    Ok(
+        match state
+            .extras
+            .get(id)
+            .and_then(|extras| extras.shares.clone())
+        {
+            Some(shares) => Ownership::Shared(shares),
+            None => Ownership::Sole(nft.owner.clone()),
+        },
+    )
+}
+
+// Checked by every path that moves, bridges or destroys an NFT: a shared one
+// only moves when its holders agree, through `consent_transfer`.
+pub fn ensure_not_shared(state: &AppState, id: &str) -> Result<(), NftError> {
+    if state
+        .extras
+        .get(id)
+        .is_some_and(|extras| extras.shares.is_some())
+    {
+        return Err(NftError::Locked(format!(
+            "NFT {} is jointly owned; its shareholders must consent to a transfer",
+            id
+        )));
+    }
+    Ok(())
+}
+
+// Splits a solely owned NFT among `shares`. Only its owner can do this,
+// signing `share_message`, and each shareholder then finds it under
+// /nfts/by-owner.
+pub fn share_nft(
+    state: &mut AppState,
+    id: &str,
+    shares: Vec<Share>,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    let owner = match ownership(state, id)? {
+        Ownership::Sole(owner) => owner,
+        Ownership::Shared(_) => {
+            return Err(NftError::Invalid(format!(
+                "NFT {} is already jointly owned",
+                id
+            )))
+        }
+    };
+    if owner != caller {
+        return Err(NftError::Forbidden(format!(
+            "only the owner of NFT {} can share it",
+            id
+        )));
+    }
+    verify_signature(
+        caller,
+        &share_message(&state.tenant, id, &shares, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    validate_shares(&shares)?;
+    accept_nonce(state, caller, nonce);
+    set_index(state, id, &shares, &owner, true);
+    let extras = state.extras_mut(id);
+    extras.shares = Some(shares);
+    extras.share_consents.clear();
+    state.approvals.remove(id);
+    state.record(EventKind::Share, id, Some(caller), None);
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConsentStatus {
+    // Basis points of shares that have agreed to send the NFT to `to`.
+    pub consented_bps: u32,
+    // True once that passed half and the NFT moved, now solely owned by `to`.
+    pub transferred: bool,
+}
+
+// Records that shareholder `caller`, signing `consent_message`, agrees to send
+// the NFT to `to`; a later consent from the same holder replaces an earlier one. Once holders of more
+// than half the shares agree on the same `to`, the NFT moves there through
+// `transfer_nft`, becoming solely owned again. If that transfer is refused
+// (frozen, in cooldown, ...) nothing changes, this consent included.
+pub fn consent_transfer(
+    state: &mut AppState,
+    id: &str,
+    to: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<ConsentStatus, NftError> {
+    let shares = match ownership(state, id)? {
+        Ownership::Shared(shares) => shares,
+        Ownership::Sole(_) => {
+            return Err(NftError::Invalid(format!(
+                "NFT {} is not jointly owned",
+                id
+            )))
+        }
+    };
+    if !shares.iter().any(|share| share.address == caller) {
+        return Err(NftError::Forbidden(format!(
+            "{} is not an owner of a share in NFT {}",
+            caller, id
+        )));
+    }
+    verify_signature(
+        caller,
+        &consent_message(&state.tenant, id, to, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    let extras = state.extras_mut(id);
+    let previous = extras
+        .share_consents
+        .insert(caller.to_string(), to.to_string());
+    let consented_bps = agreed_bps(&shares, &extras.share_consents, to);
+    if consented_bps * 2 <= u32::from(MAX_BPS) {
+        accept_nonce(state, caller, nonce);
+        state.record(EventKind::ShareConsent, id, Some(caller), Some(to));
+        return Ok(ConsentStatus {
+            consented_bps,
+            transferred: false,
+        });
+    }
+    // The move itself goes from the registered owner, so index it there.
+    let registered = state
+        .ledger
+        .get_nft(id)
+        .map(|nft| nft.owner.clone())
+        .unwrap_or_default();
+    set_index(state, id, &shares, &registered, false);
+    let consents = std::mem::take(&mut state.extras_mut(id).share_consents);
+    state.extras_mut(id).shares = None;
+    if let Err(err) = transfer_nft(state, id, to) {
+        set_index(state, id, &shares, &registered, true);
+        let extras = state.extras_mut(id);
+        extras.shares = Some(shares);
+        extras.share_consents = consents;
+        match previous {
+            Some(previous) => extras.share_consents.insert(caller.to_string(), previous),
+            None => extras.share_consents.remove(caller),
+        };
+        return Err(err);
+    }
+    accept_nonce(state, caller, nonce);
+    Ok(ConsentStatus {
+        consented_bps,
+        transferred: true,
+    })
+}
+
+fn agreed_bps(shares: &[Share], consents: &BTreeMap<String, String>, to: &str) -> u32 {
+    shares
+        .iter()
+        .filter(|share| consents.get(&share.address).is_some_and(|c| c == to))
+        .map(|share| share.bps)
+        .sum()
+}
+
+// Lists the NFT under its shareholders (`shared`) or its registered owner.
+fn set_index(state: &mut AppState, id: &str, shares: &[Share], registered: &str, shared: bool) {
+    if shared {
+        state.owner_index.remove(registered, id);
+        for share in shares {
+            state.owner_index.insert(&share.address, id);
+        }
+    } else {
+        for share in shares {
+            state.owner_index.remove(&share.address, id);
+        }
+        state.owner_index.insert(registered, id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, Key};
+
+    fn share(address: &str, bps: u32) -> Share {
+        Share {
+            address: address.to_string(),
+            bps,
+        }
+    }
+
+    // Mints to `owner` and splits the NFT between `a` (60%) and `b` (40%).
+    fn shared(state: &mut AppState, owner: &Key, a: &Key, b: &Key) -> String {
+        let id = testutil::mint(state, &owner.address());
+        let shares = vec![share(&a.address(), 6000), share(&b.address(), 4000)];
+        let signature = owner.sign(&share_message("", &id, &shares, 1));
+        share_nft(state, &id, shares, &owner.address(), 1, &signature).unwrap();
+        id
+    }
+
+    fn consent(
+        state: &mut AppState,
+        holder: &Key,
+        id: &str,
+        to: &str,
+        nonce: u64,
+    ) -> ConsentStatus {
+        let signature = holder.sign(&consent_message("", id, to, nonce));
+        consent_transfer(state, id, to, &holder.address(), nonce, &signature).unwrap()
+    }
+
+    #[test]
+    fn shares_must_sum_to_10000_bps() {
+        assert!(validate_shares(&[share("a", 5000), share("b", 5000)]).is_ok());
+        assert!(validate_shares(&[share("a", 5000), share("b", 4000)]).is_err());
+        assert!(validate_shares(&[share("a", 10_000)]).is_err());
+        assert!(validate_shares(&[share("a", 10_000), share("b", 0)]).is_err());
+        assert!(validate_shares(&[share("a", 5000), share("a", 5000)]).is_err());
+        assert!(validate_shares(&[share("", 5000), share("b", 5000)]).is_err());
+    }
+
+    #[test]
+    fn every_shareholder_finds_the_nft_in_the_owner_index() {
+        let (mut state, _) = testutil::state();
+        let (owner, a, b) = (Key::new(1), Key::new(2), Key::new(3));
+        let id = shared(&mut state, &owner, &a, &b);
+        for holder in [&a, &b] {
+            assert!(state
+                .owner_index
+                .ids(&holder.address())
+                .any(|held| *held == id));
+        }
+        assert_eq!(state.owner_index.ids(&owner.address()).count(), 0);
+        assert!(matches!(ownership(&state, &id), Ok(Ownership::Shared(_))));
+    }
+
+    #[test]
+    fn share_needs_the_owner_signature() {
+        let (mut state, _) = testutil::state();
+        let owner = Key::new(1);
+        let id = testutil::mint(&mut state, &owner.address());
+        let shares = vec![share("a", 5000), share("b", 5000)];
+        let forged = Key::new(2).sign(&share_message("", &id, &shares, 1));
+        let err = share_nft(
+            &mut state,
+            &id,
+            shares.clone(),
+            &owner.address(),
+            1,
+            &forged,
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        // Signed for a different split.
+        let other = vec![share("a", 9000), share("b", 1000)];
+        let signature = owner.sign(&share_message("", &id, &other, 1));
+        let err = share_nft(&mut state, &id, shares, &owner.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(matches!(ownership(&state, &id), Ok(Ownership::Sole(_))));
+    }
+
+    #[test]
+    fn majority_consent_moves_the_nft() {
+        let (mut state, _) = testutil::state();
+        let (owner, a, b) = (Key::new(1), Key::new(2), Key::new(3));
+        let id = shared(&mut state, &owner, &a, &b);
+        let status = consent(&mut state, &b, &id, "carol", 1);
+        assert_eq!(status.consented_bps, 4000);
+        assert!(!status.transferred);
+        let status = consent(&mut state, &a, &id, "carol", 1);
+        assert!(status.transferred);
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, "carol");
+        assert!(matches!(ownership(&state, &id), Ok(Ownership::Sole(_))));
+        assert_eq!(state.owner_index.ids(&a.address()).count(), 0);
+    }
+
+    #[test]
+    fn consent_needs_the_holder_signature_and_a_fresh_nonce() {
+        let (mut state, _) = testutil::state();
+        let (owner, a, b) = (Key::new(1), Key::new(2), Key::new(3));
+        let id = shared(&mut state, &owner, &a, &b);
+        let forged = b.sign(&consent_message("", &id, "mallory", 1));
+        let err = consent_transfer(&mut state, &id, "mallory", &a.address(), 1, &forged);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        consent(&mut state, &b, &id, "carol", 1);
+        let replayed = b.sign(&consent_message("", &id, "carol", 1));
+        let err = consent_transfer(&mut state, &id, "carol", &b.address(), 1, &replayed);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        assert!(!state.extras[&id].share_consents.contains_key(&a.address()));
+    }
+}
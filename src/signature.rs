@@ -0,0 +1,175 @@
+use crate::{error::NftError, shares::Share};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+// Every message below puts the tenant it is signed for on its second
+// line, "" for the default namespace. Each tenant keeps its own nonces, so
+// without it a signature made under /t/a would replay under /t/b.
+
+// The bytes an owner signs to authorize moving `id` to `to`. Newline
+// separated so no field can run into the next.
+pub fn transfer_message(tenant: &str, id: &str, to: &str, nonce: u64) -> Vec<u8> {
+    format!("pnft-transfer\n{}\n{}\n{}\n{}", tenant, id, to, nonce).into_bytes()
+}
+
+// The bytes an approved spender signs to move `id` from `from` to `to`.
+pub fn transfer_from_message(tenant: &str, id: &str, from: &str, to: &str, nonce: u64) -> Vec<u8> {
+    format!(
+        "pnft-transfer-from\n{}\n{}\n{}\n{}\n{}",
+        tenant, id, from, to, nonce
+    )
+    .into_bytes()
+}
+
+// The bytes an owner signs to let `spender` move `id`.
+pub fn approve_message(tenant: &str, id: &str, spender: &str, nonce: u64) -> Vec<u8> {
+    format!("pnft-approve\n{}\n{}\n{}\n{}", tenant, id, spender, nonce).into_bytes()
+}
+
+// The bytes an owner signs to lock `id` for an offer to `buyer`.
+pub fn lock_offer_message(
+    tenant: &str,
+    id: &str,
+    buyer: &str,
+    expires_at: u64,
+    nonce: u64,
+) -> Vec<u8> {
+    format!(
+        "pnft-lock-for-offer\n{}\n{}\n{}\n{}\n{}",
+        tenant, id, buyer, expires_at, nonce
+    )
+    .into_bytes()
+}
+
+// The bytes an owner signs to change `id`'s metadata. Each field is
+// JSON-encoded, `null` when left as it is; attributes in the stored form
+// /view returns them in.
+pub fn metadata_patch_message(
+    tenant: &str,
+    id: &str,
+    name: Option<&str>,
+    description: Option<&str>,
+    attributes: Option<&str>,
+    nonce: u64,
+) -> Vec<u8> {
+    let field = |value: Option<&str>| serde_json::to_string(&value).expect("strings serialize");
+    format!(
+        "pnft-update-metadata\n{}\n{}\n{}\n{}\n{}\n{}",
+        tenant,
+        id,
+        nonce,
+        field(name),
+        field(description),
+        field(attributes)
+    )
+    .into_bytes()
+}
+
+// The bytes an owner signs to point `id` at a re-pinned image.
+pub fn repin_message(tenant: &str, id: &str, image_cid: &str, nonce: u64) -> Vec<u8> {
+    format!("pnft-repin\n{}\n{}\n{}\n{}", tenant, id, image_cid, nonce).into_bytes()
+}
+
+// The bytes an owner signs to split `id` among `shares`, one
+// `address:bps` line each, in the order given.
+pub fn share_message(tenant: &str, id: &str, shares: &[Share], nonce: u64) -> Vec<u8> {
+    let lines: Vec<String> = shares
+        .iter()
+        .map(|share| format!("{}:{}", share.address, share.bps))
+        .collect();
+    format!(
+        "pnft-share\n{}\n{}\n{}\n{}",
+        tenant,
+        id,
+        nonce,
+        lines.join("\n")
+    )
+    .into_bytes()
+}
+
+// The bytes a shareholder signs to agree to sending `id` to `to`.
+pub fn consent_message(tenant: &str, id: &str, to: &str, nonce: u64) -> Vec<u8> {
+    format!("pnft-consent\n{}\n{}\n{}\n{}", tenant, id, to, nonce).into_bytes()
+}
+
+// How a call that an admin may also make was authorized.
+#[derive(Clone, Copy)]
+pub enum Auth<'a> {
+    Admin,
+    Owner { nonce: u64, signature: &'a str },
+}
+
+// The bytes signed for an `action` on `id` that takes no other input, such
+// as "accept-offer" or "freeze".
+pub fn action_message(tenant: &str, action: &str, id: &str, nonce: u64) -> Vec<u8> {
+    format!("pnft-{}\n{}\n{}\n{}", action, tenant, id, nonce).into_bytes()
+}
+
+// The bytes an owner signs to authorize moving every id in `ids` to `to`.
+pub fn batch_transfer_message(tenant: &str, ids: &[String], to: &str, nonce: u64) -> Vec<u8> {
+    format!(
+        "pnft-transfer-batch\n{}\n{}\n{}\n{}",
+        tenant,
+        to,
+        nonce,
+        ids.join("\n")
+    )
+    .into_bytes()
+}
+
+// The bytes an owner signs to burn every id in `ids`.
+pub fn burn_batch_message(tenant: &str, ids: &[String], nonce: u64) -> Vec<u8> {
+    format!("pnft-burn-batch\n{}\n{}\n{}", tenant, nonce, ids.join("\n")).into_bytes()
+}
+
+// The bytes an owner signs for an `action` on every id in `ids`, such as
+// "stake" or "unstake".
+pub fn batch_action_message(tenant: &str, action: &str, ids: &[String], nonce: u64) -> Vec<u8> {
+    format!(
+        "pnft-{}-batch\n{}\n{}\n{}",
+        action,
+        tenant,
+        nonce,
+        ids.join("\n")
+    )
+    .into_bytes()
+}
+
+// The bytes an owner signs to move everything it holds to `to`.
+pub fn drain_message(tenant: &str, to: &str, nonce: u64) -> Vec<u8> {
+    format!("pnft-transfer-all\n{}\n{}\n{}", tenant, to, nonce).into_bytes()
+}
+
+// The bytes each party signs to agree to trading `nft_a` (owned by
+// `owner_a`) for `nft_b` (owned by `owner_b`), with their own nonce.
+pub fn swap_message(
+    tenant: &str,
+    nft_a: &str,
+    owner_a: &str,
+    nft_b: &str,
+    owner_b: &str,
+    nonce: u64,
+) -> Vec<u8> {
+    format!(
+        "pnft-swap\n{}\n{}\n{}\n{}\n{}\n{}",
+        tenant, nft_a, owner_a, nft_b, owner_b, nonce
+    )
+    .into_bytes()
+}
+
+// Checks a hex-encoded ed25519 signature against `owner`, which is the
+// hex-encoded 32-byte public key.
+pub fn verify_signature(owner: &str, message: &[u8], signature: &str) -> Result<(), NftError> {
+    let key: [u8; 32] = decode_hex(owner).ok_or_else(|| {
+        NftError::Invalid(format!("owner {} is not an ed25519 public key", owner))
+    })?;
+    let key = VerifyingKey::from_bytes(&key)
+        .map_err(|_| NftError::Invalid(format!("owner {} is not an ed25519 public key", owner)))?;
+    let signature: [u8; 64] = decode_hex(signature)
+        .ok_or_else(|| NftError::Invalid("signature must be 64 hex-encoded bytes".to_string()))?;
+    key.verify(message, &Signature::from_bytes(&signature))
+        .map_err(|_| NftError::Forbidden(format!("signature is not valid for owner {}", owner)))
+}
+
+fn decode_hex<const N: usize>(s: &str) -> Option<[u8; N]> {
+    hex::decode(s).ok()?.try_into().ok()
+}
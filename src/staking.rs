@@ -0,0 +1,467 @@
+use crate::{
+    app::AppState,
+    burn::ensure_not_burned,
+    error::NftError,
+    events::EventKind,
+    nonce::{accept_nonce, check_nonce},
+    signature::{action_message, batch_action_message, verify_signature},
+    transfer::BatchOutcome,
+};
+use penumbra_nft::staking;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const DEFAULT_REWARD_RATE: u64 = 1;
+
+// Everything known about one stake. An NFT has an entry in
+// `AppState::staking` exactly while it is staked; penumbra_nft's `staked`
+// flag is kept in step for the ledger's sake, but this is what the server
+// goes by.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StakeInfo {
+    // Unknown for NFTs staked before it was tracked.
+    pub staked_at: Option<u64>,
+    // Start of the current reward window; each claim moves it to the claim
+    // time. While unknown, nothing accrues.
+    pub accrues_from: Option<u64>,
+    // Unix seconds before which the NFT can't be unstaked. Fixed at stake
+    // time, so claiming doesn't extend it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unstake_after: Option<u64>,
+    // Paid out by claims so far during this stake.
+    #[serde(default)]
+    pub claimed: u64,
+}
+
+pub fn stake_info<'a>(state: &'a AppState, id: &str) -> Option<&'a StakeInfo> {
+    state.staking.get(id)
+}
+
+pub fn is_staked(state: &AppState, id: &str) -> bool {
+    stake_info(state, id).is_some()
+}
+
+// Restaking would restart accrual and lose what was earned, so it's refused.
+pub fn stake_nft(state: &mut AppState, id: &str) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    if state.ledger.get_nft(id).is_none() {
+        return Err(NftError::NotFound(format!("NFT {} not found", id)));
+    }
+    if is_staked(state, id) {
+        return Err(NftError::Conflict(format!("NFT {} is already staked", id)));
+    }
+    staking::stake_nft(&mut state.ledger, id).map_err(NftError::Invalid)?;
+    let now = state.now();
+    let lockup = state.stake_lockup_secs;
+    state.staking.insert(
+        id.to_string(),
+        StakeInfo {
+            staked_at: Some(now),
+            accrues_from: Some(now),
+            unstake_after: (lockup > 0).then(|| now.saturating_add(lockup)),
+            claimed: 0,
+        },
+    );
+    state.record(EventKind::Stake, id, None, None);
+    Ok(())
+}
+
+// Unstaking claims whatever has accrued; the claimed amount is returned.
+pub fn unstake_nft(state: &mut AppState, id: &str) -> Result<u64, NftError> {
+    ensure_lockup_elapsed(state, id)?;
+    remove_stake(state, id, EventKind::Unstake)
+}
+
+// Admin override: unstakes regardless of the lockup, still paying out what
+// has accrued.
+pub fn force_unstake_nft(state: &mut AppState, id: &str) -> Result<u64, NftError> {
+    remove_stake(state, id, EventKind::ForceUnstake)
+}
+
+fn remove_stake(state: &mut AppState, id: &str, kind: EventKind) -> Result<u64, NftError> {
+    if state.ledger.get_nft(id).is_none() {
+        return Err(NftError::NotFound(format!("NFT {} not found", id)));
+    }
+    if !is_staked(state, id) {
+        return Err(NftError::Invalid(format!("NFT {} is not staked", id)));
+    }
+    let claimed = accrued_rewards(state, id, state.now());
+    staking::unstake_nft(&mut state.ledger, id).map_err(NftError::Invalid)?;
+    state.staking.remove(id);
+    state.record(kind, id, None, None);
+    Ok(claimed)
+}
+
+fn ensure_lockup_elapsed(state: &AppState, id: &str) -> Result<(), NftError> {
+    match stake_info(state, id).and_then(|stake| stake.unstake_after) {
+        Some(after) if state.now() < after => Err(NftError::Locked(format!(
+            "NFT {} is still locked until {}",
+            id, after
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnstakeOutcome {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claimed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// Checks one signature by `caller` over the whole batch and uses up its
+// nonce, as /burn/batch does.
+fn authorize_batch(
+    state: &mut AppState,
+    action: &str,
+    ids: &[String],
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    if ids.is_empty() {
+        return Err(NftError::Invalid(format!(
+            "batch {} needs at least one id",
+            action
+        )));
+    }
+    verify_signature(
+        caller,
+        &batch_action_message(&state.tenant, action, ids, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)?;
+    accept_nonce(state, caller, nonce);
+    Ok(())
+}
+
+fn ensure_owner(state: &AppState, id: &str, caller: &str) -> Result<(), NftError> {
+    match state.ledger.get_nft(id) {
+        None => Err(NftError::NotFound(format!("NFT {} not found", id))),
+        Some(nft) if nft.owner != caller => Err(NftError::Forbidden(format!(
+            "{} is not the owner of NFT {}",
+            caller, id
+        ))),
+        Some(_) => Ok(()),
+    }
+}
+
+// Best effort, authorized by one signature by `caller` over the list: each
+// id is staked or fails on its own (not owned, already staked, burned, ...)
+// without affecting the rest.
+pub fn stake_nft_batch(
+    state: &mut AppState,
+    ids: &[String],
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<BTreeMap<String, BatchOutcome>, NftError> {
+    authorize_batch(state, "stake", ids, caller, nonce, signature)?;
+    let mut results = BTreeMap::new();
+    for id in ids {
+        let error = ensure_owner(state, id, caller)
+            .and_then(|()| stake_nft(state, id))
+            .err()
+            .map(|err| err.to_string());
+        let outcome = BatchOutcome {
+            ok: error.is_none(),
+            error,
+        };
+        results.insert(id.clone(), outcome);
+    }
+    Ok(results)
+}
+
+// Like `stake_nft_batch`; ids still inside their lockup are reported and
+// left staked.
+pub fn unstake_nft_batch(
+    state: &mut AppState,
+    ids: &[String],
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<BTreeMap<String, UnstakeOutcome>, NftError> {
+    authorize_batch(state, "unstake", ids, caller, nonce, signature)?;
+    let mut results = BTreeMap::new();
+    for id in ids {
+        let outcome = match ensure_owner(state, id, caller).and_then(|()| unstake_nft(state, id)) {
+            Ok(claimed) => UnstakeOutcome {
+                ok: true,
+                claimed: Some(claimed),
+                error: None,
+            },
+            Err(err) => UnstakeOutcome {
+                ok: false,
+                claimed: None,
+                error: Some(err.to_string()),
+            },
+        };
+        results.insert(id.clone(), outcome);
+    }
+    Ok(results)
+}
+
+// Rewards earned since the NFT was staked or last claimed.
+pub fn accrued_rewards(state: &AppState, id: &str, now: u64) -> u64 {
+    stake_info(state, id)
+        .and_then(|stake| stake.accrues_from)
+        .map(|since| now.saturating_sub(since).saturating_mul(state.reward_rate))
+        .unwrap_or(0)
+}
+
+// Pays out the accrued rewards and restarts accrual from `now`.
+pub fn claim_rewards(state: &mut AppState, id: &str) -> Result<u64, NftError> {
+    if state.ledger.get_nft(id).is_none() {
+        return Err(NftError::NotFound(format!("NFT {} not found", id)));
+    }
+    let now = state.now();
+    let claimed = accrued_rewards(state, id, now);
+    let stake = state
+        .staking
+        .get_mut(id)
+        .ok_or_else(|| NftError::Invalid(format!("NFT {} is not staked", id)))?;
+    stake.accrues_from = Some(now);
+    stake.claimed = stake.claimed.saturating_add(claimed);
+    state.record(EventKind::Claim, id, None, None);
+    Ok(claimed)
+}
+
+// `stake_nft`, `unstake_nft` and `claim_rewards` for their HTTP routes: the
+// owner signs `action_message` with the action's name.
+pub fn stake_signed(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    authorize(state, "stake", id, caller, nonce, signature)?;
+    stake_nft(state, id)?;
+    accept_nonce(state, caller, nonce);
+    Ok(())
+}
+
+pub fn unstake_signed(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<u64, NftError> {
+    authorize(state, "unstake", id, caller, nonce, signature)?;
+    let claimed = unstake_nft(state, id)?;
+    accept_nonce(state, caller, nonce);
+    Ok(claimed)
+}
+
+pub fn claim_signed(
+    state: &mut AppState,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<u64, NftError> {
+    authorize(state, "claim", id, caller, nonce, signature)?;
+    let claimed = claim_rewards(state, id)?;
+    accept_nonce(state, caller, nonce);
+    Ok(claimed)
+}
+
+fn authorize(
+    state: &AppState,
+    action: &str,
+    id: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    let nft = state
+        .ledger
+        .get_nft(id)
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if nft.owner != caller {
+        return Err(NftError::Forbidden(format!(
+            "only the owner of NFT {} can {} it",
+            id, action
+        )));
+    }
+    verify_signature(
+        caller,
+        &action_message(&state.tenant, action, id, nonce),
+        signature,
+    )?;
+    check_nonce(state, caller, nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, Key};
+
+    #[test]
+    fn stake_inserts_an_entry() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        stake_nft(&mut state, &id).unwrap();
+        assert_eq!(state.staking[&id].staked_at, Some(testutil::START));
+        assert!(state.ledger.get_nft(&id).unwrap().staked);
+    }
+
+    #[test]
+    fn unstake_removes_it_and_pays_out() {
+        let (mut state, clock) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        stake_nft(&mut state, &id).unwrap();
+        clock.advance(10);
+        assert_eq!(
+            unstake_nft(&mut state, &id).unwrap(),
+            10 * DEFAULT_REWARD_RATE
+        );
+        assert!(!state.staking.contains_key(&id));
+        assert!(!state.ledger.get_nft(&id).unwrap().staked);
+    }
+
+    #[test]
+    fn claim_pays_the_accrual_and_resets_it() {
+        let (mut state, clock) = testutil::state();
+        state.reward_rate = 3;
+        let id = testutil::mint(&mut state, "alice");
+        stake_nft(&mut state, &id).unwrap();
+        clock.advance(100);
+        assert_eq!(accrued_rewards(&state, &id, state.now()), 300);
+        assert_eq!(claim_rewards(&mut state, &id).unwrap(), 300);
+        assert_eq!(accrued_rewards(&state, &id, state.now()), 0);
+        assert_eq!(state.staking[&id].claimed, 300);
+        clock.advance(5);
+        assert_eq!(claim_rewards(&mut state, &id).unwrap(), 15);
+    }
+
+    #[test]
+    fn staking_someone_elses_nft_is_forbidden() {
+        let (mut state, _) = testutil::state();
+        let owner = Key::new(1);
+        let stranger = Key::new(2);
+        let id = testutil::mint(&mut state, &owner.address());
+        let sign = |key: &Key, action: &str| key.sign(&action_message("", action, &id, 1));
+
+        let err = stake_signed(
+            &mut state,
+            &id,
+            &stranger.address(),
+            1,
+            &sign(&stranger, "stake"),
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(!state.ledger.get_nft(&id).unwrap().staked);
+        let signature = sign(&owner, "stake");
+        stake_signed(&mut state, &id, &owner.address(), 1, &signature).unwrap();
+
+        let err = claim_signed(
+            &mut state,
+            &id,
+            &stranger.address(),
+            1,
+            &sign(&stranger, "claim"),
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        let signature = sign(&stranger, "unstake");
+        let err = unstake_signed(&mut state, &id, &stranger.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(is_staked(&state, &id));
+    }
+
+    #[test]
+    fn double_stake_is_rejected() {
+        let (mut state, clock) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        stake_nft(&mut state, &id).unwrap();
+        clock.advance(10);
+        let err = stake_nft(&mut state, &id);
+        assert!(matches!(err, Err(NftError::Conflict(_))));
+        assert_eq!(state.staking[&id].accrues_from, Some(testutil::START));
+    }
+
+    #[test]
+    fn unstake_waits_out_the_lockup() {
+        let (mut state, clock) = testutil::state();
+        state.stake_lockup_secs = 100;
+        let id = testutil::mint(&mut state, "alice");
+        stake_nft(&mut state, &id).unwrap();
+        clock.advance(99);
+        let err = unstake_nft(&mut state, &id);
+        assert!(matches!(err, Err(NftError::Locked(_))));
+        assert!(is_staked(&state, &id));
+        // Claiming during the lockup doesn't push it back.
+        claim_rewards(&mut state, &id).unwrap();
+        clock.advance(1);
+        unstake_nft(&mut state, &id).unwrap();
+        assert!(!is_staked(&state, &id));
+    }
+
+    #[test]
+    fn a_stranger_cant_start_a_lockup() {
+        let (mut state, _) = testutil::state();
+        state.stake_lockup_secs = 3600;
+        let owner = Key::new(1);
+        let stranger = Key::new(2);
+        let id = testutil::mint(&mut state, &owner.address());
+        let signature = stranger.sign(&action_message("", "stake", &id, 1));
+        let err = stake_signed(&mut state, &id, &stranger.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(stake_info(&state, &id).is_none());
+        crate::transfer::transfer_nft(&mut state, &id, "bob").unwrap();
+    }
+
+    #[test]
+    fn batch_reports_already_staked_ids_and_stakes_the_rest() {
+        let (mut state, clock) = testutil::state();
+        state.stake_lockup_secs = 50;
+        let alice = Key::new(1);
+        let staked = testutil::mint(&mut state, &alice.address());
+        let fresh = testutil::mint(&mut state, &alice.address());
+        let bobs = testutil::mint(&mut state, "bob");
+        stake_nft(&mut state, &staked).unwrap();
+        clock.advance(50);
+
+        let ids = vec![
+            staked.clone(),
+            fresh.clone(),
+            bobs.clone(),
+            "missing".to_string(),
+        ];
+        let signature = alice.sign(&batch_action_message("", "stake", &ids, 1));
+        let results = stake_nft_batch(&mut state, &ids, &alice.address(), 1, &signature).unwrap();
+        assert!(!results[&staked].ok);
+        assert!(results[&staked]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("already staked"));
+        assert!(results[&fresh].ok);
+        assert!(!results["missing"].ok);
+        assert!(results[&bobs]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("not the owner"));
+        assert!(!is_staked(&state, &bobs));
+        assert_eq!(state.staking[&staked].accrues_from, Some(testutil::START));
+        // The nonce is spent.
+        let err = stake_nft_batch(&mut state, &ids, &alice.address(), 1, &signature);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        let signature = alice.sign(&batch_action_message("", "unstake", &ids, 2));
+        let err = stake_nft_batch(&mut state, &ids, &alice.address(), 2, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+
+        // `fresh` is still inside the lockup it just started.
+        let ids = [staked.clone(), fresh.clone()];
+        let signature = alice.sign(&batch_action_message("", "unstake", &ids, 2));
+        let results = unstake_nft_batch(&mut state, &ids, &alice.address(), 2, &signature).unwrap();
+        assert_eq!(results[&staked].claimed, Some(50 * DEFAULT_REWARD_RATE));
+        assert!(!results[&fresh].ok);
+        assert!(is_staked(&state, &fresh));
+        assert!(!is_staked(&state, &staked));
+    }
+}
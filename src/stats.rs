@@ -0,0 +1,85 @@
+use crate::{app::AppState, burn::is_burned, staking::is_staked};
+use std::collections::HashSet;
+
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+pub struct StateStats {
+    pub total: usize,
+    // Tombstones; not counted in any of the other fields.
+    pub burned: usize,
+    pub staked: usize,
+    pub frozen: usize,
+    pub shielded: usize,
+    pub public: usize,
+    pub owners: usize,
+    pub collections: usize,
+}
+
+// One pass over the ledger; flags kept in extras are looked up per NFT.
+pub fn state_stats(state: &AppState) -> StateStats {
+    let mut stats = StateStats {
+        collections: state.collections.len(),
+        ..Default::default()
+    };
+    let mut owners = HashSet::new();
+    for nft in state.ledger.nfts.values() {
+        if is_burned(state, &nft.id) {
+            stats.burned += 1;
+            continue;
+        }
+        stats.total += 1;
+        if is_staked(state, &nft.id) {
+            stats.staked += 1;
+        }
+        if state
+            .extras
+            .get(&nft.id)
+            .is_some_and(|extras| extras.frozen)
+        {
+            stats.frozen += 1;
+        }
+        if nft.metadata.shielded {
+            stats.shielded += 1;
+        } else {
+            stats.public += 1;
+        }
+        owners.insert(nft.owner.as_str());
+    }
+    stats.owners = owners.len();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{burn::burn_nft, mint::mint_nft, staking::stake_nft, testutil};
+
+    #[test]
+    fn counts_a_known_state() {
+        let (mut state, _) = testutil::state();
+        testutil::mint(&mut state, "alice");
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        item.extras.collection = Some("apes".to_string());
+        mint_nft(&mut state, "alice".to_string(), item).unwrap();
+        let staked = testutil::mint(&mut state, "bob");
+        stake_nft(&mut state, &staked).unwrap();
+        let frozen = testutil::mint(&mut state, "carol");
+        state.extras_mut(&frozen).frozen = true;
+        let burned = testutil::mint(&mut state, "dave");
+        burn_nft(&mut state, &burned).unwrap();
+
+        assert_eq!(
+            state_stats(&state),
+            StateStats {
+                total: 4,
+                burned: 1,
+                staked: 1,
+                frozen: 1,
+                shielded: 1,
+                public: 3,
+                owners: 3,
+                collections: 1,
+            }
+        );
+    }
+}
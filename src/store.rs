@@ -0,0 +1,189 @@
+use penumbra_nft::types::NFT;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, io, path::Path, sync::Mutex};
+
+// One NFT as a store keeps it: the ledger entry with this crate's extras.
+// The extras stay as stored until the whole document is migrated, so a
+// step can still see fields `NftExtras` no longer has.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NftRecord {
+    pub nft: NFT,
+    #[serde(default)]
+    pub extras: Value,
+}
+
+// Keyed storage for NFT records, plus one opaque blob for the rest of the
+// state. Lets a backend write only the NFTs that changed instead of
+// rewriting everything on each save.
+pub trait StateStore: Send + Sync {
+    fn get(&self, id: &str) -> io::Result<Option<NftRecord>>;
+    fn insert(&self, id: &str, record: &NftRecord) -> io::Result<()>;
+    fn remove(&self, id: &str) -> io::Result<()>;
+    fn iter(&self) -> io::Result<Vec<(String, NftRecord)>>;
+    fn load_meta(&self) -> io::Result<Option<Vec<u8>>>;
+    fn save_meta(&self, meta: &[u8]) -> io::Result<()>;
+    // Makes everything written so far durable.
+    fn flush(&self) -> io::Result<()>;
+}
+
+// Keeps records in a HashMap; nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryStore {
+    records: Mutex<HashMap<String, NftRecord>>,
+    meta: Mutex<Option<Vec<u8>>>,
+}
+
+impl StateStore for MemoryStore {
+    fn get(&self, id: &str) -> io::Result<Option<NftRecord>> {
+        Ok(lock(&self.records).get(id).cloned())
+    }
+
+    fn insert(&self, id: &str, record: &NftRecord) -> io::Result<()> {
+        lock(&self.records).insert(id.to_string(), record.clone());
+        Ok(())
+    }
+
+    fn remove(&self, id: &str) -> io::Result<()> {
+        lock(&self.records).remove(id);
+        Ok(())
+    }
+
+    fn iter(&self) -> io::Result<Vec<(String, NftRecord)>> {
+        Ok(lock(&self.records)
+            .iter()
+            .map(|(id, record)| (id.clone(), record.clone()))
+            .collect())
+    }
+
+    fn load_meta(&self) -> io::Result<Option<Vec<u8>>> {
+        Ok(lock(&self.meta).clone())
+    }
+
+    fn save_meta(&self, meta: &[u8]) -> io::Result<()> {
+        *lock(&self.meta) = Some(meta.to_vec());
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Records as JSON values in a sled tree, one key per NFT id.
+pub struct SledStore {
+    db: sled::Db,
+    nfts: sled::Tree,
+}
+
+const META_KEY: &[u8] = b"meta";
+
+impl SledStore {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let db = sled::open(path).map_err(io::Error::other)?;
+        let nfts = db.open_tree("nfts").map_err(io::Error::other)?;
+        Ok(SledStore { db, nfts })
+    }
+}
+
+impl StateStore for SledStore {
+    fn get(&self, id: &str) -> io::Result<Option<NftRecord>> {
+        match self.nfts.get(id).map_err(io::Error::other)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn insert(&self, id: &str, record: &NftRecord) -> io::Result<()> {
+        self.nfts
+            .insert(id, serde_json::to_vec(record)?)
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn remove(&self, id: &str) -> io::Result<()> {
+        self.nfts.remove(id).map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn iter(&self) -> io::Result<Vec<(String, NftRecord)>> {
+        self.nfts
+            .iter()
+            .map(|entry| {
+                let (key, value) = entry.map_err(io::Error::other)?;
+                let id = String::from_utf8(key.to_vec()).map_err(io::Error::other)?;
+                Ok((id, serde_json::from_slice(&value)?))
+            })
+            .collect()
+    }
+
+    fn load_meta(&self) -> io::Result<Option<Vec<u8>>> {
+        Ok(self
+            .db
+            .get(META_KEY)
+            .map_err(io::Error::other)?
+            .map(|bytes| bytes.to_vec()))
+    }
+
+    fn save_meta(&self, meta: &[u8]) -> io::Result<()> {
+        self.db.insert(META_KEY, meta).map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.db.flush().map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    fn record(owner: &str) -> NftRecord {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, owner);
+        NftRecord {
+            nft: state.ledger.get_nft(&id).unwrap().clone(),
+            extras: serde_json::json!({ "frozen": false }),
+        }
+    }
+
+    // The same round trips, whichever backend is under them.
+    fn crud(store: &dyn StateStore) {
+        let (alice, bob) = (record("alice"), record("bob"));
+        assert_eq!(store.get("a").unwrap(), None);
+        store.insert("a", &alice).unwrap();
+        store.insert("b", &bob).unwrap();
+        assert_eq!(store.get("a").unwrap(), Some(alice.clone()));
+
+        store.insert("a", &bob).unwrap();
+        assert_eq!(store.get("a").unwrap(), Some(bob.clone()));
+
+        store.remove("a").unwrap();
+        assert_eq!(store.get("a").unwrap(), None);
+        assert_eq!(store.iter().unwrap(), vec![("b".to_string(), bob)]);
+
+        assert_eq!(store.load_meta().unwrap(), None);
+        store.save_meta(b"meta").unwrap();
+        store.flush().unwrap();
+        assert_eq!(store.load_meta().unwrap().as_deref(), Some(&b"meta"[..]));
+    }
+
+    #[test]
+    fn memory_store_round_trips() {
+        crud(&MemoryStore::default());
+    }
+
+    #[test]
+    fn sled_store_round_trips() {
+        let path = std::env::temp_dir().join(format!("pnft-sled-{}", std::process::id()));
+        crud(&SledStore::open(&path).unwrap());
+        let _ = std::fs::remove_dir_all(&path);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{
+    app::AppState,
+    error::NftError,
+    nonce::{accept_nonce, check_nonce},
+    signature::{swap_message, verify_signature},
+    transfer::transfer_nft,
+    tx::atomically,
+};
+
+// One party's half of a swap: the NFT they give up and their signature over
+// the whole trade.
+pub struct SwapSide {
+    pub nft: String,
+    pub owner: String,
+    pub nonce: u64,
+    pub signature: String,
+}
+
+// Trades `a.nft` to `b.owner` and `b.nft` to `a.owner` in one step. Both
+// owners must hold their NFT and have signed the same terms; if either
+// transfer fails, neither applies.
+pub fn swap_nfts(state: &mut AppState, a: &SwapSide, b: &SwapSide) -> Result<(), NftError> {
+    if a.nft == b.nft {
+        return Err(NftError::Invalid("cannot swap an NFT for itself".into()));
+    }
+    if a.owner == b.owner {
+        return Err(NftError::Invalid(
+            "a swap needs two different owners".into(),
+        ));
+    }
+    for side in [a, b] {
+        let owner = state
+            .ledger
+            .get_nft(&side.nft)
+            .map(|nft| nft.owner.as_str())
+            .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", side.nft)))?;
+        if owner != side.owner {
+            return Err(NftError::Forbidden(format!(
+                "{} is not the owner of NFT {}",
+                side.owner, side.nft
+            )));
+        }
+        let message = swap_message(
+            &state.tenant,
+            &a.nft,
+            &a.owner,
+            &b.nft,
+            &b.owner,
+            side.nonce,
+        );
+        verify_signature(&side.owner, &message, &side.signature)?;
+        check_nonce(state, &side.owner, side.nonce)?;
+    }
+    atomically(state, |state, snapshot| {
+        for side in [a, b] {
+            snapshot.touch(state, &side.nft);
+            snapshot.touch_nonce(state, &side.owner);
+        }
+        transfer_nft(state, &a.nft, &b.owner)?;
+        transfer_nft(state, &b.nft, &a.owner)?;
+        accept_nonce(state, &a.owner, a.nonce);
+        accept_nonce(state, &b.owner, b.nonce);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, Key};
+
+    struct Trade {
+        alice: Key,
+        bob: Key,
+        a_nft: String,
+        b_nft: String,
+    }
+
+    fn trade(state: &mut AppState) -> Trade {
+        let (alice, bob) = (Key::new(1), Key::new(2));
+        let a_nft = testutil::mint(state, &alice.address());
+        let b_nft = testutil::mint(state, &bob.address());
+        Trade {
+            alice,
+            bob,
+            a_nft,
+            b_nft,
+        }
+    }
+
+    fn side(trade: &Trade, key: &Key, nft: &str) -> SwapSide {
+        let message = swap_message(
+            "",
+            &trade.a_nft,
+            &trade.alice.address(),
+            &trade.b_nft,
+            &trade.bob.address(),
+            1,
+        );
+        SwapSide {
+            nft: nft.to_string(),
+            owner: key.address(),
+            nonce: 1,
+            signature: key.sign(&message),
+        }
+    }
+
+    fn owners(state: &AppState, trade: &Trade) -> (String, String) {
+        let owner = |id: &str| state.ledger.get_nft(id).unwrap().owner.clone();
+        (owner(&trade.a_nft), owner(&trade.b_nft))
+    }
+
+    #[test]
+    fn signed_swap_trades_both_nfts() {
+        let (mut state, _) = testutil::state();
+        let trade = trade(&mut state);
+        let a = side(&trade, &trade.alice, &trade.a_nft);
+        let b = side(&trade, &trade.bob, &trade.b_nft);
+        swap_nfts(&mut state, &a, &b).unwrap();
+        assert_eq!(
+            owners(&state, &trade),
+            (trade.bob.address(), trade.alice.address())
+        );
+        assert_eq!(state.nonces[&trade.alice.address()], 1);
+        assert_eq!(state.nonces[&trade.bob.address()], 1);
+    }
+
+    #[test]
+    fn one_bad_signature_aborts_both() {
+        let (mut state, _) = testutil::state();
+        let trade = trade(&mut state);
+        let a = side(&trade, &trade.alice, &trade.a_nft);
+        let mut b = side(&trade, &trade.bob, &trade.b_nft);
+        b.signature = side(&trade, &Key::new(3), &trade.b_nft).signature;
+        let err = swap_nfts(&mut state, &a, &b);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(
+            owners(&state, &trade),
+            (trade.alice.address(), trade.bob.address())
+        );
+        assert!(state.nonces.is_empty());
+    }
+
+    #[test]
+    fn not_owned_aborts_both() {
+        let (mut state, _) = testutil::state();
+        let mut trade = trade(&mut state);
+        // Both sign, but Alice offers an NFT that's really Carol's.
+        trade.a_nft = testutil::mint(&mut state, "carol");
+        let a = side(&trade, &trade.alice, &trade.a_nft);
+        let b = side(&trade, &trade.bob, &trade.b_nft);
+        let err = swap_nfts(&mut state, &a, &b);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(
+            owners(&state, &trade),
+            ("carol".to_string(), trade.bob.address())
+        );
+        assert!(state.nonces.is_empty());
+    }
+
+    #[test]
+    fn failed_second_transfer_rolls_back_the_first() {
+        let (mut state, _) = testutil::state();
+        let trade = trade(&mut state);
+        state.extras_mut(&trade.b_nft).frozen = true;
+        let a = side(&trade, &trade.alice, &trade.a_nft);
+        let b = side(&trade, &trade.bob, &trade.b_nft);
+        let err = swap_nfts(&mut state, &a, &b);
+        assert!(matches!(err, Err(NftError::Locked(_))));
+        assert_eq!(
+            owners(&state, &trade),
+            (trade.alice.address(), trade.bob.address())
+        );
+        assert!(state.nonces.is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{app::AppState, events::EventKind, reservation::reclaim_expired, SharedState};
+use std::time::Duration;
+
+// What one sweep released.
+#[derive(Debug, Default, PartialEq)]
+pub struct SweepReport {
+    pub reservations: usize,
+    pub offers: usize,
+}
+
+// Releases reservations and offer locks whose time, by the state's clock, has
+// run out. Both already stop counting once expired; this drops them so supply
+// and the NFT are free without waiting for the next access. Each released lock
+// is recorded as an OfferExpire event, each reservation as a
+// ReservationExpire.
+pub fn sweep(state: &mut AppState) -> SweepReport {
+    let reservations = reclaim_expired(state);
+    let now = state.now();
+    let mut expired: Vec<(String, String)> = state
+        .extras
+        .iter()
+        .filter_map(|(id, extras)| {
+            let lock = extras.offer.as_ref()?;
+            (lock.expires_at <= now).then(|| (id.clone(), lock.buyer.clone()))
+        })
+        .collect();
+    // Events land in a stable order.
+    expired.sort();
+    for (id, buyer) in &expired {
+        state.extras_mut(id).offer = None;
+        state.record(EventKind::OfferExpire, id, None, Some(buyer));
+    }
+    SweepReport {
+        reservations,
+        offers: expired.len(),
+    }
+}
+
+// Sweeps every `interval` for as long as the server runs. The write lock is
+// held only for the sweep and save, like any handler's, and never across the
+// wait.
+pub async fn run(state: SharedState, interval: Duration) {
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick completes immediately.
+    ticks.tick().await;
+    loop {
+        ticks.tick().await;
+        let mut state = state.write().await;
+        let report = sweep(&mut state);
+        if report == SweepReport::default() {
+            continue;
+        }
+        tracing::info!(
+            reservations = report.reservations,
+            offers = report.offers,
+            "swept expired holds"
+        );
+        if let Some(backend) = state.backend {
+            if let Err(err) = backend.save(&mut state) {
+                tracing::error!("Failed to persist swept state: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mint::mint_nft,
+        offer::{active_offer, lock_for_offer},
+        reservation::{reserve_mint, reserved_count},
+        signature::lock_offer_message,
+        testutil::{self, Key, START},
+    };
+
+    #[test]
+    fn sweep_releases_expired_reservations_and_offer_locks() {
+        let (mut state, clock) = testutil::state();
+        let owner = Key::new(1);
+        let buyer = Key::new(2);
+        let mut item = testutil::item("member");
+        item.extras.collection = Some("apes".to_string());
+        item.max_supply = Some(2);
+        let id = mint_nft(&mut state, owner.address(), item.clone()).unwrap();
+        item.max_supply = None;
+        let (reservation, reservation_expires) = reserve_mint(&mut state, "apes").unwrap();
+        let offer_expires = START + 3600;
+        let message = lock_offer_message("", &id, &buyer.address(), offer_expires, 1);
+        lock_for_offer(
+            &mut state,
+            &id,
+            &buyer.address(),
+            offer_expires,
+            &owner.address(),
+            1,
+            &owner.sign(&message),
+        )
+        .unwrap();
+
+        assert_eq!(sweep(&mut state), SweepReport::default());
+        assert_eq!(state.reservations.len(), 1);
+        assert!(active_offer(&state, &id).is_some());
+
+        clock.set(reservation_expires.max(offer_expires));
+        assert_eq!(
+            sweep(&mut state),
+            SweepReport {
+                reservations: 1,
+                offers: 1
+            }
+        );
+        assert!(state.reservations.is_empty());
+        assert_eq!(reserved_count(&state, "apes"), 0);
+        assert!(state.extras[&id].offer.is_none());
+        let events = state.events.since(0);
+        let [.., released, unlocked] = events else {
+            panic!("expected the sweep's events");
+        };
+        assert_eq!(released.kind, EventKind::ReservationExpire);
+        assert_eq!(released.nft_id, reservation);
+        assert_eq!(released.to.as_deref(), Some("apes"));
+        assert_eq!(unlocked.kind, EventKind::OfferExpire);
+        assert_eq!(unlocked.nft_id, id);
+        assert_eq!(unlocked.to.as_deref(), Some(buyer.address().as_str()));
+        mint_nft(&mut state, "carol".to_string(), item).unwrap();
+        assert_eq!(sweep(&mut state), SweepReport::default());
+    }
+}
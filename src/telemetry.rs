@@ -0,0 +1,45 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::time::Instant;
+
+pub const MINTS: &str = "pnft_mints_total";
+pub const TRANSFERS: &str = "pnft_transfers_total";
+pub const BURNS: &str = "pnft_burns_total";
+pub const NFTS: &str = "pnft_nfts";
+const REQUEST_DURATION: &str = "pnft_http_request_duration_seconds";
+
+const LATENCY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+// Installs the global recorder. Until this runs (e.g. under the CLI), the
+// metric macros are no-ops.
+pub fn install() -> Result<PrometheusHandle, String> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Full(REQUEST_DURATION.into()), LATENCY_BUCKETS)
+        .and_then(|builder| builder.install_recorder())
+        .map_err(|e| format!("failed to install metrics recorder: {}", e))
+}
+
+// Records request latency by method, route pattern and status.
+pub async fn track(req: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = req.method().to_string();
+    // The route pattern, not the raw path, so NFT ids don't explode cardinality.
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".into());
+    let response = next.run(req).await;
+    metrics::histogram!(
+        REQUEST_DURATION,
+        "method" => method,
+        "route" => route,
+        "status" => response.status().as_u16().to_string(),
+    )
+    .record(start.elapsed().as_secs_f64());
+    response
+}
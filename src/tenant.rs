@@ -0,0 +1,43 @@
+use std::path::{Path, PathBuf};
+
+// One isolated NFT namespace served under `/t/<name>/`. Its state is a whole
+// separate AppState with its own storage, so ids, owners and collections
+// never meet those of another tenant or of the unprefixed default routes.
+#[derive(Clone, Debug)]
+pub struct TenantSpec {
+    pub name: String,
+    // Falls back to --admin-key when unset.
+    pub admin_key: Option<String>,
+}
+
+// `<name>` or `<name>=<admin key>`, as given to --tenant.
+pub fn parse_tenant(spec: &str) -> Result<TenantSpec, String> {
+    let (name, admin_key) = match spec.split_once('=') {
+        Some((name, "")) => return Err(format!("tenant {} has an empty admin key", name)),
+        Some((name, key)) => (name, Some(key.to_string())),
+        None => (spec, None),
+    };
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if name.is_empty() || !valid {
+        return Err(format!(
+            "tenant name {:?} must be lowercase letters, digits, '-' or '_'",
+            name
+        ));
+    }
+    Ok(TenantSpec {
+        name: name.to_string(),
+        admin_key,
+    })
+}
+
+// `state.json` -> `state.<tenant>.json`, and likewise for the sled directory.
+pub fn tenant_path(path: &Path, tenant: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, tenant, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, tenant),
+    };
+    path.with_file_name(name)
+}
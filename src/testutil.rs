@@ -0,0 +1,59 @@
+// Fixtures shared by the unit tests.
+
+use crate::{
+    app::AppState,
+    clock::MockClock,
+    extras::NftExtras,
+    mint::{mint_nft, MintItem, MintOptions},
+};
+use ed25519_dalek::{Signer as _, SigningKey};
+use penumbra_nft::types::NFTMetadata;
+use std::sync::Arc;
+
+pub const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+pub const START: u64 = 1_700_000_000;
+
+// A fresh state whose clock only moves when the test moves it.
+pub fn state() -> (AppState, Arc<MockClock>) {
+    let clock = Arc::new(MockClock::new(START));
+    let mut state = AppState::new();
+    state.clock = clock.clone();
+    (state, clock)
+}
+
+pub fn item(name: &str) -> MintItem {
+    MintItem {
+        metadata: NFTMetadata {
+            name: name.to_string(),
+            description: "test".to_string(),
+            image_cid: CID.to_string(),
+            attributes: "[]".to_string(),
+            shielded: false,
+        },
+        extras: NftExtras::default(),
+        max_supply: None,
+        transfer_cooldown_secs: None,
+        options: MintOptions::default(),
+    }
+}
+
+pub fn mint(state: &mut AppState, owner: &str) -> String {
+    mint_nft(state, owner.to_string(), item("test")).expect("mint")
+}
+
+// An ed25519 key whose address is its hex public key, as owners' are.
+pub struct Key(SigningKey);
+
+impl Key {
+    pub fn new(seed: u8) -> Self {
+        Key(SigningKey::from_bytes(&[seed; 32]))
+    }
+
+    pub fn address(&self) -> String {
+        hex::encode(self.0.verifying_key().to_bytes())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> String {
+        hex::encode(self.0.sign(message).to_bytes())
+    }
+}
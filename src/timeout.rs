@@ -0,0 +1,72 @@
+use crate::error::NftError;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::time::Duration;
+
+// Answers 504 once a request has run for longer than the deadline, dropping
+// the handler. Handlers only await before they take the state lock or after
+// they have finished with it, so a dropped handler releases the lock as it
+// unwinds and never leaves a change half applied. Streaming bodies and
+// WebSockets run after the handler returns and aren't cut off.
+pub async fn enforce(
+    State(deadline): State<Option<Duration>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(deadline) = deadline else {
+        return next.run(req).await;
+    };
+    match tokio::time::timeout(deadline, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!("Request timed out after {:?}", deadline);
+            NftError::Timeout(format!("request did not finish within {:?}", deadline))
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware, routing::get, Router};
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+    use tower::ServiceExt;
+
+    fn app(deadline: Option<Duration>, lock: Arc<RwLock<()>>) -> Router {
+        Router::new()
+            .route(
+                "/slow",
+                get(move || async move {
+                    let _guard = lock.write().await;
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                }),
+            )
+            .route("/fast", get(|| async {}))
+            .route_layer(middleware::from_fn_with_state(deadline, enforce))
+    }
+
+    async fn status(app: &Router, uri: &str) -> StatusCode {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn slow_handler_gets_504_and_releases_the_lock() {
+        let lock = Arc::new(RwLock::new(()));
+        let app = app(Some(Duration::from_millis(20)), lock.clone());
+        assert_eq!(status(&app, "/slow").await, StatusCode::GATEWAY_TIMEOUT);
+        assert!(lock.try_write().is_ok());
+        assert_eq!(status(&app, "/fast").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn no_deadline_lets_requests_run() {
+        let app = app(None, Arc::default());
+        assert_eq!(status(&app, "/fast").await, StatusCode::OK);
+    }
+}
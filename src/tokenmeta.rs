@@ -0,0 +1,100 @@
+use crate::{
+    app::AppState,
+    attributes::{decode_attributes, Attribute},
+    gateway::Gateway,
+    reveal::{reveal_view, NftView},
+};
+use serde::Serialize;
+
+// The ERC-721 metadata JSON marketplaces fetch from a tokenURI.
+#[derive(Debug, Serialize)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    pub attributes: Vec<Attribute>,
+}
+
+impl TokenMetadata {
+    // Served for shielded NFTs the caller can't see, so the document still
+    // has the expected shape without giving anything away.
+    fn placeholder() -> Self {
+        TokenMetadata {
+            name: "Shielded NFT".into(),
+            description: "This NFT is shielded; its metadata is only shown with a viewing key."
+                .into(),
+            image: String::new(),
+            attributes: Vec::new(),
+        }
+    }
+}
+
+// None when the NFT doesn't exist. `image` is an `ipfs://` URI unless a
+// gateway is given to build an http(s) URL with.
+pub fn token_metadata(
+    state: &AppState,
+    id: &str,
+    viewing_key: Option<&str>,
+    gateway: Option<&Gateway>,
+) -> Option<TokenMetadata> {
+    let nft = match reveal_view(state, id, viewing_key)? {
+        NftView::Full { nft, .. } => nft,
+        NftView::Redacted { .. } => return Some(TokenMetadata::placeholder()),
+    };
+    let cid = &nft.metadata.image_cid;
+    let image = match gateway {
+        _ if cid.is_empty() => String::new(),
+        Some(gateway) => gateway.url(cid),
+        None => format!("ipfs://{}", cid),
+    };
+    Some(TokenMetadata {
+        name: nft.metadata.name,
+        description: nft.metadata.description,
+        image,
+        attributes: decode_attributes(&nft.metadata.attributes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mint::mint_nft, testutil};
+    use serde_json::json;
+
+    #[test]
+    fn public_nft_has_the_erc721_shape() {
+        let (mut state, _) = testutil::state();
+        let mut item = testutil::item("ape");
+        item.metadata.attributes = r#"[{"trait_type":"eyes","value":"laser"}]"#.to_string();
+        let id = mint_nft(&mut state, "alice".to_string(), item).unwrap();
+
+        let doc = serde_json::to_value(token_metadata(&state, &id, None, None).unwrap()).unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "name": "ape",
+                "description": "test",
+                "image": format!("ipfs://{}", testutil::CID),
+                "attributes": [{"trait_type": "eyes", "value": "laser"}],
+            })
+        );
+        let gateway = Gateway::new("https://ipfs.io/ipfs/", false).unwrap();
+        let doc = token_metadata(&state, &id, None, Some(&gateway)).unwrap();
+        assert_eq!(doc.image, format!("https://ipfs.io/ipfs/{}", testutil::CID));
+        assert!(token_metadata(&state, "missing", None, None).is_none());
+    }
+
+    #[test]
+    fn shielded_nft_gets_the_placeholder_without_a_key() {
+        let (mut state, _) = testutil::state();
+        let mut item = testutil::item("hidden");
+        item.metadata.shielded = true;
+        let id = mint_nft(&mut state, "alice".to_string(), item).unwrap();
+
+        let doc = token_metadata(&state, &id, None, None).unwrap();
+        assert_eq!(doc.name, "Shielded NFT");
+        assert!(doc.image.is_empty() && doc.attributes.is_empty());
+        let doc = token_metadata(&state, &id, Some("viewing-key"), None).unwrap();
+        assert_eq!(doc.name, "hidden");
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    app::AppState,
+    attributes::{Attribute, AttributeValue},
+    error::NftError,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Traits every NFT minted into a collection must carry, and the values they
+// may take. Trait types it doesn't mention are unconstrained.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttributeSchema {
+    #[serde(default)]
+    pub required: Vec<String>,
+    // Trait type -> what its value may be.
+    #[serde(default)]
+    pub allowed: BTreeMap<String, TraitRule>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TraitRule {
+    // One of a fixed set of values, e.g. `{"one_of": ["common", "rare"]}`.
+    OneOf { one_of: Vec<AttributeValue> },
+    // A number within inclusive bounds, e.g. `{"min": 1, "max": 10}`.
+    Range { min: Option<f64>, max: Option<f64> },
+}
+
+impl AttributeSchema {
+    // Rejects schemas no NFT could satisfy.
+    pub fn validate(&self) -> Result<(), NftError> {
+        if self
+            .required
+            .iter()
+            .any(|trait_type| trait_type.trim().is_empty())
+        {
+            return Err(NftError::Invalid(
+                "required trait types must not be empty".into(),
+            ));
+        }
+        for (trait_type, rule) in &self.allowed {
+            match rule {
+                TraitRule::OneOf { one_of } if one_of.is_empty() => {
+                    return Err(NftError::Invalid(format!(
+                        "one_of for trait {} lists no values",
+                        trait_type
+                    )));
+                }
+                TraitRule::Range {
+                    min: Some(min),
+                    max: Some(max),
+                } if min > max => {
+                    return Err(NftError::Invalid(format!(
+                        "range for trait {} has min {} above max {}",
+                        trait_type, min, max
+                    )));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn check(&self, collection: &str, attrs: &[Attribute]) -> Result<(), NftError> {
+        for trait_type in &self.required {
+            if !attrs.iter().any(|attr| attr.trait_type == *trait_type) {
+                return Err(NftError::Invalid(format!(
+                    "collection {} requires trait {}",
+                    collection, trait_type
+                )));
+            }
+        }
+        for attr in attrs {
+            let Some(rule) = self.allowed.get(&attr.trait_type) else {
+                continue;
+            };
+            if !rule.allows(&attr.value) {
+                return Err(NftError::Invalid(format!(
+                    "{} is not an allowed value of trait {} in collection {}",
+                    describe(&attr.value),
+                    attr.trait_type,
+                    collection
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TraitRule {
+    fn allows(&self, value: &AttributeValue) -> bool {
+        match (self, value) {
+            (TraitRule::OneOf { one_of }, value) => one_of.contains(value),
+            (TraitRule::Range { min, max }, AttributeValue::Number(n)) => {
+                min.is_none_or(|min| *n >= min) && max.is_none_or(|max| *n <= max)
+            }
+            (TraitRule::Range { .. }, _) => false,
+        }
+    }
+}
+
+fn describe(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::Bool(b) => b.to_string(),
+        AttributeValue::Number(n) => n.to_string(),
+        AttributeValue::String(s) => format!("{:?}", s),
+    }
+}
+
+// Replaces the schema for `collection`. NFTs already in it aren't rechecked.
+pub fn set_schema(
+    state: &mut AppState,
+    collection: &str,
+    schema: AttributeSchema,
+) -> Result<(), NftError> {
+    if collection.trim().is_empty() {
+        return Err(NftError::Invalid(
+            "collection name must not be empty".into(),
+        ));
+    }
+    schema.validate()?;
+    state
+        .attribute_schemas
+        .insert(collection.to_string(), schema);
+    Ok(())
+}
+
+// Checks `attrs` against the collection's schema; collections without one
+// accept anything.
+pub fn check_attributes(
+    state: &AppState,
+    collection: Option<&str>,
+    attrs: &[Attribute],
+) -> Result<(), NftError> {
+    let Some(collection) = collection else {
+        return Ok(());
+    };
+    match state.attribute_schemas.get(collection) {
+        Some(schema) => schema.check(collection, attrs),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mint::mint_nft, testutil};
+    use serde_json::json;
+
+    fn mint_with(
+        state: &mut AppState,
+        collection: &str,
+        attributes: &str,
+    ) -> Result<String, NftError> {
+        let mut item = testutil::item("member");
+        item.extras.collection = Some(collection.to_string());
+        item.metadata.attributes = attributes.to_string();
+        mint_nft(state, "alice".to_string(), item)
+    }
+
+    fn schema() -> AttributeSchema {
+        serde_json::from_value(json!({
+            "required": ["rarity"],
+            "allowed": {
+                "rarity": { "one_of": ["common", "rare"] },
+                "level": { "min": 1, "max": 10 },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn conforming_mint_is_accepted() {
+        let (mut state, _) = testutil::state();
+        set_schema(&mut state, "apes", schema()).unwrap();
+        let attributes = r#"[{"trait_type":"rarity","value":"rare"},{"trait_type":"level","value":10},{"trait_type":"hat","value":"any"}]"#;
+        mint_with(&mut state, "apes", attributes).unwrap();
+        // Collections without a schema stay unconstrained.
+        mint_with(&mut state, "cats", "[]").unwrap();
+    }
+
+    #[test]
+    fn missing_required_trait_is_rejected() {
+        let (mut state, _) = testutil::state();
+        set_schema(&mut state, "apes", schema()).unwrap();
+        let err = mint_with(&mut state, "apes", r#"[{"trait_type":"level","value":3}]"#);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        assert!(state.ledger.nfts.is_empty());
+    }
+
+    #[test]
+    fn values_outside_the_rules_are_rejected() {
+        let (mut state, _) = testutil::state();
+        set_schema(&mut state, "apes", schema()).unwrap();
+        for attributes in [
+            r#"[{"trait_type":"rarity","value":"legendary"}]"#,
+            r#"[{"trait_type":"rarity","value":"rare"},{"trait_type":"level","value":11}]"#,
+            r#"[{"trait_type":"rarity","value":"rare"},{"trait_type":"level","value":"high"}]"#,
+        ] {
+            let err = mint_with(&mut state, "apes", attributes);
+            assert!(matches!(err, Err(NftError::Invalid(_))), "{}", attributes);
+        }
+        assert!(state.ledger.nfts.is_empty());
+    }
+
+    #[test]
+    fn unsatisfiable_schemas_are_refused() {
+        let (mut state, _) = testutil::state();
+        for schema in [
+            json!({ "required": [" "] }),
+            json!({ "allowed": { "rarity": { "one_of": [] } } }),
+            json!({ "allowed": { "level": { "min": 5, "max": 1 } } }),
+        ] {
+            let schema: AttributeSchema = serde_json::from_value(schema).unwrap();
+            assert!(set_schema(&mut state, "apes", schema).is_err());
+        }
+    }
+}
@@ -0,0 +1,627 @@
+use crate::{
+    app::AppState,
+    burn::ensure_not_burned,
+    cooldown::ensure_cooldown_elapsed,
+    error::NftError,
+    events::EventKind,
+    expiry::ensure_not_expired,
+    extras::OwnershipRecord,
+    freeze::ensure_not_frozen,
+    ibc::ensure_not_exported,
+    nonce::{accept_nonce, check_nonce},
+    offer::{ensure_offer_allows, OfferLock},
+    reveal::is_revealed,
+    shares::ensure_not_shared,
+    signature::{
+        batch_transfer_message, drain_message, transfer_from_message, transfer_message,
+        verify_signature,
+    },
+    staking::is_staked,
+    telemetry,
+};
+use penumbra_nft::{airdrop, transfer, types::NFT};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub fn transfer_nft(state: &mut AppState, id: &str, to: &str) -> Result<(), NftError> {
+    let from = current_owner(state, id)?;
+    ensure_transferable(state, id, to)?;
+    transfer::transfer_nft(&mut state.ledger, id, to).map_err(NftError::Invalid)?;
+    after_owner_change(state, EventKind::Transfer, id, &from);
+    metrics::counter!(telemetry::TRANSFERS).increment(1);
+    Ok(())
+}
+
+// A transfer authorized by the owner's ed25519 signature over
+// `(id, to, nonce)`. The nonce must exceed the last one accepted from the
+// owner, so a captured request can't be replayed.
+pub fn transfer_signed(
+    state: &mut AppState,
+    id: &str,
+    from: &str,
+    to: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    check_transfer_signed(state, id, from, to, nonce, signature)?;
+    transfer_nft(state, id, to)?;
+    accept_nonce(state, from, nonce);
+    Ok(())
+}
+
+// Every check `transfer_signed` makes before moving anything; used alone for
+// dry runs. Rules enforced inside penumbra_nft itself can't be checked here.
+pub fn check_transfer_signed(
+    state: &AppState,
+    id: &str,
+    from: &str,
+    to: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    if current_owner(state, id)? != from {
+        return Err(NftError::Forbidden(format!(
+            "{} is not the owner of NFT {}",
+            from, id
+        )));
+    }
+    verify_signature(
+        from,
+        &transfer_message(&state.tenant, id, to, nonce),
+        signature,
+    )?;
+    check_nonce(state, from, nonce)?;
+    ensure_transferable(state, id, to)
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchOutcome {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// Best effort: one signature by `from` over the whole batch authorizes it,
+// then each id is moved or fails on its own (not owned, frozen, expired, ...)
+// without affecting the rest. The nonce is used up once the signature checks
+// out, whatever happens to individual ids.
+pub fn transfer_nft_batch(
+    state: &mut AppState,
+    ids: &[String],
+    from: &str,
+    to: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<BTreeMap<String, BatchOutcome>, NftError> {
+    if ids.is_empty() {
+        return Err(NftError::Invalid(
+            "batch transfer needs at least one id".into(),
+        ));
+    }
+    verify_signature(
+        from,
+        &batch_transfer_message(&state.tenant, ids, to, nonce),
+        signature,
+    )?;
+    check_nonce(state, from, nonce)?;
+    accept_nonce(state, from, nonce);
+    let mut results = BTreeMap::new();
+    for id in ids {
+        let result = match current_owner(state, id) {
+            Ok(owner) if owner != from => Err(NftError::Forbidden(format!(
+                "{} is not the owner of NFT {}",
+                from, id
+            ))),
+            Ok(_) => transfer_nft(state, id, to),
+            Err(err) => Err(err),
+        };
+        let outcome = match result {
+            Ok(()) => BatchOutcome {
+                ok: true,
+                error: None,
+            },
+            Err(err) => BatchOutcome {
+                ok: false,
+                error: Some(err.to_string()),
+            },
+        };
+        results.insert(id.clone(), outcome);
+    }
+    Ok(results)
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DrainReport {
+    pub moved: usize,
+    pub skipped: usize,
+    // Skipped id -> why it stayed behind.
+    pub reasons: BTreeMap<String, String>,
+}
+
+// Moves every NFT `from` owns to `to`, authorized by one signature over
+// `(to, nonce)`. Staked and frozen NFTs, and any the transfer itself refuses,
+// stay behind and are reported; the rest move regardless.
+pub fn transfer_all(
+    state: &mut AppState,
+    from: &str,
+    to: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<DrainReport, NftError> {
+    verify_signature(from, &drain_message(&state.tenant, to, nonce), signature)?;
+    check_nonce(state, from, nonce)?;
+    accept_nonce(state, from, nonce);
+    let mut ids: Vec<String> = state.owner_index.ids(from).cloned().collect();
+    ids.sort();
+    let mut report = DrainReport::default();
+    for id in ids {
+        match transfer_nft(state, &id, to) {
+            Ok(()) => report.moved += 1,
+            Err(reason) => {
+                report.skipped += 1;
+                report.reasons.insert(id, reason.to_string());
+            }
+        }
+    }
+    Ok(report)
+}
+
+// Moves an NFT on behalf of `from`. `caller` must be the owner, signing
+// `transfer_message`, or the approved spender, signing
+// `transfer_from_message`; either way the nonce is the caller's own.
+pub fn transfer_from(
+    state: &mut AppState,
+    id: &str,
+    from: &str,
+    to: &str,
+    caller: &str,
+    nonce: u64,
+    signature: &str,
+) -> Result<(), NftError> {
+    if current_owner(state, id)? != from {
+        return Err(NftError::Forbidden(format!(
+            "{} is not the owner of NFT {}",
+            from, id
+        )));
+    }
+    let approved = state
+        .approvals
+        .get(id)
+        .is_some_and(|spender| spender == caller);
+    let message = if caller == from {
+        transfer_message(&state.tenant, id, to, nonce)
+    } else if approved {
+        transfer_from_message(&state.tenant, id, from, to, nonce)
+    } else {
+        return Err(NftError::Forbidden(format!(
+            "{} is neither the owner nor approved for NFT {}",
+            caller, id
+        )));
+    };
+    verify_signature(caller, &message, signature)?;
+    check_nonce(state, caller, nonce)?;
+    transfer_nft(state, id, to)?;
+    accept_nonce(state, caller, nonce);
+    Ok(())
+}
+
+// An existing NFT has a single owner, so it can only be airdropped to exactly
+// one recipient. Copies for many recipients go through `batch::airdrop_mint`.
+pub fn airdrop_nft(
+    state: &mut AppState,
+    id: &str,
+    recipients: Vec<String>,
+) -> Result<(), NftError> {
+    if recipients.len() != 1 {
+        return Err(NftError::Invalid(format!(
+            "airdropping NFT {} needs exactly one recipient, got {}; use POST /airdrop/mint to mint a copy per recipient",
+            id,
+            recipients.len()
+        )));
+    }
+    let from = current_owner(state, id)?;
+    ensure_transferable(state, id, &recipients[0])?;
+    airdrop::airdrop_nft(&mut state.ledger, id, recipients).map_err(NftError::Invalid)?;
+    after_owner_change(state, EventKind::Airdrop, id, &from);
+    Ok(())
+}
+
+// The rules every ownership change has to pass, whoever authorizes it. A
+// staked NFT stays with the owner earning on it until unstaked, as it does
+// for burns.
+fn ensure_transferable(state: &AppState, id: &str, to: &str) -> Result<(), NftError> {
+    ensure_not_burned(state, id)?;
+    if is_staked(state, id) {
+        return Err(NftError::Locked(format!(
+            "NFT {} is staked; unstake it before transferring",
+            id
+        )));
+    }
+    ensure_not_shared(state, id)?;
+    ensure_not_frozen(state, id)?;
+    ensure_not_exported(state, id)?;
+    ensure_not_expired(state, id)?;
+    ensure_cooldown_elapsed(state, id)?;
+    ensure_offer_allows(state, id, to)
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferPreview {
+    #[serde(flatten)]
+    pub nft: NFT,
+    pub owner_history: Vec<OwnershipRecord>,
+    // Both always cleared by a transfer; included so UIs can show that.
+    pub approved: Option<String>,
+    pub offer: Option<OfferLock>,
+}
+
+// The record `transfer_nft(state, id, to)` would leave behind, built on a
+// copy: the same rules are checked, but nothing in `state` changes. Shielded
+// NFTs need a viewing key that reveals them, as for /view.
+pub fn transfer_preview(
+    state: &AppState,
+    id: &str,
+    to: &str,
+    viewing_key: Option<&str>,
+) -> Result<TransferPreview, NftError> {
+    let mut nft = state
+        .ledger
+        .get_nft(id)
+        .cloned()
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))?;
+    if !is_revealed(state, &nft, viewing_key) {
+        return Err(NftError::Forbidden(format!(
+            "NFT {} is shielded; not authorized to preview it without its viewing key",
+            id
+        )));
+    }
+    ensure_transferable(state, id, to)?;
+    let mut owner_history = owner_history(state, id)?;
+    owner_history.push(OwnershipRecord {
+        owner: to.to_string(),
+        acquired_at: Some(state.now()),
+    });
+    nft.owner = to.to_string();
+    Ok(TransferPreview {
+        nft,
+        owner_history,
+        approved: None,
+        offer: None,
+    })
+}
+
+fn current_owner(state: &AppState, id: &str) -> Result<String, NftError> {
+    state
+        .ledger
+        .get_nft(id)
+        .map(|nft| nft.owner.clone())
+        .ok_or_else(|| NftError::NotFound(format!("NFT {} not found", id)))
+}
+
+// Bookkeeping shared by every path that changes an NFT's owner.
+fn after_owner_change(state: &mut AppState, kind: EventKind, id: &str, from: &str) {
+    state.approvals.remove(id);
+    if let Some(extras) = state.extras.get_mut(id) {
+        extras.offer = None;
+    }
+    let to = state.ledger.get_nft(id).map(|nft| nft.owner.clone());
+    if let Some(to) = &to {
+        state.owner_index.move_nft(id, from, to);
+        let now = state.now();
+        let history = &mut state.extras_mut(id).owner_history;
+        if history.is_empty() {
+            history.push(OwnershipRecord {
+                owner: from.to_string(),
+                acquired_at: None,
+            });
+        }
+        history.push(OwnershipRecord {
+            owner: to.clone(),
+            acquired_at: Some(now),
+        });
+    }
+    state.record(kind, id, Some(from), to.as_deref());
+}
+
+// The ordered chain of owners for an NFT.
+pub fn owner_history(state: &AppState, id: &str) -> Result<Vec<OwnershipRecord>, NftError> {
+    let owner = current_owner(state, id)?;
+    let history = state
+        .extras
+        .get(id)
+        .map(|extras| extras.owner_history.clone())
+        .unwrap_or_default();
+    if history.is_empty() {
+        return Ok(vec![OwnershipRecord {
+            owner,
+            acquired_at: None,
+        }]);
+    }
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::IdScheme,
+        staking::stake_nft,
+        testutil::{self, Key},
+    };
+
+    fn owner_of(state: &AppState, id: &str) -> String {
+        state.ledger.get_nft(id).unwrap().owner.clone()
+    }
+
+    #[test]
+    fn signed_transfer_moves_the_nft() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = alice.sign(&transfer_message("", &id, "bob", 1));
+        transfer_signed(&mut state, &id, &alice.address(), "bob", 1, &signature).unwrap();
+        assert_eq!(owner_of(&state, &id), "bob");
+        assert_eq!(state.nonces[&alice.address()], 1);
+    }
+
+    #[test]
+    fn a_signature_from_one_tenant_doesnt_replay_in_another() {
+        let alice = Key::new(1);
+        let tenant = |name: &str| {
+            let (mut state, _) = testutil::state();
+            state.tenant = name.to_string();
+            // So both tenants assign the same id.
+            state.id_scheme = IdScheme::ContentHash;
+            let id = testutil::mint(&mut state, &alice.address());
+            (state, id)
+        };
+        let (mut a, id) = tenant("a");
+        let (mut b, same_id) = tenant("b");
+        assert_eq!(id, same_id);
+        let signature = alice.sign(&transfer_message("a", &id, "bob", 1));
+
+        let err = transfer_signed(&mut b, &id, &alice.address(), "bob", 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(owner_of(&b, &id), alice.address());
+        transfer_signed(&mut a, &id, &alice.address(), "bob", 1, &signature).unwrap();
+        assert_eq!(owner_of(&a, &id), "bob");
+    }
+
+    #[test]
+    fn history_lists_every_owner_in_order() {
+        let (mut state, clock) = testutil::state();
+        let (alice, bob) = (Key::new(1), Key::new(2));
+        let id = testutil::mint(&mut state, &alice.address());
+        clock.advance(10);
+        let signature = alice.sign(&transfer_message("", &id, &bob.address(), 1));
+        transfer_signed(
+            &mut state,
+            &id,
+            &alice.address(),
+            &bob.address(),
+            1,
+            &signature,
+        )
+        .unwrap();
+        clock.advance(10);
+        let signature = bob.sign(&transfer_message("", &id, "carol", 1));
+        transfer_signed(&mut state, &id, &bob.address(), "carol", 1, &signature).unwrap();
+
+        let history: Vec<(String, Option<u64>)> = owner_history(&state, &id)
+            .unwrap()
+            .into_iter()
+            .map(|record| (record.owner, record.acquired_at))
+            .collect();
+        let start = testutil::START;
+        assert_eq!(
+            history,
+            [
+                (alice.address(), Some(start)),
+                (bob.address(), Some(start + 10)),
+                ("carol".to_string(), Some(start + 20)),
+            ]
+        );
+    }
+
+    #[test]
+    fn airdrop_of_an_existing_nft_needs_exactly_one_recipient() {
+        let (mut state, _) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        for recipients in [vec![], vec!["bob".to_string(), "carol".to_string()]] {
+            let err = airdrop_nft(&mut state, &id, recipients);
+            assert!(matches!(err, Err(NftError::Invalid(_))));
+            assert_eq!(owner_of(&state, &id), "alice");
+        }
+        airdrop_nft(&mut state, &id, vec!["bob".to_string()]).unwrap();
+        assert_eq!(owner_of(&state, &id), "bob");
+    }
+
+    #[test]
+    fn dry_run_reports_without_transferring() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = alice.sign(&transfer_message("", &id, "bob", 1));
+        check_transfer_signed(&state, &id, &alice.address(), "bob", 1, &signature).unwrap();
+        assert_eq!(owner_of(&state, &id), alice.address());
+        assert!(state.nonces.is_empty());
+        let err = check_transfer_signed(&state, &id, "bob", "carol", 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+    }
+
+    #[test]
+    fn wrong_key_signature_is_rejected() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let signature = Key::new(2).sign(&transfer_message("", &id, "bob", 1));
+        let err = transfer_signed(&mut state, &id, &alice.address(), "bob", 1, &signature);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(owner_of(&state, &id), alice.address());
+        assert!(state.nonces.is_empty());
+    }
+
+    #[test]
+    fn replayed_nonce_is_rejected() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let other = testutil::mint(&mut state, &alice.address());
+        let signature = alice.sign(&transfer_message("", &id, "bob", 1));
+        transfer_signed(&mut state, &id, &alice.address(), "bob", 1, &signature).unwrap();
+        let signature = alice.sign(&transfer_message("", &other, "bob", 1));
+        let err = transfer_signed(&mut state, &other, &alice.address(), "bob", 1, &signature);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+        assert_eq!(owner_of(&state, &other), alice.address());
+    }
+
+    #[test]
+    fn transfer_from_by_owner_needs_the_owner_signature() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let owner = alice.address();
+        let id = testutil::mint(&mut state, &owner);
+        let forged = Key::new(2).sign(&transfer_message("", &id, "bob", 1));
+        assert!(transfer_from(&mut state, &id, &owner, "bob", &owner, 1, &forged).is_err());
+        let signature = alice.sign(&transfer_message("", &id, "bob", 1));
+        transfer_from(&mut state, &id, &owner, "bob", &owner, 1, &signature).unwrap();
+        assert_eq!(owner_of(&state, &id), "bob");
+    }
+
+    #[test]
+    fn approved_spender_signs_with_its_own_nonce() {
+        let (mut state, _) = testutil::state();
+        let owner = Key::new(1).address();
+        let spender = Key::new(2);
+        let id = testutil::mint(&mut state, &owner);
+        state.approvals.insert(id.clone(), spender.address());
+        let message = transfer_from_message("", &id, &owner, "carol", 7);
+        transfer_from(
+            &mut state,
+            &id,
+            &owner,
+            "carol",
+            &spender.address(),
+            7,
+            &spender.sign(&message),
+        )
+        .unwrap();
+        assert_eq!(owner_of(&state, &id), "carol");
+        assert_eq!(state.nonces[&spender.address()], 7);
+        assert!(!state.nonces.contains_key(&owner));
+        // The transfer cleared the approval.
+        assert!(!state.approvals.contains_key(&id));
+    }
+
+    #[test]
+    fn unapproved_caller_is_rejected_even_when_signed() {
+        let (mut state, _) = testutil::state();
+        let owner = Key::new(1).address();
+        let mallory = Key::new(3);
+        let id = testutil::mint(&mut state, &owner);
+        let message = transfer_from_message("", &id, &owner, "mallory", 1);
+        let err = transfer_from(
+            &mut state,
+            &id,
+            &owner,
+            "mallory",
+            &mallory.address(),
+            1,
+            &mallory.sign(&message),
+        );
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert_eq!(owner_of(&state, &id), owner);
+    }
+
+    #[test]
+    fn staked_nft_is_refused_by_every_path() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let staked = testutil::mint(&mut state, &alice.address());
+        let free = testutil::mint(&mut state, &alice.address());
+        stake_nft(&mut state, &staked).unwrap();
+
+        let signature = alice.sign(&transfer_message("", &staked, "bob", 1));
+        let err = transfer_signed(&mut state, &staked, &alice.address(), "bob", 1, &signature);
+        assert!(matches!(err, Err(NftError::Locked(_))));
+
+        let signature = alice.sign(&drain_message("", "bob", 1));
+        let report = transfer_all(&mut state, &alice.address(), "bob", 1, &signature).unwrap();
+        assert_eq!((report.moved, report.skipped), (1, 1));
+        assert!(report.reasons[&staked].contains("staked"));
+        assert_eq!(owner_of(&state, &staked), alice.address());
+        assert_eq!(owner_of(&state, &free), "bob");
+    }
+
+    #[test]
+    fn preview_shows_the_new_owner_without_transferring() {
+        let (mut state, clock) = testutil::state();
+        let id = testutil::mint(&mut state, "alice");
+        state.approvals.insert(id.clone(), "spender".to_string());
+        clock.advance(5);
+        let events = state.events.len();
+
+        let preview = transfer_preview(&state, &id, "bob", None).unwrap();
+        assert_eq!(preview.nft.owner, "bob");
+        assert_eq!(preview.approved, None);
+        let last = preview.owner_history.last().unwrap();
+        assert_eq!(
+            (last.owner.as_str(), last.acquired_at),
+            ("bob", Some(testutil::START + 5))
+        );
+
+        assert_eq!(owner_of(&state, &id), "alice");
+        assert_eq!(owner_history(&state, &id).unwrap().len(), 1);
+        assert_eq!(state.approvals[&id], "spender");
+        assert_eq!(state.events.len(), events);
+    }
+
+    #[test]
+    fn drain_moves_the_transferable_and_explains_the_rest() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let free: Vec<String> = (0..2)
+            .map(|_| testutil::mint(&mut state, &alice.address()))
+            .collect();
+        let frozen = testutil::mint(&mut state, &alice.address());
+        let staked = testutil::mint(&mut state, &alice.address());
+        let others = testutil::mint(&mut state, "carol");
+        state.extras_mut(&frozen).frozen = true;
+        stake_nft(&mut state, &staked).unwrap();
+
+        let signature = alice.sign(&drain_message("", "bob", 1));
+        let report = transfer_all(&mut state, &alice.address(), "bob", 1, &signature).unwrap();
+        assert_eq!((report.moved, report.skipped), (2, 2));
+        assert!(report.reasons[&frozen].contains("frozen"));
+        assert!(report.reasons[&staked].contains("staked"));
+        assert!(free.iter().all(|id| owner_of(&state, id) == "bob"));
+        assert_eq!(owner_of(&state, &frozen), alice.address());
+        assert_eq!(owner_of(&state, &others), "carol");
+        assert_eq!(state.owner_index.ids(&alice.address()).count(), 2);
+
+        let err = transfer_all(&mut state, &alice.address(), "bob", 1, &signature);
+        assert!(matches!(err, Err(NftError::Invalid(_))));
+    }
+
+    #[test]
+    fn batch_moves_what_it_can_and_reports_the_rest() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let owned = testutil::mint(&mut state, &alice.address());
+        let frozen = testutil::mint(&mut state, &alice.address());
+        let not_owned = testutil::mint(&mut state, "carol");
+        state.extras_mut(&frozen).frozen = true;
+
+        let ids = vec![owned.clone(), frozen.clone(), not_owned.clone()];
+        let signature = alice.sign(&batch_transfer_message("", &ids, "bob", 1));
+        let results =
+            transfer_nft_batch(&mut state, &ids, &alice.address(), "bob", 1, &signature).unwrap();
+        assert!(results[&owned].ok);
+        assert!(!results[&frozen].ok);
+        assert!(results[&frozen].error.as_ref().unwrap().contains("frozen"));
+        assert!(!results[&not_owned].ok);
+        assert_eq!(owner_of(&state, &owned), "bob");
+        assert_eq!(owner_of(&state, &frozen), alice.address());
+        assert_eq!(owner_of(&state, &not_owned), "carol");
+    }
+}
@@ -0,0 +1,287 @@
+use crate::{
+    app::AppState,
+    burn::is_burned,
+    error::NftError,
+    extras::NftExtras,
+    freeze::freeze_nft,
+    staking::{stake_signed, StakeInfo},
+    transfer::transfer_signed,
+};
+use penumbra_nft::types::NFT;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Operation {
+    // Signed exactly as for POST /transfer.
+    Transfer {
+        #[serde(deserialize_with = "crate::ids::normalized")]
+        id: String,
+        from: String,
+        to: String,
+        nonce: u64,
+        signature: String,
+    },
+    // Signed exactly as for POST /stake/:id.
+    Stake {
+        #[serde(deserialize_with = "crate::ids::normalized")]
+        id: String,
+        caller: String,
+        nonce: u64,
+        signature: String,
+    },
+    // Signed exactly as for POST /freeze/:id.
+    Freeze {
+        #[serde(deserialize_with = "crate::ids::normalized")]
+        id: String,
+        caller: String,
+        nonce: u64,
+        signature: String,
+    },
+}
+
+#[derive(Serialize)]
+pub struct OperationResult {
+    pub index: usize,
+    pub id: String,
+    pub status: &'static str,
+}
+
+// What restoring a touched NFT needs: its ledger entry, extras, approval and
+// stake as they were before the transaction.
+struct Saved {
+    nft: Option<NFT>,
+    extras: Option<NftExtras>,
+    approval: Option<String>,
+    stake: Option<StakeInfo>,
+}
+
+// Pre-transaction copies of everything the operations may change.
+#[derive(Default)]
+pub struct Snapshot {
+    nfts: HashMap<String, Saved>,
+    nonces: HashMap<String, Option<u64>>,
+    events: usize,
+}
+
+impl Snapshot {
+    // Call before the first change to `id`.
+    pub fn touch(&mut self, state: &AppState, id: &str) {
+        self.nfts.entry(id.to_string()).or_insert_with(|| Saved {
+            nft: state.ledger.get_nft(id).cloned(),
+            extras: state.extras.get(id).cloned(),
+            approval: state.approvals.get(id).cloned(),
+            stake: state.staking.get(id).cloned(),
+        });
+    }
+
+    pub fn touch_nonce(&mut self, state: &AppState, owner: &str) {
+        self.nonces
+            .entry(owner.to_string())
+            .or_insert_with(|| state.nonces.get(owner).copied());
+    }
+
+    fn restore(self, state: &mut AppState) {
+        for (id, saved) in self.nfts {
+            if let Some(current) = state.ledger.nfts.remove(&id) {
+                state.owner_index.remove(&current.owner, &id);
+            }
+            restore_entry(&mut state.extras, &id, saved.extras);
+            if let Some(nft) = saved.nft {
+                if !is_burned(state, &id) {
+                    state.owner_index.insert(&nft.owner, &id);
+                }
+                state.ledger.nfts.insert(id.clone(), nft);
+            }
+            restore_entry(&mut state.approvals, &id, saved.approval);
+            restore_entry(&mut state.staking, &id, saved.stake);
+        }
+        for (owner, nonce) in self.nonces {
+            restore_entry(&mut state.nonces, &owner, nonce);
+        }
+        state.events.truncate(self.events);
+    }
+}
+
+fn restore_entry<V>(map: &mut HashMap<String, V>, key: &str, saved: Option<V>) {
+    match saved {
+        Some(value) => map.insert(key.to_string(), value),
+        None => map.remove(key),
+    };
+}
+
+// Applies `operations` in order, all or nothing. On the first failure every
+// change made so far is undone and the error names the failing index.
+pub fn apply_tx(
+    state: &mut AppState,
+    operations: Vec<Operation>,
+) -> Result<Vec<OperationResult>, NftError> {
+    if operations.is_empty() {
+        return Err(NftError::Invalid("transaction has no operations".into()));
+    }
+    atomically(state, |state, snapshot| {
+        let mut results = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.into_iter().enumerate() {
+            let (id, status) = apply(state, snapshot, operation)
+                .map_err(|err| err.prefixed(format_args!("operation {}", index)))?;
+            results.push(OperationResult { index, id, status });
+        }
+        Ok(results)
+    })
+}
+
+// Runs `f`, undoing everything it changed if it fails. `f` must `touch` each
+// NFT and nonce owner before changing them. Events reach live subscribers
+// only once `f` has succeeded.
+pub fn atomically<T>(
+    state: &mut AppState,
+    f: impl FnOnce(&mut AppState, &mut Snapshot) -> Result<T, NftError>,
+) -> Result<T, NftError> {
+    let mut snapshot = Snapshot {
+        events: state.events.len(),
+        ..Default::default()
+    };
+    state.hold_feed = true;
+    let result = f(state, &mut snapshot);
+    state.hold_feed = false;
+    if result.is_err() {
+        snapshot.restore(state);
+        return result;
+    }
+    for event in state.events.since(snapshot.events as u64) {
+        let _ = state.event_feed.send(event.clone());
+    }
+    result
+}
+
+fn apply(
+    state: &mut AppState,
+    snapshot: &mut Snapshot,
+    operation: Operation,
+) -> Result<(String, &'static str), NftError> {
+    match operation {
+        Operation::Transfer {
+            id,
+            from,
+            to,
+            nonce,
+            signature,
+        } => {
+            snapshot.touch(state, &id);
+            snapshot.touch_nonce(state, &from);
+            transfer_signed(state, &id, &from, &to, nonce, &signature)?;
+            Ok((id, "transferred"))
+        }
+        Operation::Stake {
+            id,
+            caller,
+            nonce,
+            signature,
+        } => {
+            snapshot.touch(state, &id);
+            snapshot.touch_nonce(state, &caller);
+            stake_signed(state, &id, &caller, nonce, &signature)?;
+            Ok((id, "staked"))
+        }
+        Operation::Freeze {
+            id,
+            caller,
+            nonce,
+            signature,
+        } => {
+            snapshot.touch(state, &id);
+            snapshot.touch_nonce(state, &caller);
+            freeze_nft(state, &id, &caller, nonce, &signature)?;
+            Ok((id, "frozen"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        owners::OwnerIndex,
+        signature::{action_message, transfer_message},
+        testutil::{self, Key},
+    };
+
+    fn stake(key: &Key, id: &str, nonce: u64) -> Operation {
+        Operation::Stake {
+            id: id.to_string(),
+            caller: key.address(),
+            nonce,
+            signature: key.sign(&action_message("", "stake", id, nonce)),
+        }
+    }
+
+    fn transfer(key: &Key, id: &str, to: &str, nonce: u64) -> Operation {
+        Operation::Transfer {
+            id: id.to_string(),
+            from: key.address(),
+            to: to.to_string(),
+            nonce,
+            signature: key.sign(&transfer_message("", id, to, nonce)),
+        }
+    }
+
+    #[test]
+    fn every_operation_applies() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let moved = testutil::mint(&mut state, &alice.address());
+        let frozen = testutil::mint(&mut state, &alice.address());
+        let staked = testutil::mint(&mut state, &alice.address());
+        let operations = vec![
+            transfer(&alice, &moved, "bob", 1),
+            Operation::Freeze {
+                id: frozen.clone(),
+                caller: alice.address(),
+                nonce: 2,
+                signature: alice.sign(&action_message("", "freeze", &frozen, 2)),
+            },
+            stake(&alice, &staked, 3),
+        ];
+        let results = apply_tx(&mut state, operations).unwrap();
+        let statuses: Vec<&str> = results.iter().map(|result| result.status).collect();
+        assert_eq!(statuses, ["transferred", "frozen", "staked"]);
+        assert_eq!(state.ledger.get_nft(&moved).unwrap().owner, "bob");
+        assert!(state.extras[&frozen].frozen);
+        assert!(state.staking.contains_key(&staked));
+        assert_eq!(state.nonces[&alice.address()], 3);
+    }
+
+    #[test]
+    fn stake_must_be_signed_by_the_owner() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let mallory = Key::new(2);
+        let id = testutil::mint(&mut state, &alice.address());
+        let err = apply_tx(&mut state, vec![stake(&mallory, &id, 1)]);
+        assert!(matches!(err, Err(NftError::Forbidden(_))));
+        assert!(!state.staking.contains_key(&id));
+    }
+
+    #[test]
+    fn failing_operation_rolls_back_the_rest() {
+        let (mut state, _) = testutil::state();
+        let alice = Key::new(1);
+        let id = testutil::mint(&mut state, &alice.address());
+        let other = testutil::mint(&mut state, &alice.address());
+        let events = state.events.len();
+        let operations = vec![
+            transfer(&alice, &id, "bob", 1),
+            stake(&alice, &other, 2),
+            stake(&alice, "missing", 3),
+        ];
+        let err = apply_tx(&mut state, operations);
+        assert!(matches!(err, Err(NftError::NotFound(msg)) if msg.contains("operation 2")));
+        assert_eq!(state.ledger.get_nft(&id).unwrap().owner, alice.address());
+        assert!(!state.ledger.get_nft(&other).unwrap().staked);
+        assert!(!state.staking.contains_key(&other));
+        assert!(!state.nonces.contains_key(&alice.address()));
+        assert_eq!(state.events.len(), events);
+        assert_eq!(state.owner_index, OwnerIndex::build(&state));
+    }
+}
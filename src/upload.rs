@@ -0,0 +1,71 @@
+use crate::cid::raw_cid;
+use std::{io, path::PathBuf};
+
+// Image types accepted by POST /mint/upload, with the leading bytes each
+// file must start with; the declared type alone isn't trusted.
+const IMAGE_TYPES: &[(&str, &[u8])] = &[
+    ("image/png", b"\x89PNG\r\n\x1a\n"),
+    ("image/jpeg", b"\xff\xd8\xff"),
+    ("image/gif", b"GIF8"),
+    ("image/webp", b"RIFF"),
+];
+
+// Uploaded images, stored under their own CID so they can be pinned to IPFS
+// later without changing any NFT.
+pub struct BlobStore {
+    pub dir: PathBuf,
+    pub max_bytes: usize,
+}
+
+impl BlobStore {
+    // Checks the upload and writes it to `<dir>/<cid>`, returning the CID.
+    // Identical uploads land on the same file.
+    pub async fn put(
+        &self,
+        content_type: Option<&str>,
+        bytes: &[u8],
+    ) -> Result<String, UploadError> {
+        if bytes.len() > self.max_bytes {
+            return Err(UploadError::TooLarge(format!(
+                "image is {} bytes; the limit is {}",
+                bytes.len(),
+                self.max_bytes
+            )));
+        }
+        check_image_type(content_type, bytes).map_err(UploadError::Invalid)?;
+        let cid = raw_cid(bytes);
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.dir.join(&cid);
+        if !tokio::fs::try_exists(&path).await? {
+            let tmp = path.with_extension("tmp");
+            tokio::fs::write(&tmp, bytes).await?;
+            tokio::fs::rename(&tmp, &path).await?;
+        }
+        Ok(cid)
+    }
+}
+
+#[derive(Debug)]
+pub enum UploadError {
+    Invalid(String),
+    TooLarge(String),
+    Io(io::Error),
+}
+
+impl From<io::Error> for UploadError {
+    fn from(err: io::Error) -> Self {
+        UploadError::Io(err)
+    }
+}
+
+fn check_image_type(content_type: Option<&str>, bytes: &[u8]) -> Result<(), String> {
+    let content_type = content_type.ok_or("image needs a content type")?;
+    let (_, magic) = IMAGE_TYPES
+        .iter()
+        .find(|(name, _)| *name == content_type)
+        .ok_or_else(|| format!("unsupported image type {}", content_type))?;
+    if !bytes.starts_with(magic) {
+        return Err(format!("image content does not match {}", content_type));
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::negotiate::Format;
+use axum::body::Bytes;
+use lru::LruCache;
+use std::{
+    num::NonZeroUsize,
+    sync::{Mutex, MutexGuard},
+};
+
+// Encoded /view responses, looked up before the state lock is taken.
+// `AppState::record` evicts an NFT's entries whenever it changes, so an entry
+// present here always matches the NFT's current version.
+pub struct ViewCache {
+    // None when caching is disabled.
+    entries: Option<Mutex<LruCache<ViewKey, CachedView>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ViewKey {
+    pub id: String,
+    // Different keys can reveal different things, so each gets its own entry.
+    pub viewing_key: Option<String>,
+    pub format: Format,
+}
+
+#[derive(Clone)]
+pub struct CachedView {
+    pub etag: String,
+    pub body: Bytes,
+}
+
+impl ViewCache {
+    // Holds up to `capacity` responses; 0 disables the cache.
+    pub fn new(capacity: usize) -> Self {
+        ViewCache {
+            entries: NonZeroUsize::new(capacity).map(|cap| Mutex::new(LruCache::new(cap))),
+        }
+    }
+
+    pub fn get(&self, key: &ViewKey) -> Option<CachedView> {
+        self.lock()?.get(key).cloned()
+    }
+
+    pub fn insert(&self, key: ViewKey, view: CachedView) {
+        if let Some(mut entries) = self.lock() {
+            entries.put(key, view);
+        }
+    }
+
+    // Drops every cached response for `id`, whatever key or format.
+    pub fn invalidate(&self, id: &str) {
+        if let Some(mut entries) = self.lock() {
+            let stale: Vec<ViewKey> = entries
+                .iter()
+                .filter(|(key, _)| key.id == id)
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in stale {
+                entries.pop(&key);
+            }
+        }
+    }
+
+    // A panic mid-update may have left entries that missed an invalidation,
+    // so a poisoned cache is emptied and used again rather than abandoned.
+    fn lock(&self) -> Option<MutexGuard<'_, LruCache<ViewKey, CachedView>>> {
+        let entries = self.entries.as_ref()?;
+        Some(entries.lock().unwrap_or_else(|poisoned| {
+            entries.clear_poison();
+            let mut guard = poisoned.into_inner();
+            guard.clear();
+            guard
+        }))
+    }
+}
+
+impl Default for ViewCache {
+    fn default() -> Self {
+        ViewCache::new(0)
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{events::NftEvent, SharedState};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+// Hex HMAC-SHA256 of the request body under the shared secret.
+pub const SIGNATURE_HEADER: &str = "x-pnft-signature";
+const TIMEOUT: Duration = Duration::from_secs(10);
+const FIRST_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+// POSTs every recorded event, as its `NftEvent` JSON, to one configured URL.
+pub struct Webhook {
+    url: reqwest::Url,
+    secret: Vec<u8>,
+    max_retries: u32,
+    client: reqwest::Client,
+}
+
+impl Webhook {
+    // `url` must be an absolute http(s) URL; checked once at startup.
+    pub fn new(url: &str, secret: &str, max_retries: u32) -> Result<Self, String> {
+        let url = reqwest::Url::parse(url.trim())
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| format!("invalid webhook URL {:?}: expected an http(s) URL", url))?;
+        if secret.is_empty() {
+            return Err("webhook secret must not be empty".into());
+        }
+        let client = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .map_err(|e| format!("failed to build webhook client: {}", e))?;
+        Ok(Webhook {
+            url,
+            secret: secret.as_bytes().to_vec(),
+            max_retries,
+            client,
+        })
+    }
+
+    pub fn signature(&self, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC takes any key");
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    // Delivers events in order, starting with the first one recorded after
+    // it starts. The live feed only wakes it; what to send is read back from
+    // the event log, so a slow receiver that lags the feed still gets every
+    // event. Runs in the background, so handlers never wait on the receiver.
+    pub async fn run(self, state: SharedState) {
+        let (mut next, mut feed) = {
+            let state = state.read().await;
+            (state.events.len() as u64, state.event_feed.subscribe())
+        };
+        loop {
+            match feed.recv().await {
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return,
+            }
+            let pending = state.read().await.events.since(next).to_vec();
+            for event in pending {
+                next = event.seq;
+                self.deliver(&event).await;
+            }
+        }
+    }
+
+    // Retries with exponential backoff until the receiver answers 2xx or
+    // `max_retries` is used up, then gives up on this event.
+    async fn deliver(&self, event: &NftEvent) {
+        let body = serde_json::to_vec(event).expect("events serialize");
+        let signature = self.signature(&body);
+        let mut backoff = FIRST_BACKOFF;
+        for attempt in 0..=self.max_retries {
+            let result = self
+                .client
+                .post(self.url.clone())
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, format!("sha256={}", signature))
+                .body(body.clone())
+                .send()
+                .await;
+            let error = match result {
+                Ok(response) if response.status().is_success() => return,
+                Ok(response) => format!("receiver answered {}", response.status()),
+                Err(err) => err.to_string(),
+            };
+            if attempt == self.max_retries {
+                tracing::error!(seq = event.seq, "Giving up on webhook delivery: {}", error);
+                return;
+            }
+            tracing::warn!(
+                seq = event.seq,
+                attempt,
+                "Webhook delivery failed, retrying in {:?}: {}",
+                backoff,
+                error
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{events::EventKind, testutil};
+    use axum::{
+        body::Bytes,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
+    use std::sync::Arc;
+    use tokio::sync::{mpsc, RwLock};
+
+    #[tokio::test]
+    async fn mint_triggers_a_signed_callback() {
+        let (tx, mut received) = mpsc::unbounded_channel();
+        let receiver = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                let _ = tx.send((headers, body));
+                StatusCode::OK
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, receiver).await });
+
+        let state = Arc::new(RwLock::new(testutil::state().0));
+        let webhook = Webhook::new(&url, "shh", 0).unwrap();
+        tokio::spawn(webhook.run(state.clone()));
+        // Events recorded before it subscribes aren't sent.
+        while state.read().await.event_feed.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let id = testutil::mint(&mut *state.write().await, "alice");
+
+        let (headers, body) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"shh").unwrap();
+        mac.update(&body);
+        let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        assert_eq!(headers[SIGNATURE_HEADER], expected.as_str());
+        let event: NftEvent = serde_json::from_slice(&body).unwrap();
+        assert_eq!((event.kind, event.nft_id), (EventKind::Mint, id));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    events::{EventFilter, NftEvent},
+    SharedState,
+};
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
+use tokio::sync::broadcast::error::RecvError;
+
+// Sends events with `seq > since` from the log, then live events as they are
+// recorded, skipping any that don't pass `filter`. Backlog and subscription
+// are taken under one read lock, so nothing is missed or repeated in between.
+// A client that falls behind the broadcast buffer is disconnected with a lag
+// notice; producers never wait.
+pub async fn stream_events(
+    mut socket: WebSocket,
+    state: SharedState,
+    since: Option<u64>,
+    filter: EventFilter,
+) {
+    let (backlog, mut feed) = {
+        let state = state.read().await;
+        let backlog = since.map(|seq| state.events.query(seq, &filter, None));
+        (backlog.unwrap_or_default(), state.event_feed.subscribe())
+    };
+    for event in &backlog {
+        if send_event(&mut socket, event).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        tokio::select! {
+            event = feed.recv() => match event {
+                Ok(event) if !filter.matches(&event) => {}
+                Ok(event) => {
+                    if send_event(&mut socket, &event).await.is_err() {
+                        return;
+                    }
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    let frame = CloseFrame {
+                        code: close_code::AGAIN,
+                        reason: format!("lagged behind by {} events; reconnect with ?since=", missed).into(),
+                    };
+                    let _ = socket.send(Message::Close(Some(frame))).await;
+                    return;
+                }
+                Err(RecvError::Closed) => return,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn send_event(socket: &mut WebSocket, event: &NftEvent) -> Result<(), axum::Error> {
+    let json = serde_json::to_string(event).expect("events serialize");
+    socket.send(Message::Text(json)).await
+}
@@ -0,0 +1,30 @@
+// Runs the built binary's one-shot subcommands against a scratch state file.
+
+use std::{path::PathBuf, process::Command};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pnft-cli-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn mint_prints_the_new_id() {
+    let dir = scratch_dir("mint");
+    let output = Command::new(env!("CARGO_BIN_EXE_penumbra-nft-rpc"))
+        .current_dir(&dir)
+        .args(["mint", "--owner", "alice", "--name", "cli"])
+        .args(["--image-cid", "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let id = stdout.trim();
+    assert!(!id.is_empty() && !id.contains(char::is_whitespace));
+
+    let state = std::fs::read_to_string(dir.join("state.json")).unwrap();
+    let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+    assert_eq!(state["nfts"][id]["owner"], "alice");
+    std::fs::remove_dir_all(&dir).unwrap();
+}